@@ -0,0 +1,292 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::error::AppError;
+use crate::secrets;
+use crate::state::AppState;
+use crate::webview;
+
+pub const MAX_ENTRIES: usize = 1000;
+pub const DEFAULT_LIMIT: usize = 50;
+// Commands whose `value` argument is a secret.
+const SECRET_COMMANDS: &[&str] = &["secret_set"];
+// Commands that report their result after the invoke handler has returned.
+const ASYNC_COMMANDS: &[&str] = &["execute_js"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditResult {
+  Ok,
+  Err,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditEntry {
+  pub timestamp_ms: u64,
+  pub command_name: String,
+  pub args_json: String,
+  pub result: AuditResult,
+  pub duration_ms: u64,
+}
+
+/// A command the invoke handler passed on that hasn't reported its result.
+#[derive(Debug)]
+struct OpenCall {
+  id: u64,
+  command_name: String,
+  args_json: String,
+  timestamp_ms: u64,
+  started: Instant,
+}
+
+/// The last `MAX_ENTRIES` command invocations, oldest first.
+#[derive(Debug, Default)]
+pub struct CommandAuditLog {
+  entries: VecDeque<AuditEntry>,
+  open: VecDeque<OpenCall>,
+  next_id: u64,
+}
+
+impl CommandAuditLog {
+  pub fn record(&mut self, entry: AuditEntry) {
+    if self.entries.len() >= MAX_ENTRIES {
+      self.entries.pop_front();
+    }
+    self.entries.push_back(entry);
+  }
+
+  /// Notes a call that's about to run; `close` records it once it's done.
+  pub fn open(&mut self, command_name: &str, args_json: String, timestamp_ms: u64) -> u64 {
+    // Calls that never report (an async command whose arguments didn't
+    // parse) mustn't pile up.
+    if self.open.len() >= MAX_ENTRIES {
+      self.open.pop_front();
+    }
+    self.next_id += 1;
+    self.open.push_back(OpenCall {
+      id: self.next_id,
+      command_name: command_name.to_string(),
+      args_json,
+      timestamp_ms,
+      started: Instant::now(),
+    });
+    self.next_id
+  }
+
+  /// Records the oldest open call of `command_name` with `result`. False if
+  /// none is open, e.g. when one command calls another directly.
+  pub fn close(&mut self, command_name: &str, result: AuditResult) -> bool {
+    let at = self.open.iter().position(|call| call.command_name == command_name);
+    self.close_at(at, result)
+  }
+
+  /// Like `close`, for the call `open` returned `id` for.
+  pub fn close_id(&mut self, id: u64, result: AuditResult) -> bool {
+    let at = self.open.iter().position(|call| call.id == id);
+    self.close_at(at, result)
+  }
+
+  fn close_at(&mut self, at: Option<usize>, result: AuditResult) -> bool {
+    let Some(call) = at.and_then(|at| self.open.remove(at)) else {
+      return false;
+    };
+    self.record(AuditEntry {
+      timestamp_ms: call.timestamp_ms,
+      command_name: call.command_name,
+      args_json: call.args_json,
+      result,
+      duration_ms: call.started.elapsed().as_millis() as u64,
+    });
+    true
+  }
+
+  /// The newest `limit` entries, oldest first.
+  pub fn recent(&self, limit: usize) -> Vec<AuditEntry> {
+    let skip = self.entries.len().saturating_sub(limit);
+    self.entries.iter().skip(skip).cloned().collect()
+  }
+
+  /// Drops every entry, returning how many there were.
+  pub fn clear(&mut self) -> usize {
+    let cleared = self.entries.len();
+    self.entries.clear();
+    cleared
+  }
+}
+
+/// A command's arguments as recorded: secret values blanked and every known
+/// secret scrubbed, like `debug_log` lines.
+pub fn args_json(command: &str, body: &InvokeBody, known_secrets: &[String]) -> String {
+  let json = match body {
+    InvokeBody::Json(args) => {
+      let mut args = args.clone();
+      if SECRET_COMMANDS.contains(&command) {
+        if let Some(value) = args.get_mut("value") {
+          *value = Value::String(secrets::REDACTED.to_string());
+        }
+      }
+      args.to_string()
+    }
+    InvokeBody::Raw(bytes) => format!("<{} raw bytes>", bytes.len()),
+  };
+  secrets::redact(&json, known_secrets)
+}
+
+/// Wraps the invoke handler so every command lands in the audit log. The
+/// handler never sees what a command returned, so commands report it
+/// themselves through `run` or `finish`; this opens the entry they close.
+/// A sync command that hasn't reported by the time the handler returns
+/// never ran: no handler took it, or its arguments didn't parse.
+pub fn audited<R: Runtime>(
+  handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+  move |invoke| {
+    let app = invoke.message.webview().app_handle().clone();
+    let state = app.state::<AppState>();
+    let command_name = invoke.message.command().to_string();
+    let known_secrets = state.known_secrets.lock().map(|s| s.clone()).unwrap_or_default();
+    let args_json = args_json(&command_name, invoke.message.payload(), &known_secrets);
+    let id = state
+      .command_audit_log
+      .lock()
+      .map(|mut log| log.open(&command_name, args_json, crate::now_ms()))
+      .ok();
+
+    let handled = handler(invoke);
+
+    if !handled || !ASYNC_COMMANDS.contains(&command_name.as_str()) {
+      if let (Some(id), Ok(mut log)) = (id, state.command_audit_log.lock()) {
+        log.close_id(id, AuditResult::Err);
+      }
+    }
+    handled
+  }
+}
+
+/// Records how a command ended and hands its result back, at the command's
+/// boundary: `audited` never sees results, and guessing them from anything
+/// else gets async commands and error-carrying payloads wrong.
+pub fn finish<T>(
+  app: &AppHandle,
+  command_name: &str,
+  result: Result<T, AppError>,
+) -> Result<T, AppError> {
+  let outcome = if result.is_ok() { AuditResult::Ok } else { AuditResult::Err };
+  if let Ok(mut log) = app.state::<AppState>().command_audit_log.lock() {
+    log.close(command_name, outcome);
+  }
+  result
+}
+
+/// Runs a sync command's body and records its result with `finish`.
+pub fn run<T>(
+  app: AppHandle,
+  command_name: &str,
+  body: impl FnOnce() -> Result<T, AppError>,
+) -> Result<T, AppError> {
+  finish(&app, command_name, body())
+}
+
+/// The most recent `limit` commands (default 50), for a developer console.
+/// Debug mode only.
+#[tauri::command]
+pub fn get_audit_log(app: AppHandle, limit: Option<usize>) -> Result<Vec<AuditEntry>, AppError> {
+  run(app.clone(), "get_audit_log", move || {
+    webview::require_debug_mode(&app, "get_audit_log")?;
+    let state = app.state::<AppState>();
+    let log = state.command_audit_log.lock().map_err(|e| e.to_string())?;
+    Ok(log.recent(limit.unwrap_or(DEFAULT_LIMIT)))
+  })
+}
+
+/// Debug mode only.
+#[tauri::command]
+pub fn clear_audit_log(app: AppHandle) -> Result<u32, AppError> {
+  run(app.clone(), "clear_audit_log", move || {
+    log::info!("clear_audit_log invoked");
+
+    webview::require_debug_mode(&app, "clear_audit_log")?;
+    let state = app.state::<AppState>();
+    let cleared = state.command_audit_log.lock().map_err(|e| e.to_string())?.clear();
+
+    log::debug!("cleared {} audit log entries", cleared);
+    Ok(cleared as u32)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entry(n: u64) -> AuditEntry {
+    AuditEntry {
+      timestamp_ms: n,
+      command_name: "center_window".to_string(),
+      args_json: "{}".to_string(),
+      result: AuditResult::Ok,
+      duration_ms: 0,
+    }
+  }
+
+  #[test]
+  fn keeps_only_the_newest_entries() {
+    let mut log = CommandAuditLog::default();
+    for n in 0..(MAX_ENTRIES as u64 + 5) {
+      log.record(entry(n));
+    }
+    let entries = log.recent(MAX_ENTRIES);
+    assert_eq!(entries.len(), MAX_ENTRIES);
+    assert_eq!(entries[0].timestamp_ms, 5);
+  }
+
+  #[test]
+  fn recent_returns_the_newest_oldest_first() {
+    let mut log = CommandAuditLog::default();
+    for n in 0..5 {
+      log.record(entry(n));
+    }
+    let stamps: Vec<u64> = log.recent(2).iter().map(|e| e.timestamp_ms).collect();
+    assert_eq!(stamps, vec![3, 4]);
+    assert_eq!(log.recent(50).len(), 5);
+    assert_eq!(log.clear(), 5);
+    assert!(log.recent(50).is_empty());
+  }
+
+  #[test]
+  fn secrets_never_reach_the_log() {
+    let body = InvokeBody::Json(serde_json::json!({ "name": "token", "value": "hunter2" }));
+    assert_eq!(args_json("secret_set", &body, &[]), r#"{"name":"token","value":"[redacted]"}"#);
+
+    let body = InvokeBody::Json(serde_json::json!({ "title": "hunter2" }));
+    let known = ["hunter2".to_string()];
+    assert_eq!(args_json("set_window_title", &body, &known), r#"{"title":"[redacted]"}"#);
+  }
+
+  #[test]
+  fn calls_are_recorded_when_they_close() {
+    let mut log = CommandAuditLog::default();
+    let first = log.open("execute_js", "{}".to_string(), 1);
+    log.open("center_window", "{}".to_string(), 2);
+    assert!(log.recent(50).is_empty());
+
+    assert!(log.close("center_window", AuditResult::Ok));
+    assert!(log.close_id(first, AuditResult::Err));
+    let entries = log.recent(50);
+    assert_eq!(entries[0].command_name, "center_window");
+    assert_eq!(entries[1].result, AuditResult::Err);
+
+    // Each call closes once; a direct call between commands has none open.
+    assert!(!log.close_id(first, AuditResult::Ok));
+    assert!(!log.close("center_window", AuditResult::Ok));
+  }
+
+  #[test]
+  fn results_serialize_lowercase() {
+    assert_eq!(serde_json::to_value(AuditResult::Err).unwrap(), serde_json::json!("err"));
+  }
+}
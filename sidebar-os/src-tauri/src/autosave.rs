@@ -0,0 +1,92 @@
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Listener, Manager, PhysicalPosition, WebviewWindow};
+
+use crate::state::AppState;
+use crate::{layouts, profiles, WindowPos};
+
+pub const AUTOSAVE_POSITION_KEY: &str = "autosave_position";
+// Quiet time after the last move event before the position is written.
+const DEBOUNCE: Duration = Duration::from_millis(750);
+// How long after one of our own moves its move events are ignored.
+const SUPPRESS_FOR: Duration = Duration::from_millis(500);
+
+pub fn enabled(app: &AppHandle) -> bool {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(AUTOSAVE_POSITION_KEY))
+    .and_then(|value| value.as_bool())
+    .unwrap_or(crate::settings::DEFAULT_AUTOSAVE_POSITION)
+}
+
+/// Call before moving the panel from the backend, so the move isn't taken
+/// for a drag and autosaved.
+pub fn suppress(window: &WebviewWindow) {
+  if let Ok(mut until) = window.state::<AppState>().autosave_suppressed_until.lock() {
+    *until = Some(Instant::now() + SUPPRESS_FOR);
+  }
+}
+
+fn suppressed(app: &AppHandle) -> bool {
+  let until = *app.state::<AppState>().autosave_suppressed_until.lock().unwrap();
+  until.is_some_and(|until| Instant::now() < until)
+}
+
+/// The autosaved position for `mode`, unless autosave is off or the entry is
+/// stale.
+pub fn load_autosaved_position(app: &AppHandle, mode: &str) -> Option<WindowPos> {
+  if !enabled(app) {
+    return None;
+  }
+  let pos = layouts::load_entry(app, mode).autosaved_position?;
+  if pos.stale {
+    log::debug!("ignoring stale autosaved position for {}", mode);
+    return None;
+  }
+  Some(pos)
+}
+
+fn save(app: &AppHandle, position: PhysicalPosition<i32>) {
+  let mode = app.state::<AppState>().current_mode.lock().unwrap().clone();
+  let pos = crate::window_pos_at(app, position.x, position.y);
+  log::debug!("autosaving {} position ({}, {})", mode, pos.x, pos.y);
+  if let Err(e) = layouts::update_entry(app, &mode, |entry| entry.autosaved_position = Some(pos)) {
+    log::warn!("could not autosave position: {}", e);
+  }
+}
+
+/// Watches the panel's move events and writes where it settles once moves
+/// stop for `DEBOUNCE`. Separate from explicit custom positions, which win
+/// when both exist.
+pub fn start(app: &AppHandle) {
+  let Some(window) = app.get_webview_window("panel") else {
+    return;
+  };
+  let (tx, rx) = mpsc::channel::<PhysicalPosition<i32>>();
+
+  let handle = app.clone();
+  window.listen("tauri://move", move |_event| {
+    if suppressed(&handle) || !enabled(&handle) {
+      return;
+    }
+    let window = handle.get_webview_window("panel");
+    if let Some(position) = window.and_then(|w| w.outer_position().ok()) {
+      let _ = tx.send(position);
+    }
+  });
+
+  let handle = app.clone();
+  std::thread::spawn(move || {
+    while let Ok(mut latest) = rx.recv() {
+      loop {
+        match rx.recv_timeout(DEBOUNCE) {
+          Ok(position) => latest = position,
+          Err(mpsc::RecvTimeoutError::Timeout) => break,
+          Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        }
+      }
+      save(&handle, latest);
+    }
+  });
+}
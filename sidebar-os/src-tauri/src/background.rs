@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use tauri::window::Color;
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::profiles;
+
+pub const BACKGROUND_COLOR_KEY: &str = "background_color";
+
+/// Native background behind the webview, shown until the page paints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackgroundColor {
+  pub r: u8,
+  pub g: u8,
+  pub b: u8,
+  pub a: u8,
+}
+
+fn apply(window: &WebviewWindow, color: BackgroundColor) -> Result<(), String> {
+  let BackgroundColor { r, g, b, a } = color;
+  window.set_background_color(Some(Color(r, g, b, a))).map_err(|e| e.to_string())
+}
+
+/// Re-applies a persisted color during setup, before the panel is first
+/// shown, so a transparent panel doesn't flash white while the page loads.
+pub fn restore_background(app: &AppHandle) {
+  let color = profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(BACKGROUND_COLOR_KEY))
+    .and_then(|value| serde_json::from_value::<BackgroundColor>(value).ok());
+  if let (Some(color), Some(window)) = (color, app.get_webview_window("panel")) {
+    if let Err(e) = apply(&window, color) {
+      log::warn!("could not restore background color: {}", e);
+    }
+  }
+}
+
+/// Sets the panel's native background color and remembers it. Use alpha 0
+/// for a transparent panel.
+#[tauri::command]
+pub fn set_background_color(app: AppHandle, r: u8, g: u8, b: u8, a: u8) -> CommandResult {
+  audit::run(app.clone(), "set_background_color", move || {
+    log::info!("set_background_color: rgba=({}, {}, {}, {})", r, g, b, a);
+
+    let color = BackgroundColor { r, g, b, a };
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    apply(&window, color)?;
+
+    let store = profiles::settings_store(&app)?;
+    store.set(BACKGROUND_COLOR_KEY, serde_json::to_value(color).map_err(|e| e.to_string())?);
+    store.save()?;
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn colors_need_every_channel_in_range() {
+    let parse = |value| serde_json::from_value::<BackgroundColor>(value).ok();
+    let clear = serde_json::json!({ "r": 0, "g": 0, "b": 0, "a": 0 });
+    assert_eq!(parse(clear), Some(BackgroundColor { r: 0, g: 0, b: 0, a: 0 }));
+    assert_eq!(parse(serde_json::json!({ "r": 256, "g": 0, "b": 0, "a": 0 })), None);
+    assert_eq!(parse(serde_json::json!({ "r": 0, "g": 0, "b": 0 })), None);
+  }
+}
@@ -0,0 +1,690 @@
+use std::time::{Duration, Instant};
+
+use tauri::{
+  AppHandle, LogicalSize, Manager, PhysicalPosition, PhysicalSize, Position, Size, WebviewWindow,
+};
+
+use crate::audit;
+use crate::autosave;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::history;
+use crate::hotkeys;
+use crate::journal;
+use crate::monitors::{self, MonitorRect};
+use crate::onboarding;
+use crate::placement::{self, Anchor, Placement};
+use crate::profiles;
+use crate::sizing;
+use crate::stacking;
+use crate::state::AppState;
+use crate::theme;
+use crate::usage::{self, ShowTrigger};
+
+// Whether the panel was showing (rather than minimized) when last touched.
+pub const LAST_VISIBLE_KEY: &str = "last_visible";
+// Anchors the cycle-position hotkey steps through, in order.
+pub const CYCLE_ANCHORS: &[Anchor] =
+  &[Anchor::RightCenter, Anchor::TopCenter, Anchor::LeftCenter, Anchor::Center];
+// A peek stays up at least this long, so a quick tap doesn't flicker.
+pub const PEEK_MIN_DURATION: Duration = Duration::from_millis(150);
+// Ends a peek whose release never arrived.
+pub const PEEK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Window size the frontend uses for a mode (see App.tsx).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SizePreset {
+  pub width: f64,
+  pub height: f64,
+  pub min: Option<(f64, f64)>,
+  pub resizable: bool,
+}
+
+pub fn size_preset(mode: &str) -> Option<SizePreset> {
+  match mode {
+    "collapsed" => Some(SizePreset { width: 220.0, height: 160.0, min: None, resizable: false }),
+    "expanded" => Some(SizePreset {
+      width: 800.0,
+      height: 600.0,
+      min: Some((640.0, 360.0)),
+      resizable: true,
+    }),
+    _ => None,
+  }
+}
+
+/// Positions the window for `mode`: a saved custom position wins, then the
+/// autosaved one, otherwise the mode's stored (or default) placement.
+fn try_place_for_mode(app: &AppHandle, window: &WebviewWindow, mode: &str) -> Result<(), String> {
+  if let Some(pos) = crate::load_custom_position(app, mode) {
+    log::debug!("placing {} at saved custom position ({}, {})", mode, pos.x, pos.y);
+    autosave::suppress(window);
+    return window
+      .set_position(Position::Physical(PhysicalPosition { x: pos.x, y: pos.y }))
+      .map_err(|e| e.to_string());
+  }
+  if let Some(pos) = autosave::load_autosaved_position(app, mode) {
+    log::debug!("placing {} at autosaved position ({}, {})", mode, pos.x, pos.y);
+    autosave::suppress(window);
+    return window
+      .set_position(Position::Physical(PhysicalPosition { x: pos.x, y: pos.y }))
+      .map_err(|e| e.to_string());
+  }
+
+  placement::apply_placement(window, placement::load_placement(app, mode)).map(|_| ())
+}
+
+fn place_for_mode(app: &AppHandle, window: &WebviewWindow, mode: &str) {
+  if let Err(e) = try_place_for_mode(app, window, mode) {
+    log::warn!("could not apply {} placement: {}", mode, e);
+  }
+}
+
+/// Sizes the window for `mode`: a size registered with `register_mode_size`
+/// wins over the preset. Modes with neither keep their current size.
+fn apply_size_for_mode(app: &AppHandle, window: &WebviewWindow, mode: &str) -> Result<(), String> {
+  let preset = size_preset(mode);
+  if let Some(preset) = preset {
+    window.set_resizable(preset.resizable).map_err(|e| e.to_string())?;
+    let min = preset.min.map(|(w, h)| Size::Logical(LogicalSize { width: w, height: h }));
+    window.set_min_size(min).map_err(|e| e.to_string())?;
+  }
+
+  let size = match sizing::load_mode_size(app, mode) {
+    Some(size) => Some((size.width as f64, size.height as f64)),
+    None => preset.map(|p| (p.width, p.height)),
+  };
+  if let Some((width, height)) = size {
+    window
+      .set_size(Size::Logical(LogicalSize { width, height }))
+      .map_err(|e| e.to_string())?;
+  }
+  Ok(())
+}
+
+/// Re-applies the layout for the current mode, e.g. after a profile switch.
+pub fn reapply_layout(app: &AppHandle) {
+  let mode = app.state::<AppState>().current_mode.lock().unwrap().clone();
+  if let Some(w) = app.get_webview_window("panel") {
+    place_for_mode(app, &w, &mode);
+  }
+}
+
+fn remember_visible(app: &AppHandle, visible: bool) {
+  // Debounced write; this runs on every show.
+  if let Ok(store) = profiles::settings_store(app) {
+    store.set(LAST_VISIBLE_KEY, visible);
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchAction {
+  Expand,
+  Restore,
+  Minimize,
+}
+
+/// The first launch, and every launch until onboarding is done, opens the
+/// expanded panel. After that the panel comes back the way it was left.
+pub fn launch_action(first_run: bool, onboarded: bool, last_visible: bool) -> LaunchAction {
+  if first_run || !onboarded {
+    LaunchAction::Expand
+  } else if last_visible {
+    LaunchAction::Restore
+  } else {
+    LaunchAction::Minimize
+  }
+}
+
+/// Shows (or doesn't) the panel during setup; see `launch_action`.
+pub fn show_on_launch(app: &AppHandle) {
+  let first_run = onboarding::mark_launched(app);
+  let last_visible = profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(LAST_VISIBLE_KEY))
+    .and_then(|value| value.as_bool())
+    .unwrap_or(true);
+  let action = launch_action(first_run, onboarding::load(app).completed, last_visible);
+  log::debug!("launch: first_run={} action={:?}", first_run, action);
+
+  if action == LaunchAction::Expand {
+    show_expanded(app, ShowTrigger::Launch);
+    return;
+  }
+  let Some(w) = app.get_webview_window("panel") else {
+    return;
+  };
+  place_for_mode(app, &w, "expanded");
+  if action == LaunchAction::Minimize {
+    let was_on_top = stacking::is_always_on_top(&w).unwrap_or(true);
+    autosave::suppress(&w);
+    if w.minimize().is_ok() {
+      *app.state::<AppState>().minimized.lock().unwrap() = Some(was_on_top);
+    }
+  }
+}
+
+/// Shared path for every backend "show the panel" trigger (tray, hotkeys,
+/// single-instance, launch): place it for expanded mode, raise it and tell
+/// the webview to expand. `trigger` feeds the local usage counters.
+pub fn show_expanded(app: &AppHandle, trigger: ShowTrigger) {
+  usage::record_show(app, trigger);
+  // A real show outlasts any peek in progress.
+  app.state::<AppState>().peek.lock().unwrap().take();
+  // Undoes the app hide from `hide_panel`.
+  #[cfg(target_os = "macos")]
+  let _ = app.show();
+  if let Some(w) = app.get_webview_window("panel") {
+    place_for_mode(app, &w, "expanded");
+    let _ = w.show();
+    let _ = w.set_focus();
+    let _ = stacking::set_always_on_top(&w, true);
+    let _ = journal::emit(app, "panel-should-expand", ());
+    *app.state::<AppState>().current_mode.lock().unwrap() = "expanded".to_string();
+    remember_visible(app, true);
+  }
+}
+
+/// Hides the panel; the way back is `show_expanded`. On macOS the app is
+/// hidden as well, which hands focus back to the app that had it before.
+pub fn hide_panel(app: &AppHandle) {
+  if let Some(w) = app.get_webview_window("panel") {
+    let _ = w.hide();
+    #[cfg(target_os = "macos")]
+    let _ = app.hide();
+    remember_visible(app, false);
+    let _ = journal::emit(app, "panel-hidden", ());
+  }
+}
+
+/// Moves the panel to the next monitor (see `monitors::next_monitor`), at
+/// the anchor it has now.
+pub fn move_to_next_monitor(app: &AppHandle) -> Result<(), String> {
+  let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+  let available = window.available_monitors().map_err(|e| e.to_string())?;
+  let rects: Vec<MonitorRect> = available.iter().map(MonitorRect::from).collect();
+  let current = window.current_monitor().map_err(|e| e.to_string())?;
+  let index = monitors::next_monitor(&rects, current.as_ref().map(MonitorRect::from))
+    .ok_or(AppError::MonitorNotFound)?;
+
+  history::record(app, &window);
+  placement::apply_placement_on(&window, &available[index], placement::current(app))?;
+  let _ = window.set_focus();
+  let _ = journal::emit(
+    app,
+    "panel-monitor-changed",
+    serde_json::json!({ "index": index, "name": available[index].name() }),
+  );
+  Ok(())
+}
+
+/// The anchor after `current` in `CYCLE_ANCHORS`; one outside the cycle
+/// starts it over.
+pub fn next_cycle_anchor(current: Anchor) -> Anchor {
+  let next = CYCLE_ANCHORS
+    .iter()
+    .position(|anchor| *anchor == current)
+    .map_or(0, |i| (i + 1) % CYCLE_ANCHORS.len());
+  CYCLE_ANCHORS[next]
+}
+
+/// Cycle-position hotkey: shows the panel if hidden, then moves it to the
+/// next anchor in `CYCLE_ANCHORS` on its monitor, keeping the margin.
+pub fn cycle_position(app: &AppHandle) -> Result<Anchor, String> {
+  let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+  let visible = window.is_visible().map_err(|e| e.to_string())?
+    && !window.is_minimized().map_err(|e| e.to_string())?;
+  if !visible {
+    show_expanded(app, ShowTrigger::Hotkey);
+  }
+
+  let current = placement::current(app);
+  let next = Placement { anchor: next_cycle_anchor(current.anchor), ..current };
+  history::record(app, &window);
+  placement::apply_placement(&window, next)?;
+  let _ = journal::emit(app, "panel-position-cycled", serde_json::json!({ "anchor": next.anchor }));
+  Ok(next.anchor)
+}
+
+/// A peek in progress; see `start_peek`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Peek {
+  pub generation: u64,
+  pub started: Instant,
+}
+
+/// How much longer a peek that's been up for `shown_for` has to stay.
+pub fn peek_remaining(shown_for: Duration) -> Duration {
+  PEEK_MIN_DURATION.saturating_sub(shown_for)
+}
+
+/// Peek-panel press: shows a hidden panel in its current mode without taking
+/// focus, until `end_peek`. Independent of the toggle: a panel that's showing
+/// (or minimized) is left alone, and one shown for real during the peek
+/// stays up after the release. A release that never comes ends the peek
+/// after `PEEK_TIMEOUT`.
+pub fn start_peek(app: &AppHandle) -> Result<(), String> {
+  let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+  if window.is_visible().map_err(|e| e.to_string())?
+    || window.is_minimized().map_err(|e| e.to_string())?
+  {
+    log::debug!("peek requested while the panel is up; ignoring");
+    return Ok(());
+  }
+
+  let state = app.state::<AppState>();
+  let generation = {
+    let mut next = state.next_peek.lock().map_err(|e| e.to_string())?;
+    *next += 1;
+    *next
+  };
+  let mode = state.current_mode.lock().map_err(|e| e.to_string())?.clone();
+  place_for_mode(app, &window, &mode);
+  stacking::show_without_focus(&window).map_err(|e| e.to_string())?;
+  let _ = stacking::set_always_on_top(&window, true);
+  let peek = Peek { generation, started: Instant::now() };
+  *state.peek.lock().map_err(|e| e.to_string())? = Some(peek);
+  let _ = journal::emit(app, "panel-peek-started", ());
+
+  let app = app.clone();
+  std::thread::spawn(move || {
+    std::thread::sleep(PEEK_TIMEOUT);
+    if finish_peek(&app, generation) {
+      log::warn!("peek release never arrived; hid the panel after {:?}", PEEK_TIMEOUT);
+    }
+  });
+  Ok(())
+}
+
+/// Peek-panel release: hides the panel again, no sooner than
+/// `PEEK_MIN_DURATION` after it appeared. Waits off the shortcut handler's
+/// thread.
+pub fn end_peek(app: &AppHandle) {
+  let Some(peek) = *app.state::<AppState>().peek.lock().unwrap() else {
+    return;
+  };
+  let remaining = peek_remaining(peek.started.elapsed());
+  let app = app.clone();
+  std::thread::spawn(move || {
+    std::thread::sleep(remaining);
+    finish_peek(&app, peek.generation);
+  });
+}
+
+/// Hides the panel if peek `generation` is still in progress. Returns
+/// whether it was.
+fn finish_peek(app: &AppHandle, generation: u64) -> bool {
+  {
+    let mut peek = app.state::<AppState>().peek.lock().unwrap();
+    if peek.is_none_or(|p| p.generation != generation) {
+      return false;
+    }
+    *peek = None;
+  }
+  if let Some(window) = app.get_webview_window("panel") {
+    let _ = window.hide();
+  }
+  let _ = journal::emit(app, "panel-peek-ended", ());
+  true
+}
+
+/// What the Cmd+1 toggle turns the panel into: a hidden panel comes back
+/// expanded, otherwise it flips between collapsed and expanded.
+pub fn toggle_target(visible: bool, mode: &str) -> &'static str {
+  if !visible || mode == "collapsed" {
+    "expanded"
+  } else {
+    "collapsed"
+  }
+}
+
+/// Backend side of the Cmd+1 toggle; see `toggle_target`.
+pub fn toggle_panel(app: &AppHandle) -> Result<(), String> {
+  let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+  let minimized = window.is_minimized().map_err(|e| e.to_string())?;
+  let visible = !minimized && window.is_visible().map_err(|e| e.to_string())?;
+  let mode = app.state::<AppState>().current_mode.lock().map_err(|e| e.to_string())?.clone();
+  let target = toggle_target(visible, &mode);
+  log::debug!("toggle: {} (visible={}) -> {}", mode, visible, target);
+  show_in_mode(app, target)
+}
+
+/// What an explicit collapse or expand press does to the panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+  Switch,
+  Focus,
+  Ignore,
+}
+
+/// Unlike the toggle, an explicit press never flips back: one for the mode
+/// the panel is already showing in just focuses it, and collapsing a hidden
+/// panel does nothing.
+pub fn explicit_transition(visible: bool, mode: &str, target: &str) -> Transition {
+  if visible && mode == target {
+    Transition::Focus
+  } else if !visible && target == "collapsed" {
+    Transition::Ignore
+  } else {
+    Transition::Switch
+  }
+}
+
+/// Backend side of the collapse-panel and expand-panel hotkeys; see
+/// `explicit_transition`.
+pub fn show_explicitly(app: &AppHandle, target: &str) -> Result<(), String> {
+  let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+  let minimized = window.is_minimized().map_err(|e| e.to_string())?;
+  let visible = !minimized && window.is_visible().map_err(|e| e.to_string())?;
+  let mode = app.state::<AppState>().current_mode.lock().map_err(|e| e.to_string())?.clone();
+  match explicit_transition(visible, &mode, target) {
+    Transition::Switch => show_in_mode(app, target),
+    Transition::Focus => window.set_focus().map_err(|e| e.to_string()),
+    Transition::Ignore => {
+      log::debug!("{} requested while the panel is hidden; ignoring", target);
+      Ok(())
+    }
+  }
+}
+
+/// Shows the panel in `target` mode, sized and placed natively and brought
+/// back if minimized, then tells the webview once with
+/// `panel-state-changed`.
+pub fn show_in_mode(app: &AppHandle, target: &str) -> Result<(), String> {
+  let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+  let state = app.state::<AppState>();
+  let minimized = window.is_minimized().map_err(|e| e.to_string())?;
+  history::record(app, &window);
+
+  state.peek.lock().map_err(|e| e.to_string())?.take();
+  autosave::suppress(&window);
+  if minimized {
+    let was_on_top = state.minimized.lock().map_err(|e| e.to_string())?.take();
+    window.unminimize().map_err(|e| e.to_string())?;
+    stacking::set_always_on_top(&window, was_on_top.unwrap_or(true)).map_err(|e| e.to_string())?;
+  }
+  apply_size_for_mode(app, &window, target)?;
+  place_for_mode(app, &window, target);
+  window.show().map_err(|e| e.to_string())?;
+  let _ = window.set_focus();
+
+  *state.current_mode.lock().map_err(|e| e.to_string())? = target.to_string();
+  remember_visible(app, true);
+  let _ = journal::emit(
+    app,
+    "panel-state-changed",
+    serde_json::json!({ "mode": target, "visible": true }),
+  );
+  Ok(())
+}
+
+/// Explicitly collapses or expands the panel (unlike the Cmd+1 toggle):
+/// applies the size preset and placement and records the new mode.
+#[tauri::command]
+pub fn set_panel_mode(app: AppHandle, mode: String) -> CommandResult {
+  audit::run(app.clone(), "set_panel_mode", move || {
+    log::info!("set_panel_mode: mode={}", mode);
+
+    if mode != "collapsed" && mode != "expanded" {
+      return Err(AppError::InvalidArgument(format!("Unknown panel mode: {}", mode)));
+    }
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    history::record(&app, &window);
+
+    apply_size_for_mode(&app, &window, &mode)?;
+    place_for_mode(&app, &window, &mode);
+
+    *app.state::<AppState>().current_mode.lock().map_err(|e| e.to_string())? = mode.clone();
+    let _ = journal::emit(&app, "panel-mode-changed", serde_json::json!({ "mode": mode }));
+    Ok(ok_status())
+  })
+}
+
+/// Fits a window rect into a monitor's usable area: shrunk to fit first,
+/// then moved inside it.
+pub fn clamp_geometry(
+  origin: PhysicalPosition<i32>,
+  area: PhysicalSize<u32>,
+  position: PhysicalPosition<i32>,
+  size: PhysicalSize<u32>,
+) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+  let size = PhysicalSize {
+    width: size.width.min(area.width),
+    height: size.height.min(area.height),
+  };
+  let max_x = origin.x + (area.width - size.width) as i32;
+  let max_y = origin.y + (area.height - size.height) as i32;
+  let position = PhysicalPosition {
+    x: position.x.clamp(origin.x, max_x),
+    y: position.y.clamp(origin.y, max_y),
+  };
+  (position, size)
+}
+
+/// Sets the panel's position and size (physical pixels) in one call, kept
+/// on the monitor under (x, y). A shrinking window is resized before it
+/// moves and a growing one moved first, so it never spills off-screen
+/// mid-way.
+#[tauri::command]
+pub fn apply_window_geometry(
+  app: AppHandle,
+  x: i32,
+  y: i32,
+  width: u32,
+  height: u32,
+) -> CommandResult {
+  audit::run(app.clone(), "apply_window_geometry", move || {
+    log::info!("apply_window_geometry: x={}, y={}, width={}, height={}", x, y, width, height);
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let monitor = window
+      .monitor_from_point(x as f64, y as f64)
+      .map_err(|e| e.to_string())?
+      .or(monitors::panel_monitor(&window)?)
+      .ok_or(AppError::MonitorNotFound)?;
+    let (origin, area) = monitors::usable_area(&app, &monitor);
+    let (position, size) = clamp_geometry(
+      origin,
+      area,
+      PhysicalPosition { x, y },
+      PhysicalSize { width, height },
+    );
+
+    let before = window.outer_size().map_err(|e| e.to_string())?;
+    let on_top = stacking::is_always_on_top(&window).map_err(|e| e.to_string())?;
+    history::record(&app, &window);
+    autosave::suppress(&window);
+
+    let resize = || window.set_size(Size::Physical(size)).map_err(|e| e.to_string());
+    let reposition =
+      || window.set_position(Position::Physical(position)).map_err(|e| e.to_string());
+    if size.width <= before.width && size.height <= before.height {
+      resize()?;
+      reposition()?;
+    } else {
+      reposition()?;
+      resize()?;
+    }
+    // Some window managers drop always-on-top on a resize.
+    stacking::set_always_on_top(&window, on_top)?;
+    Ok(ok_status())
+  })
+}
+
+/// Switches to any mode in one go: size, position and the current mode are
+/// applied together, and if any step fails the window is put back the way it
+/// was.
+#[tauri::command]
+pub fn switch_mode(app: AppHandle, mode: String) -> CommandResult {
+  audit::run(app.clone(), "switch_mode", move || {
+    log::info!("switch_mode: mode={}", mode);
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let state = app.state::<AppState>();
+    let before_position = window.outer_position().map_err(|e| e.to_string())?;
+    let before_size = window.outer_size().map_err(|e| e.to_string())?;
+    let before_resizable = window.is_resizable().map_err(|e| e.to_string())?;
+    let before_placement = *state.last_placement.lock().map_err(|e| e.to_string())?;
+    history::record(&app, &window);
+
+    autosave::suppress(&window);
+    let apply = || -> Result<(), String> {
+      apply_size_for_mode(&app, &window, &mode)?;
+      try_place_for_mode(&app, &window, &mode)
+    };
+
+    if let Err(e) = apply() {
+      log::warn!("switch to {} failed ({}); restoring previous window state", mode, e);
+      let _ = window.set_resizable(before_resizable);
+      let _ = window.set_size(Size::Physical(before_size));
+      let _ = window.set_position(Position::Physical(before_position));
+      *state.last_placement.lock().unwrap() = before_placement;
+      return Err(e.into());
+    }
+
+    *state.current_mode.lock().map_err(|e| e.to_string())? = mode.clone();
+    let position = window.outer_position().map_err(|e| e.to_string())?;
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let _ = journal::emit(&app, "panel-mode-changed", serde_json::json!({ "mode": mode }));
+    let _ = journal::emit(
+      &app,
+      "panel-mode-switched",
+      serde_json::json!({
+        "mode": mode,
+        "position": { "x": position.x, "y": position.y },
+        "size": { "width": size.width, "height": size.height },
+      }),
+    );
+    Ok(ok_status())
+  })
+}
+
+/// Minimizes the panel, remembering whether it was floating so unminimize
+/// can put it back.
+#[tauri::command]
+pub fn minimize_panel(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "minimize_panel", move || {
+    log::info!("minimize_panel invoked");
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let was_on_top = stacking::is_always_on_top(&window).map_err(|e| e.to_string())?;
+    autosave::suppress(&window);
+    window.minimize().map_err(|e| e.to_string())?;
+
+    *app.state::<AppState>().minimized.lock().map_err(|e| e.to_string())? = Some(was_on_top);
+    remember_visible(&app, false);
+    let _ = journal::emit(&app, "panel-minimized", ());
+    Ok(ok_status())
+  })
+}
+
+/// Brings the panel back. Window managers tend to lose always-on-top and
+/// put the window wherever they like, so both are re-applied.
+#[tauri::command]
+pub fn unminimize_panel(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "unminimize_panel", move || {
+    log::info!("unminimize_panel invoked");
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let was_on_top = app.state::<AppState>().minimized.lock().map_err(|e| e.to_string())?.take();
+    autosave::suppress(&window);
+    window.unminimize().map_err(|e| e.to_string())?;
+    stacking::set_always_on_top(&window, was_on_top.unwrap_or(true)).map_err(|e| e.to_string())?;
+    placement::apply_placement(&window, placement::current(&app))?;
+    remember_visible(&app, true);
+
+    let _ = journal::emit(&app, "panel-unminimized", ());
+    Ok(ok_status())
+  })
+}
+
+/// Called by the webview once its event listeners are registered, so events
+/// raised during setup (like shortcut failures) aren't lost. The first call
+/// emits `app-ready` with the panel's current theme to start from.
+#[tauri::command]
+pub fn frontend_ready(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "frontend_ready", move || {
+    log::info!("frontend_ready invoked");
+
+    let state = app.state::<AppState>();
+    let already =
+      std::mem::replace(&mut *state.frontend_ready.lock().map_err(|e| e.to_string())?, true);
+    if !already {
+      let theme = theme::effective(&app);
+      let _ = journal::emit(&app, "app-ready", serde_json::json!({ "theme": theme }));
+      hotkeys::emit_failures(&app);
+      onboarding::emit_if_required(&app);
+    }
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn toggle_expands_hidden_and_collapsed_panels() {
+    assert_eq!(toggle_target(false, "collapsed"), "expanded");
+    assert_eq!(toggle_target(false, "expanded"), "expanded");
+    assert_eq!(toggle_target(true, "collapsed"), "expanded");
+    assert_eq!(toggle_target(true, "expanded"), "collapsed");
+    assert_eq!(toggle_target(true, "default"), "collapsed");
+  }
+
+  #[test]
+  fn explicit_presses_never_flip_back() {
+    assert_eq!(explicit_transition(true, "expanded", "expanded"), Transition::Focus);
+    assert_eq!(explicit_transition(true, "collapsed", "expanded"), Transition::Switch);
+    assert_eq!(explicit_transition(false, "expanded", "expanded"), Transition::Switch);
+    assert_eq!(explicit_transition(true, "expanded", "collapsed"), Transition::Switch);
+    assert_eq!(explicit_transition(false, "expanded", "collapsed"), Transition::Ignore);
+  }
+
+  #[test]
+  fn peeks_stay_up_for_the_minimum_only() {
+    assert_eq!(peek_remaining(Duration::from_millis(40)), Duration::from_millis(110));
+    assert_eq!(peek_remaining(Duration::from_millis(150)), Duration::ZERO);
+    assert_eq!(peek_remaining(Duration::from_secs(2)), Duration::ZERO);
+  }
+
+  #[test]
+  fn cycle_wraps_and_starts_over_from_other_anchors() {
+    assert_eq!(next_cycle_anchor(Anchor::RightCenter), Anchor::TopCenter);
+    assert_eq!(next_cycle_anchor(Anchor::Center), Anchor::RightCenter);
+    assert_eq!(next_cycle_anchor(Anchor::BottomLeft), Anchor::RightCenter);
+  }
+
+  #[test]
+  fn presets_match_frontend_sizes() {
+    let collapsed = size_preset("collapsed").unwrap();
+    assert_eq!((collapsed.width, collapsed.height), (220.0, 160.0));
+    assert!(!collapsed.resizable);
+
+    let expanded = size_preset("expanded").unwrap();
+    assert_eq!((expanded.width, expanded.height), (800.0, 600.0));
+    assert_eq!(expanded.min, Some((640.0, 360.0)));
+    assert!(size_preset("hovered").is_none());
+  }
+
+  #[test]
+  fn geometry_is_shrunk_then_kept_on_the_monitor() {
+    let origin = PhysicalPosition { x: 1920, y: 25 };
+    let area = PhysicalSize { width: 1440, height: 875 };
+    let clamp = |x, y, width, height| {
+      let (pos, size) =
+        clamp_geometry(origin, area, PhysicalPosition { x, y }, PhysicalSize { width, height });
+      (pos.x, pos.y, size.width, size.height)
+    };
+
+    assert_eq!(clamp(3000, 0, 800, 600), (2560, 25, 800, 600));
+    assert_eq!(clamp(2000, 100, 2000, 600), (1920, 100, 1440, 600));
+  }
+
+  #[test]
+  fn only_first_launches_force_the_panel_open() {
+    assert_eq!(launch_action(true, false, false), LaunchAction::Expand);
+    assert_eq!(launch_action(false, false, true), LaunchAction::Expand);
+    assert_eq!(launch_action(false, true, true), LaunchAction::Restore);
+    assert_eq!(launch_action(false, true, false), LaunchAction::Minimize);
+  }
+}
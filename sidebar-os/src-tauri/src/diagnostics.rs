@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::error::AppError;
+use crate::hotkeys::ShortcutFailure;
+use crate::profiles;
+use crate::stacking;
+use crate::state::AppState;
+use crate::webview;
+
+// The only variables `get_environment_variables` reveals; anything else
+// might hold a token.
+const ALLOWED_ENV_VARS: &[&str] = &["PATH", "HOME", "USER", "LANG", "DISPLAY", "WAYLAND_DISPLAY"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryUsage {
+  pub resident_bytes: u64,
+  pub virtual_bytes: u64,
+  pub system_total_bytes: u64,
+  pub system_used_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+  pub memory: MemoryUsage,
+  pub cpu_percent: f32,
+  pub panel_visible: bool,
+  pub always_on_top: bool,
+  pub current_mode: String,
+  pub store_reachable: bool,
+  pub shortcuts_count: u32,
+  pub shortcut_failures: Vec<ShortcutFailure>,
+  pub uptime_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowFlags {
+  pub is_visible: bool,
+  pub is_focused: bool,
+  pub is_always_on_top: bool,
+  pub is_resizable: bool,
+  pub is_minimized: bool,
+  pub is_movable: bool,
+}
+
+/// What the about dialog shows from tauri.conf.json.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TauriConfigSummary {
+  pub product_name: Option<String>,
+  pub version: Option<String>,
+  pub identifier: String,
+  pub window_label: String,
+  pub initial_width: f64,
+  pub initial_height: f64,
+}
+
+/// Summary of `config`, taking the panel's window config (or the first
+/// window's if there's no panel).
+pub fn summarize(config: &tauri::Config) -> Result<TauriConfigSummary, String> {
+  let windows = &config.app.windows;
+  let window = windows
+    .iter()
+    .find(|w| w.label == "panel")
+    .or(windows.first())
+    .ok_or("No windows in the config")?;
+  Ok(TauriConfigSummary {
+    product_name: config.product_name.clone(),
+    version: config.version.clone(),
+    identifier: config.identifier.clone(),
+    window_label: window.label.clone(),
+    initial_width: window.width,
+    initial_height: window.height,
+  })
+}
+
+fn refresh_process(system: &mut System) -> Result<sysinfo::Pid, String> {
+  let pid = sysinfo::get_current_pid().map_err(|e| e.to_string())?;
+  system.refresh_processes_specifics(
+    ProcessesToUpdate::Some(&[pid]),
+    true,
+    ProcessRefreshKind::nothing().with_memory().with_cpu(),
+  );
+  Ok(pid)
+}
+
+fn read_memory(app: &AppHandle) -> Result<MemoryUsage, String> {
+  let state = app.state::<AppState>();
+  let mut system = state.system.lock().map_err(|e| e.to_string())?;
+  let pid = refresh_process(&mut system)?;
+  system.refresh_memory();
+
+  let process = system.process(pid).ok_or("Current process not found")?;
+  Ok(MemoryUsage {
+    resident_bytes: process.memory(),
+    virtual_bytes: process.virtual_memory(),
+    system_total_bytes: system.total_memory(),
+    system_used_bytes: system.used_memory(),
+  })
+}
+
+// CPU usage is measured between refreshes, so the first reading after launch
+// is always 0 and later readings cover the time since the previous call.
+fn read_cpu(app: &AppHandle) -> Result<f32, String> {
+  let state = app.state::<AppState>();
+  let mut system = state.system.lock().map_err(|e| e.to_string())?;
+  let pid = refresh_process(&mut system)?;
+
+  let process = system.process(pid).ok_or("Current process not found")?;
+  Ok(process.cpu_usage())
+}
+
+#[tauri::command]
+pub fn get_memory_usage(app: AppHandle) -> Result<MemoryUsage, AppError> {
+  audit::run(app.clone(), "get_memory_usage", move || {
+    Ok(read_memory(&app)?)
+  })
+}
+
+#[tauri::command]
+pub fn get_cpu_usage(app: AppHandle) -> Result<f32, AppError> {
+  audit::run(app.clone(), "get_cpu_usage", move || {
+    Ok(read_cpu(&app)?)
+  })
+}
+
+#[tauri::command]
+pub fn is_panel_visible(app: AppHandle) -> Result<bool, AppError> {
+  audit::run(app.clone(), "is_panel_visible", move || {
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    Ok(window.is_visible()?)
+  })
+}
+
+/// Window state in one call. Always-on-top is read back from the window
+/// rather than tracked, so it can't drift from what the OS reports.
+#[tauri::command]
+pub fn get_window_flags(app: AppHandle) -> Result<WindowFlags, AppError> {
+  audit::run(app.clone(), "get_window_flags", move || {
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    Ok(WindowFlags {
+      is_visible: window.is_visible().map_err(|e| e.to_string())?,
+      is_focused: window.is_focused().map_err(|e| e.to_string())?,
+      is_always_on_top: stacking::is_always_on_top(&window).map_err(|e| e.to_string())?,
+      is_resizable: window.is_resizable().map_err(|e| e.to_string())?,
+      is_minimized: window.is_minimized().map_err(|e| e.to_string())?,
+      is_movable: *app.state::<AppState>().window_movable.lock().map_err(|e| e.to_string())?,
+    })
+  })
+}
+
+#[tauri::command]
+pub fn get_tauri_config_summary(app: AppHandle) -> Result<TauriConfigSummary, AppError> {
+  audit::run(app.clone(), "get_tauri_config_summary", move || {
+    Ok(summarize(app.config())?)
+  })
+}
+
+/// The allowlisted entries of `vars`.
+pub fn allowed_env(vars: impl IntoIterator<Item = (String, String)>) -> HashMap<String, String> {
+  vars.into_iter().filter(|(name, _)| ALLOWED_ENV_VARS.contains(&name.as_str())).collect()
+}
+
+/// `ALLOWED_ENV_VARS` from the app's environment, leaving out unset ones.
+/// Debug mode only.
+#[tauri::command]
+pub fn get_environment_variables(app: AppHandle) -> Result<HashMap<String, String>, AppError> {
+  audit::run(app.clone(), "get_environment_variables", move || {
+    log::info!("get_environment_variables invoked");
+
+    webview::require_debug_mode(&app, "get_environment_variables")?;
+    Ok(allowed_env(std::env::vars_os().filter_map(|(name, value)| {
+      Some((name.into_string().ok()?, value.into_string().ok()?))
+    })))
+  })
+}
+
+#[tauri::command]
+pub fn get_uptime_seconds(app: AppHandle) -> Result<u64, AppError> {
+  audit::run(app.clone(), "get_uptime_seconds", move || {
+    Ok(app.state::<AppState>().start_time.elapsed().as_secs())
+  })
+}
+
+#[tauri::command]
+pub fn health_check(app: AppHandle) -> Result<HealthReport, AppError> {
+  audit::run(app.clone(), "health_check", move || {
+    log::info!("health_check invoked");
+
+    let memory = read_memory(&app)?;
+    let cpu_percent = read_cpu(&app)?;
+
+    let window = app.get_webview_window("panel");
+    let panel_visible = window
+      .as_ref()
+      .and_then(|w| w.is_visible().ok())
+      .unwrap_or(false);
+    let always_on_top = window
+      .as_ref()
+      .and_then(|w| stacking::is_always_on_top(w).ok())
+      .unwrap_or(false);
+
+    let store_reachable = profiles::settings_store(&app).is_ok();
+
+    let state = app.state::<AppState>();
+    let current_mode = state.current_mode.lock().map_err(|e| e.to_string())?.clone();
+    let shortcuts_count = state
+      .registered_shortcuts
+      .lock()
+      .map_err(|e| e.to_string())?
+      .len() as u32;
+    let shortcut_failures = state.shortcut_failures.lock().map_err(|e| e.to_string())?.clone();
+    let uptime_seconds = state.start_time.elapsed().as_secs();
+
+    Ok(HealthReport {
+      memory,
+      cpu_percent,
+      panel_visible,
+      always_on_top,
+      current_mode,
+      store_reachable,
+      shortcuts_count,
+      shortcut_failures,
+      uptime_seconds,
+    })
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn only_allowlisted_env_vars_get_through() {
+    let vars = [("HOME", "/home/me"), ("AWS_SECRET_ACCESS_KEY", "hunter2"), ("path", "/bin")];
+    let env = allowed_env(vars.map(|(k, v)| (k.to_string(), v.to_string())));
+    assert_eq!(env, HashMap::from([("HOME".to_string(), "/home/me".to_string())]));
+  }
+
+  #[test]
+  fn summary_prefers_the_panel_window() {
+    let config: tauri::Config = serde_json::from_value(serde_json::json!({
+      "productName": "sidebar-os",
+      "version": "0.1.0",
+      "identifier": "com.example.sidebar",
+      "app": {
+        "windows": [
+          { "label": "splash", "width": 300, "height": 200 },
+          { "label": "panel", "width": 800, "height": 600 }
+        ]
+      }
+    }))
+    .unwrap();
+    let summary = summarize(&config).unwrap();
+    assert_eq!(summary.version.as_deref(), Some("0.1.0"));
+    assert_eq!(summary.window_label, "panel");
+    assert_eq!((summary.initial_width, summary.initial_height), (800.0, 600.0));
+  }
+}
@@ -0,0 +1,376 @@
+use std::time::Duration;
+#[cfg(any(target_os = "macos", test))]
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+#[cfg(target_os = "macos")]
+use crate::hotkeys::{self, ShortcutAction};
+use crate::profiles;
+use crate::settings;
+use crate::state::AppState;
+
+pub const MODIFIER_KEY: &str = "double_tap_modifier";
+pub const INTERVAL_KEY: &str = "double_tap_interval_ms";
+// Modifier taps last tens of milliseconds, so this has to poll quickly.
+#[cfg(target_os = "macos")]
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+// Polling slows down while no modifier is configured.
+#[cfg(target_os = "macos")]
+const IDLE_INTERVAL: Duration = Duration::from_millis(1000);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Modifier {
+  Cmd,
+  Ctrl,
+  Alt,
+  Shift,
+}
+
+impl Modifier {
+  #[cfg(any(target_os = "macos", test))]
+  pub const ALL: [Modifier; 4] = [Modifier::Cmd, Modifier::Ctrl, Modifier::Alt, Modifier::Shift];
+
+  pub fn parse(value: &str) -> Option<Modifier> {
+    match value.to_lowercase().as_str() {
+      "cmd" | "command" | "super" => Some(Modifier::Cmd),
+      "ctrl" | "control" => Some(Modifier::Ctrl),
+      "alt" | "option" => Some(Modifier::Alt),
+      "shift" => Some(Modifier::Shift),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(any(target_os = "macos", test))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyEvent {
+  Pressed(Modifier),
+  Released(Modifier),
+  // Any non-modifier key went down.
+  Key,
+}
+
+/// What changed between two looks at the keyboard. A key press comes first,
+/// so Cmd+C never gets as far as a second tap.
+#[cfg(any(target_os = "macos", test))]
+pub fn events(before: &[Modifier], after: &[Modifier], key_pressed: bool) -> Vec<KeyEvent> {
+  let mut events = Vec::new();
+  if key_pressed {
+    events.push(KeyEvent::Key);
+  }
+  for modifier in Modifier::ALL {
+    match (before.contains(&modifier), after.contains(&modifier)) {
+      (true, false) => events.push(KeyEvent::Released(modifier)),
+      (false, true) => events.push(KeyEvent::Pressed(modifier)),
+      _ => {}
+    }
+  }
+  events
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoubleTap {
+  pub modifier: Modifier,
+  pub interval: Duration,
+}
+
+#[cfg(any(target_os = "macos", test))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+  Idle,
+  FirstDown(Instant),
+  FirstUp(Instant),
+  SecondDown(Instant),
+}
+
+/// Watches key events for press, release, press, release of one modifier,
+/// each step within the interval of the one before, with nothing else
+/// pressed along the way.
+#[cfg(any(target_os = "macos", test))]
+#[derive(Debug)]
+pub struct Detector {
+  config: DoubleTap,
+  phase: Phase,
+  // Other modifiers currently held; a tap only counts on its own.
+  others_held: Vec<Modifier>,
+}
+
+#[cfg(any(target_os = "macos", test))]
+impl Detector {
+  pub fn new(config: DoubleTap) -> Self {
+    Self { config, phase: Phase::Idle, others_held: Vec::new() }
+  }
+
+  pub fn config(&self) -> DoubleTap {
+    self.config
+  }
+
+  /// Returns true on the release that completes a double tap.
+  pub fn feed(&mut self, event: KeyEvent, at: Instant) -> bool {
+    let within = |since: Instant| at.saturating_duration_since(since) <= self.config.interval;
+    let pressed = match event {
+      KeyEvent::Pressed(m) if m == self.config.modifier => true,
+      KeyEvent::Released(m) if m == self.config.modifier => false,
+      KeyEvent::Pressed(m) => {
+        self.others_held.push(m);
+        self.phase = Phase::Idle;
+        return false;
+      }
+      KeyEvent::Released(m) => {
+        self.others_held.retain(|&held| held != m);
+        self.phase = Phase::Idle;
+        return false;
+      }
+      KeyEvent::Key => {
+        self.phase = Phase::Idle;
+        return false;
+      }
+    };
+
+    let (phase, fired) = match (self.phase, pressed) {
+      _ if !self.others_held.is_empty() => (Phase::Idle, false),
+      (Phase::Idle, true) => (Phase::FirstDown(at), false),
+      (Phase::FirstDown(since), false) if within(since) => (Phase::FirstUp(at), false),
+      (Phase::FirstUp(since), true) if within(since) => (Phase::SecondDown(at), false),
+      // Too slow to be the second tap, but it can still be a first one.
+      (Phase::FirstUp(_), true) => (Phase::FirstDown(at), false),
+      (Phase::SecondDown(since), false) => (Phase::Idle, within(since)),
+      // A repeated press while held.
+      (phase @ (Phase::FirstDown(_) | Phase::SecondDown(_)), true) => (phase, false),
+      _ => (Phase::Idle, false),
+    };
+    self.phase = phase;
+    fired
+  }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+  use objc2_core_graphics::{CGEventFlags, CGEventSource, CGEventSourceStateID, CGEventType};
+
+  use super::Modifier;
+
+  fn mask(modifier: Modifier) -> CGEventFlags {
+    match modifier {
+      Modifier::Cmd => CGEventFlags::MaskCommand,
+      Modifier::Ctrl => CGEventFlags::MaskControl,
+      Modifier::Alt => CGEventFlags::MaskAlternate,
+      Modifier::Shift => CGEventFlags::MaskShift,
+    }
+  }
+
+  /// Modifiers held right now, from the session's combined keyboard state.
+  /// Reading it needs no accessibility permission, unlike an event tap.
+  pub fn held_modifiers() -> Vec<Modifier> {
+    let flags = CGEventSource::flags_state(CGEventSourceStateID::CombinedSessionState);
+    Modifier::ALL.into_iter().filter(|&m| flags.contains(mask(m))).collect()
+  }
+
+  /// Seconds since any key went down.
+  pub fn since_last_key() -> f64 {
+    CGEventSource::seconds_since_last_event_type(
+      CGEventSourceStateID::CombinedSessionState,
+      CGEventType::KeyDown,
+    )
+  }
+}
+
+/// Polls the keyboard on a background thread and runs the show-panel action
+/// on a double tap of the configured modifier.
+#[cfg(target_os = "macos")]
+pub fn start_watcher(app: AppHandle) {
+  std::thread::spawn(move || {
+    let mut detector: Option<Detector> = None;
+    let mut held = Vec::new();
+    let mut last_poll = Instant::now();
+
+    loop {
+      let config = *app.state::<AppState>().double_tap.lock().unwrap();
+      let Some(config) = config else {
+        detector = None;
+        std::thread::sleep(IDLE_INTERVAL);
+        continue;
+      };
+      if detector.as_ref().is_none_or(|d| d.config() != config) {
+        detector = Some(Detector::new(config));
+      }
+      let detector = detector.as_mut().unwrap();
+
+      let now = Instant::now();
+      let now_held = macos::held_modifiers();
+      let key_pressed = macos::since_last_key() < (now - last_poll).as_secs_f64();
+      for event in events(&held, &now_held, key_pressed) {
+        if detector.feed(event, now) {
+          log::info!("double tap of {:?}; running show-panel", config.modifier);
+          hotkeys::run_action(&app, ShortcutAction::ShowPanel);
+        }
+      }
+      held = now_held;
+      last_poll = now;
+
+      std::thread::sleep(POLL_INTERVAL);
+    }
+  });
+}
+
+// Modifier-only presses never reach the global shortcut plugin, and there's
+// no keyboard polling hooked up outside macOS yet.
+#[cfg(not(target_os = "macos"))]
+pub fn start_watcher(_app: AppHandle) {}
+
+/// Reads the persisted modifier and interval into managed state before the
+/// watcher starts.
+pub fn load_from_store(app: &AppHandle) {
+  let store = profiles::settings_store(app).ok();
+  let modifier = store
+    .as_ref()
+    .and_then(|store| store.get(MODIFIER_KEY))
+    .and_then(|value| serde_json::from_value::<Modifier>(value).ok());
+  let interval_ms = store
+    .as_ref()
+    .and_then(|store| store.get(INTERVAL_KEY))
+    .and_then(|value| value.as_u64())
+    .unwrap_or(settings::DEFAULT_DOUBLE_TAP_INTERVAL_MS);
+
+  let config = modifier.map(|modifier| DoubleTap {
+    modifier,
+    interval: Duration::from_millis(interval_ms),
+  });
+  if let Some(config) = config {
+    let ms = config.interval.as_millis();
+    log::debug!("double-tap summon restored: {:?} within {}ms", config.modifier, ms);
+  }
+  *app.state::<AppState>().double_tap.lock().unwrap() = config;
+}
+
+/// Sets the modifier whose double tap summons the panel; None turns it off.
+/// The interval is the `double_tap_interval_ms` setting.
+#[tauri::command]
+pub fn set_double_tap_modifier(app: AppHandle, modifier: Option<String>) -> CommandResult {
+  audit::run(app.clone(), "set_double_tap_modifier", move || {
+    log::info!("set_double_tap_modifier: modifier={:?}", modifier);
+
+    let parsed = match modifier.as_deref() {
+      Some(value) => Some(
+        Modifier::parse(value)
+          .ok_or_else(|| AppError::InvalidArgument(format!("Unknown modifier: {}", value)))?,
+      ),
+      None => None,
+    };
+    if parsed.is_some() && cfg!(not(target_os = "macos")) {
+      return Err(AppError::PlatformUnsupported(
+        "Double-tap summoning is only supported on macOS".to_string(),
+      ));
+    }
+
+    let store = profiles::settings_store(&app)?;
+    match parsed {
+      Some(m) => store.set(MODIFIER_KEY, serde_json::to_value(m).map_err(|e| e.to_string())?),
+      None => {
+        store.delete(MODIFIER_KEY);
+      }
+    }
+    store.save().map_err(|e| e.to_string())?;
+
+    load_from_store(&app);
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const CMD: Modifier = Modifier::Cmd;
+
+  fn detector() -> Detector {
+    Detector::new(DoubleTap { modifier: CMD, interval: Duration::from_millis(300) })
+  }
+
+  /// Feeds events at the given millisecond offsets; true if any fired.
+  fn run(detector: &mut Detector, events: &[(u64, KeyEvent)]) -> bool {
+    let start = Instant::now();
+    let mut fired = false;
+    for &(ms, event) in events {
+      fired |= detector.feed(event, start + Duration::from_millis(ms));
+    }
+    fired
+  }
+
+  fn tap(at: u64, up: u64) -> [(u64, KeyEvent); 2] {
+    [(at, KeyEvent::Pressed(CMD)), (up, KeyEvent::Released(CMD))]
+  }
+
+  #[test]
+  fn parses_modifier_names() {
+    assert_eq!(Modifier::parse("Command"), Some(Modifier::Cmd));
+    assert_eq!(Modifier::parse("option"), Some(Modifier::Alt));
+    assert_eq!(Modifier::parse("fn"), None);
+  }
+
+  #[test]
+  fn two_quick_taps_fire_once() {
+    let mut d = detector();
+    assert!(run(&mut d, &[tap(0, 80), tap(200, 260)].concat()));
+    // The sequence starts over afterwards.
+    assert!(!run(&mut d, &tap(400, 450)));
+  }
+
+  #[test]
+  fn slow_taps_and_long_holds_dont_fire() {
+    assert!(!run(&mut detector(), &[tap(0, 80), tap(500, 560)].concat()));
+    assert!(!run(&mut detector(), &[tap(0, 900), tap(950, 1000)].concat()));
+    assert!(!run(&mut detector(), &[tap(0, 80), tap(200, 700)].concat()));
+  }
+
+  #[test]
+  fn a_late_second_tap_can_start_a_new_pair() {
+    assert!(run(&mut detector(), &[tap(0, 80), tap(500, 560), tap(700, 760)].concat()));
+  }
+
+  #[test]
+  fn shortcuts_using_the_modifier_dont_fire() {
+    // Cmd+C, then a tap: the key press cancels the first tap.
+    let events = [
+      (0, KeyEvent::Pressed(CMD)),
+      (40, KeyEvent::Key),
+      (90, KeyEvent::Released(CMD)),
+      (150, KeyEvent::Pressed(CMD)),
+      (200, KeyEvent::Released(CMD)),
+    ];
+    assert!(!run(&mut detector(), &events));
+
+    // A key between the taps cancels too.
+    let between = [tap(0, 60).as_slice(), &[(100, KeyEvent::Key)], &tap(150, 200)].concat();
+    assert!(!run(&mut detector(), &between));
+  }
+
+  #[test]
+  fn other_modifiers_cancel_or_block_taps() {
+    let shift = Modifier::Shift;
+    let held = [
+      [(0, KeyEvent::Pressed(shift))].as_slice(),
+      &tap(10, 60),
+      &tap(100, 150),
+      &[(200, KeyEvent::Released(shift))],
+    ]
+    .concat();
+    assert!(!run(&mut detector(), &held));
+
+    let shift_down = [(80, KeyEvent::Pressed(shift))];
+    let between = [tap(0, 60).as_slice(), &shift_down, &tap(120, 170)].concat();
+    assert!(!run(&mut detector(), &between));
+  }
+
+  #[test]
+  fn samples_become_events() {
+    assert_eq!(events(&[], &[CMD], false), vec![KeyEvent::Pressed(CMD)]);
+    assert_eq!(events(&[CMD], &[], true), vec![KeyEvent::Key, KeyEvent::Released(CMD)]);
+    assert!(events(&[CMD], &[CMD], false).is_empty());
+  }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::Value;
+
+use crate::error_codes;
+
+/// Error returned by commands. Serializes as
+/// `{ "code": ..., "message": ..., "details": ... }`, with `code` one of
+/// `error_codes` and `details` only there when the variant has any.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+  WindowNotFound,
+  MonitorNotFound,
+  StoreError(String),
+  InvalidArgument(String),
+  PlatformUnsupported(String),
+  Timeout(String),
+  // Everything that used to be a bare String.
+  Other(String),
+}
+
+/// What commands return. Ones with nothing else to report answer
+/// `ok_status()`, so the frontend can await every command the same way.
+pub type CommandResult = Result<Value, AppError>;
+
+/// `{ "status": "ok" }`, the success value of commands without a result.
+pub fn ok_status() -> Value {
+  serde_json::json!({ "status": "ok" })
+}
+
+impl AppError {
+  pub fn code(&self) -> &'static str {
+    match self {
+      AppError::WindowNotFound => error_codes::WINDOW_NOT_FOUND,
+      AppError::MonitorNotFound => error_codes::MONITOR_NOT_FOUND,
+      AppError::StoreError(_) => error_codes::STORE_ERROR,
+      AppError::InvalidArgument(_) => error_codes::INVALID_ARGUMENT,
+      AppError::PlatformUnsupported(_) => error_codes::PLATFORM_UNSUPPORTED,
+      AppError::Timeout(_) => error_codes::TIMEOUT,
+      AppError::Other(_) => error_codes::INTERNAL,
+    }
+  }
+
+  /// Extra context for the frontend beyond the message.
+  pub fn details(&self) -> Option<Value> {
+    match self {
+      AppError::PlatformUnsupported(_) => {
+        Some(serde_json::json!({ "platform": std::env::consts::OS }))
+      }
+      _ => None,
+    }
+  }
+}
+
+impl Serialize for AppError {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let details = self.details();
+    let len = if details.is_some() { 3 } else { 2 };
+    let mut state = serializer.serialize_struct("AppError", len)?;
+    state.serialize_field("code", self.code())?;
+    state.serialize_field("message", &self.to_string())?;
+    if let Some(details) = details {
+      state.serialize_field("details", &details)?;
+    }
+    state.end()
+  }
+}
+
+impl fmt::Display for AppError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      AppError::WindowNotFound => write!(f, "Window not found"),
+      AppError::MonitorNotFound => write!(f, "No monitor found"),
+      AppError::StoreError(message) => write!(f, "Store error: {}", message),
+      AppError::InvalidArgument(message)
+      | AppError::PlatformUnsupported(message)
+      | AppError::Timeout(message)
+      | AppError::Other(message) => write!(f, "{}", message),
+    }
+  }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<String> for AppError {
+  fn from(message: String) -> Self {
+    AppError::Other(message)
+  }
+}
+
+impl From<&str> for AppError {
+  fn from(message: &str) -> Self {
+    AppError::Other(message.to_string())
+  }
+}
+
+impl From<tauri::Error> for AppError {
+  fn from(e: tauri::Error) -> Self {
+    AppError::Other(e.to_string())
+  }
+}
+
+impl From<tauri_plugin_store::Error> for AppError {
+  fn from(e: tauri_plugin_store::Error) -> Self {
+    AppError::StoreError(e.to_string())
+  }
+}
+
+impl From<serde_json::Error> for AppError {
+  fn from(e: serde_json::Error) -> Self {
+    AppError::InvalidArgument(e.to_string())
+  }
+}
+
+// Lets helpers that still return String use `?` on an AppError.
+impl From<AppError> for String {
+  fn from(e: AppError) -> Self {
+    e.to_string()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn serializes_with_a_code_to_switch_on() {
+    assert_eq!(
+      serde_json::to_value(AppError::WindowNotFound).unwrap(),
+      serde_json::json!({ "code": "WINDOW_NOT_FOUND", "message": "Window not found" })
+    );
+    assert_eq!(
+      serde_json::to_value(AppError::InvalidArgument("bad margin".to_string())).unwrap(),
+      serde_json::json!({ "code": "INVALID_ARGUMENT", "message": "bad margin" })
+    );
+  }
+
+  #[test]
+  fn platform_errors_carry_the_platform_as_details() {
+    let value = serde_json::to_value(AppError::PlatformUnsupported("macOS only".to_string())).unwrap();
+    assert_eq!(value["code"], "PLATFORM_UNSUPPORTED");
+    assert_eq!(value["details"]["platform"], std::env::consts::OS);
+  }
+
+  #[test]
+  fn every_variant_uses_a_listed_code() {
+    let errors = [
+      AppError::WindowNotFound,
+      AppError::MonitorNotFound,
+      AppError::StoreError(String::new()),
+      AppError::InvalidArgument(String::new()),
+      AppError::PlatformUnsupported(String::new()),
+      AppError::Timeout(String::new()),
+      AppError::Other(String::new()),
+    ];
+    for e in errors {
+      assert!(error_codes::ALL.contains(&e.code()), "{:?}", e);
+    }
+  }
+
+  #[test]
+  fn plain_strings_become_other() {
+    let e: AppError = "something broke".into();
+    assert_eq!(e, AppError::Other("something broke".to_string()));
+    assert_eq!(String::from(AppError::MonitorNotFound), "No monitor found");
+  }
+
+  #[test]
+  fn void_commands_answer_status_ok() {
+    let result: CommandResult = Ok(ok_status());
+    assert_eq!(serde_json::to_value(result.unwrap()).unwrap(), serde_json::json!({ "status": "ok" }));
+  }
+}
@@ -0,0 +1,34 @@
+// Stable `code` values of a serialized `AppError`. The frontend switches on
+// these, so never rename one; add a new code instead.
+
+pub const WINDOW_NOT_FOUND: &str = "WINDOW_NOT_FOUND";
+pub const MONITOR_NOT_FOUND: &str = "MONITOR_NOT_FOUND";
+pub const STORE_ERROR: &str = "STORE_ERROR";
+pub const INVALID_ARGUMENT: &str = "INVALID_ARGUMENT";
+pub const PLATFORM_UNSUPPORTED: &str = "PLATFORM_UNSUPPORTED";
+pub const TIMEOUT: &str = "TIMEOUT";
+pub const INTERNAL: &str = "INTERNAL";
+
+/// Every code, for checks that they stay unique and well-formed.
+pub const ALL: &[&str] = &[
+  WINDOW_NOT_FOUND,
+  MONITOR_NOT_FOUND,
+  STORE_ERROR,
+  INVALID_ARGUMENT,
+  PLATFORM_UNSUPPORTED,
+  TIMEOUT,
+  INTERNAL,
+];
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn codes_are_unique_screaming_snake_case() {
+    for (i, code) in ALL.iter().enumerate() {
+      assert!(code.chars().all(|c| c.is_ascii_uppercase() || c == '_'), "{}", code);
+      assert!(!ALL[..i].contains(code), "duplicate code {}", code);
+    }
+  }
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, WindowEvent};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::state::AppState;
+use crate::{controller, profiles, quit};
+
+pub const ESCAPE_ACTION_KEY: &str = "escape_action";
+
+/// What Escape does in the panel, once the webview has nothing of its own
+/// (a menu, the dashboard) to close.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EscapeAction {
+  #[default]
+  None,
+  Hide,
+  Collapse,
+}
+
+pub fn load(app: &AppHandle) -> EscapeAction {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(ESCAPE_ACTION_KEY))
+    .and_then(|value| serde_json::from_value(value).ok())
+    .unwrap_or_default()
+}
+
+/// The action to run for an Escape press, if any. Presses that arrive while
+/// the panel isn't focused (a stale keydown racing a focus change) are
+/// dropped.
+pub fn action_for(setting: EscapeAction, focused: bool) -> Option<EscapeAction> {
+  (focused && setting != EscapeAction::None).then_some(setting)
+}
+
+/// Keeps `AppState::panel_focused` in step with the panel's focus events.
+/// Escape used to be a global shortcut, which took the key from every other
+/// app; now the webview forwards it (`handle_escape`) and this decides
+/// whether it counts. The quit key is bound only while focused for the same
+/// reason.
+pub fn track_focus(app: &AppHandle) {
+  let Some(window) = app.get_webview_window("panel") else {
+    return;
+  };
+  if let Ok(focused) = window.is_focused() {
+    *app.state::<AppState>().panel_focused.lock().unwrap() = focused;
+    quit::sync_quit_key(app);
+  }
+  let handle = app.clone();
+  window.on_window_event(move |event| {
+    if let WindowEvent::Focused(focused) = event {
+      *handle.state::<AppState>().panel_focused.lock().unwrap() = *focused;
+      quit::sync_quit_key(&handle);
+    }
+  });
+}
+
+/// Called by the webview on Escape. Returns whether anything happened.
+#[tauri::command]
+pub fn handle_escape(app: AppHandle) -> Result<bool, AppError> {
+  audit::run(app.clone(), "handle_escape", move || {
+    log::info!("handle_escape invoked");
+
+    let focused = *app.state::<AppState>().panel_focused.lock().map_err(|e| e.to_string())?;
+    match action_for(load(&app), focused) {
+      Some(EscapeAction::Hide) => controller::hide_panel(&app),
+      Some(EscapeAction::Collapse) => controller::show_in_mode(&app, "collapsed")?,
+      Some(EscapeAction::None) | None => return Ok(false),
+    }
+    Ok(true)
+  })
+}
+
+#[tauri::command]
+pub fn set_escape_action(app: AppHandle, action: EscapeAction) -> CommandResult {
+  audit::run(app.clone(), "set_escape_action", move || {
+    log::info!("set_escape_action: action={:?}", action);
+
+    let store = profiles::settings_store(&app)?;
+    store.set(ESCAPE_ACTION_KEY, serde_json::to_value(action).map_err(|e| e.to_string())?);
+    store.save()?;
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Manual check: with escape_action = "hide", Escape in the focused panel
+  // hides it, while Escape in another app (e.g. vim) is left alone.
+  #[test]
+  fn escape_only_acts_on_a_focused_panel() {
+    assert_eq!(action_for(EscapeAction::Hide, true), Some(EscapeAction::Hide));
+    assert_eq!(action_for(EscapeAction::Collapse, true), Some(EscapeAction::Collapse));
+    assert_eq!(action_for(EscapeAction::Hide, false), None);
+    assert_eq!(action_for(EscapeAction::None, true), None);
+    let parsed: EscapeAction = serde_json::from_value(serde_json::json!("collapse")).unwrap();
+    assert_eq!(parsed, EscapeAction::Collapse);
+  }
+}
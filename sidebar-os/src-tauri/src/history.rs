@@ -0,0 +1,268 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use tauri::{
+  AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, Position, Size, WebviewWindow,
+};
+
+use crate::audit;
+use crate::autosave;
+use crate::controller;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::monitors;
+use crate::stacking;
+use crate::state::AppState;
+use crate::validate::Validate;
+
+const MAX_HISTORY: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geometry {
+  pub position: PhysicalPosition<i32>,
+  pub size: PhysicalSize<u32>,
+}
+
+/// Everything `capture_geometry` hands the frontend (physical pixels), for
+/// its own undo stack.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+  pub x: i32,
+  pub y: i32,
+  pub width: u32,
+  pub height: u32,
+  #[serde(default)]
+  pub monitor: Option<String>,
+  pub always_on_top: bool,
+}
+
+/// Undo/redo stacks of panel geometry. Recording a new change clears redo.
+#[derive(Debug, Default)]
+pub struct WindowHistory {
+  undo: VecDeque<Geometry>,
+  redo: Vec<Geometry>,
+}
+
+impl WindowHistory {
+  pub fn record(&mut self, before: Geometry) {
+    // Repeated calls that don't move the window shouldn't fill the stack.
+    if self.undo.back() != Some(&before) {
+      self.undo.push_back(before);
+      if self.undo.len() > MAX_HISTORY {
+        self.undo.pop_front();
+      }
+    }
+    self.redo.clear();
+  }
+
+  /// Swaps `current` for the previous geometry, if there is one.
+  pub fn undo(&mut self, current: Geometry) -> Option<Geometry> {
+    let previous = self.undo.pop_back()?;
+    self.redo.push(current);
+    Some(previous)
+  }
+
+  pub fn redo(&mut self, current: Geometry) -> Option<Geometry> {
+    let next = self.redo.pop()?;
+    self.undo.push_back(current);
+    Some(next)
+  }
+
+  pub fn depth(&self) -> usize {
+    self.undo.len()
+  }
+}
+
+fn current_geometry(window: &WebviewWindow) -> Result<Geometry, String> {
+  Ok(Geometry {
+    position: window.outer_position().map_err(|e| e.to_string())?,
+    size: window.outer_size().map_err(|e| e.to_string())?,
+  })
+}
+
+fn apply(window: &WebviewWindow, geometry: Geometry) -> Result<(), String> {
+  autosave::suppress(window);
+  window
+    .set_size(Size::Physical(geometry.size))
+    .map_err(|e| e.to_string())?;
+  window
+    .set_position(Position::Physical(geometry.position))
+    .map_err(|e| e.to_string())
+}
+
+/// Call before any command moves or resizes the panel.
+pub fn record(app: &AppHandle, window: &WebviewWindow) {
+  match current_geometry(window) {
+    Ok(before) => app.state::<AppState>().window_history.lock().unwrap().record(before),
+    Err(e) => log::warn!("could not record window history: {}", e),
+  }
+}
+
+#[tauri::command]
+pub fn undo_window_action(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "undo_window_action", move || {
+    log::info!("undo_window_action invoked");
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let current = current_geometry(&window)?;
+    let previous = app
+      .state::<AppState>()
+      .window_history
+      .lock()
+      .map_err(|e| e.to_string())?
+      .undo(current)
+      .ok_or("Nothing to undo")?;
+
+    apply(&window, previous)?;
+    Ok(ok_status())
+  })
+}
+
+#[tauri::command]
+pub fn redo_window_action(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "redo_window_action", move || {
+    log::info!("redo_window_action invoked");
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let current = current_geometry(&window)?;
+    let next = app
+      .state::<AppState>()
+      .window_history
+      .lock()
+      .map_err(|e| e.to_string())?
+      .redo(current)
+      .ok_or("Nothing to redo")?;
+
+    apply(&window, next)?;
+    Ok(ok_status())
+  })
+}
+
+#[tauri::command]
+pub fn capture_geometry(app: AppHandle) -> Result<WindowGeometry, AppError> {
+  audit::run(app.clone(), "capture_geometry", move || {
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let current = current_geometry(&window)?;
+    let monitor = window.current_monitor().map_err(|e| e.to_string())?;
+    Ok(WindowGeometry {
+      x: current.position.x,
+      y: current.position.y,
+      width: current.size.width,
+      height: current.size.height,
+      monitor: monitor.and_then(|m| m.name().cloned()),
+      always_on_top: stacking::is_always_on_top(&window).map_err(|e| e.to_string())?,
+    })
+  })
+}
+
+/// The captured monitor if it's still connected, else whichever one is
+/// under the saved position, else the panel's current one.
+fn target_monitor(window: &WebviewWindow, geometry: &WindowGeometry) -> Result<Monitor, String> {
+  let by_name = window
+    .available_monitors()
+    .map_err(|e| e.to_string())?
+    .into_iter()
+    .find(|m| geometry.monitor.is_some() && m.name() == geometry.monitor.as_ref());
+  if let Some(monitor) = by_name {
+    return Ok(monitor);
+  }
+  log::debug!("monitor {:?} is gone; clamping to another", geometry.monitor);
+  window
+    .monitor_from_point(geometry.x as f64, geometry.y as f64)
+    .map_err(|e| e.to_string())?
+    .or(monitors::panel_monitor(window)?)
+    .ok_or("No monitor found".to_string())
+}
+
+/// Puts back a `capture_geometry` result, clamped onto its monitor (or
+/// another one if it was unplugged). Recorded in the backend undo history
+/// too.
+#[tauri::command]
+pub fn restore_geometry(app: AppHandle, geometry: WindowGeometry) -> CommandResult {
+  audit::run(app.clone(), "restore_geometry", move || {
+    log::info!("restore_geometry: {:?}", geometry);
+
+    geometry.validate()?;
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let monitor = target_monitor(&window, &geometry)?;
+    let (origin, area) = monitors::usable_area(&app, &monitor);
+    let (position, size) = controller::clamp_geometry(
+      origin,
+      area,
+      PhysicalPosition { x: geometry.x, y: geometry.y },
+      PhysicalSize { width: geometry.width, height: geometry.height },
+    );
+
+    record(&app, &window);
+    apply(&window, Geometry { position, size })?;
+    stacking::set_always_on_top(&window, geometry.always_on_top)?;
+    Ok(ok_status())
+  })
+}
+
+#[tauri::command]
+pub fn get_undo_stack_depth(app: AppHandle) -> Result<usize, AppError> {
+  audit::run(app.clone(), "get_undo_stack_depth", move || {
+    let state = app.state::<AppState>();
+    let history = state.window_history.lock().map_err(|e| e.to_string())?;
+    Ok(history.depth())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn at(x: i32) -> Geometry {
+    Geometry {
+      position: PhysicalPosition { x, y: 0 },
+      size: PhysicalSize { width: 100, height: 100 },
+    }
+  }
+
+  #[test]
+  fn undo_then_redo_round_trips() {
+    let mut history = WindowHistory::default();
+    history.record(at(0));
+    history.record(at(1));
+
+    assert_eq!(history.undo(at(2)), Some(at(1)));
+    assert_eq!(history.undo(at(1)), Some(at(0)));
+    assert_eq!(history.undo(at(0)), None);
+
+    assert_eq!(history.redo(at(0)), Some(at(1)));
+    assert_eq!(history.redo(at(1)), Some(at(2)));
+    assert_eq!(history.redo(at(2)), None);
+    assert_eq!(history.depth(), 2);
+  }
+
+  #[test]
+  fn new_change_clears_redo() {
+    let mut history = WindowHistory::default();
+    history.record(at(0));
+    history.undo(at(1));
+    history.record(at(0));
+    assert_eq!(history.redo(at(5)), None);
+  }
+
+  #[test]
+  fn captured_geometry_round_trips_without_a_monitor() {
+    let json = serde_json::json!({
+      "x": -10, "y": 5, "width": 800, "height": 600, "always_on_top": true
+    });
+    let geometry: WindowGeometry = serde_json::from_value(json).unwrap();
+    assert_eq!(geometry.monitor, None);
+    assert_eq!(serde_json::to_value(&geometry).unwrap()["x"], -10);
+  }
+
+  #[test]
+  fn keeps_only_the_most_recent_entries() {
+    let mut history = WindowHistory::default();
+    for x in 0..(MAX_HISTORY as i32 + 5) {
+      history.record(at(x));
+    }
+    assert_eq!(history.depth(), MAX_HISTORY);
+
+    history.record(at(MAX_HISTORY as i32 + 4));
+    assert_eq!(history.depth(), MAX_HISTORY);
+  }
+}
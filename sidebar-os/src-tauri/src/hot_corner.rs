@@ -0,0 +1,228 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, Position};
+
+use crate::audit;
+use crate::autosave;
+use crate::error::{ok_status, CommandResult};
+use crate::journal;
+use crate::monitors;
+use crate::profiles;
+use crate::stacking;
+use crate::state::AppState;
+use crate::usage::{self, ShowTrigger};
+
+pub const HOT_CORNER_KEY: &str = "hot_corner";
+// How long the cursor has to sit in the corner before the panel shows.
+const DWELL: Duration = Duration::from_millis(300);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+// Polling slows down while no corner is configured.
+const IDLE_INTERVAL: Duration = Duration::from_millis(1000);
+// Pixels from the exact corner that still count as "in" it.
+const CORNER_TOLERANCE: f64 = 4.0;
+const CORNER_MARGIN: i32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Corner {
+  TopLeft,
+  TopRight,
+  BottomLeft,
+  BottomRight,
+}
+
+impl Corner {
+  pub fn parse(value: &str) -> Option<Corner> {
+    match value.to_lowercase().as_str() {
+      "top-left" => Some(Corner::TopLeft),
+      "top-right" => Some(Corner::TopRight),
+      "bottom-left" => Some(Corner::BottomLeft),
+      "bottom-right" => Some(Corner::BottomRight),
+      _ => None,
+    }
+  }
+}
+
+fn cursor_in_corner(
+  cursor: PhysicalPosition<f64>,
+  monitor_position: PhysicalPosition<i32>,
+  monitor_size: PhysicalSize<u32>,
+  corner: Corner,
+) -> bool {
+  let left = monitor_position.x as f64;
+  let top = monitor_position.y as f64;
+  let right = left + monitor_size.width as f64 - 1.0;
+  let bottom = top + monitor_size.height as f64 - 1.0;
+
+  let near_x = match corner {
+    Corner::TopLeft | Corner::BottomLeft => cursor.x - left <= CORNER_TOLERANCE,
+    Corner::TopRight | Corner::BottomRight => right - cursor.x <= CORNER_TOLERANCE,
+  };
+  let near_y = match corner {
+    Corner::TopLeft | Corner::TopRight => cursor.y - top <= CORNER_TOLERANCE,
+    Corner::BottomLeft | Corner::BottomRight => bottom - cursor.y <= CORNER_TOLERANCE,
+  };
+
+  near_x && near_y
+}
+
+fn show_panel_in_corner(app: &AppHandle, corner: Corner) {
+  usage::record_show(app, ShowTrigger::HotCorner);
+  let Some(window) = app.get_webview_window("panel") else {
+    return;
+  };
+
+  // A pinned monitor wins over the one whose corner was hit.
+  let monitor = monitors::pinned(app).or_else(|| {
+    let cursor = app.cursor_position().ok()?;
+    app.monitor_from_point(cursor.x, cursor.y).ok().flatten()
+  });
+  if let (Some(monitor), Ok(window_size)) = (monitor, window.outer_size()) {
+    let (origin, size) = monitors::usable_area(app, &monitor);
+    let (x, y) = crate::calculate_corner_position(
+      origin,
+      size,
+      window_size,
+      CORNER_MARGIN,
+      corner,
+    );
+    autosave::suppress(&window);
+    let _ = window.set_position(Position::Physical(PhysicalPosition { x, y }));
+  }
+
+  let _ = window.show();
+  let _ = window.set_focus();
+  let _ = stacking::set_always_on_top(&window, true);
+  let _ = journal::emit(app, "panel-should-expand", ());
+}
+
+/// Polls the cursor on a background thread and shows the panel once it has
+/// dwelled in the configured corner.
+pub fn start_watcher(app: AppHandle) {
+  std::thread::spawn(move || {
+    let mut entered_at: Option<Instant> = None;
+    let mut fired = false;
+
+    loop {
+      let corner = *app.state::<AppState>().hot_corner.lock().unwrap();
+      let Some(corner) = corner else {
+        entered_at = None;
+        fired = false;
+        std::thread::sleep(IDLE_INTERVAL);
+        continue;
+      };
+
+      let in_corner = app
+        .cursor_position()
+        .ok()
+        .and_then(|cursor| {
+          let monitor = app.monitor_from_point(cursor.x, cursor.y).ok()??;
+          Some(cursor_in_corner(
+            cursor,
+            monitor.position().to_owned(),
+            monitor.size().to_owned(),
+            corner,
+          ))
+        })
+        .unwrap_or(false);
+
+      if in_corner {
+        let since = *entered_at.get_or_insert_with(Instant::now);
+        if !fired && since.elapsed() >= DWELL {
+          log::info!("hot corner {:?} triggered; showing panel", corner);
+          show_panel_in_corner(&app, corner);
+          // Only fire once per visit; the cursor has to leave and come back.
+          fired = true;
+        }
+      } else {
+        entered_at = None;
+        fired = false;
+      }
+
+      std::thread::sleep(POLL_INTERVAL);
+    }
+  });
+}
+
+/// Reads the persisted corner into managed state before the watcher starts.
+pub fn load_from_store(app: &AppHandle) {
+  let corner = profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(HOT_CORNER_KEY))
+    .and_then(|value| serde_json::from_value::<Corner>(value).ok());
+
+  if let Some(corner) = corner {
+    log::debug!("hot corner restored from settings: {:?}", corner);
+  }
+  *app.state::<AppState>().hot_corner.lock().unwrap() = corner;
+}
+
+#[tauri::command]
+pub fn set_hot_corner(app: AppHandle, corner: Option<String>) -> CommandResult {
+  audit::run(app.clone(), "set_hot_corner", move || {
+    log::info!("set_hot_corner: corner={:?}", corner);
+
+    let parsed = match corner.as_deref() {
+      Some(value) => Some(Corner::parse(value).ok_or(format!("Unknown corner: {}", value))?),
+      None => None,
+    };
+
+    let store = profiles::settings_store(&app)?;
+    match parsed {
+      Some(c) => store.set(HOT_CORNER_KEY, serde_json::to_value(c).map_err(|e| e.to_string())?),
+      None => {
+        store.delete(HOT_CORNER_KEY);
+      }
+    }
+    store.save().map_err(|e| e.to_string())?;
+
+    let state = app.state::<AppState>();
+    *state.hot_corner.lock().map_err(|e| e.to_string())? = parsed;
+
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const ORIGIN: PhysicalPosition<i32> = PhysicalPosition { x: 0, y: 0 };
+  const MONITOR: PhysicalSize<u32> = PhysicalSize { width: 1920, height: 1080 };
+
+  #[test]
+  fn parses_corner_names() {
+    assert_eq!(Corner::parse("top-left"), Some(Corner::TopLeft));
+    assert_eq!(Corner::parse("Bottom-Right"), Some(Corner::BottomRight));
+    assert_eq!(Corner::parse("middle"), None);
+  }
+
+  #[test]
+  fn detects_cursor_in_each_corner() {
+    let cases = [
+      (PhysicalPosition { x: 0.0, y: 0.0 }, Corner::TopLeft),
+      (PhysicalPosition { x: 1919.0, y: 0.0 }, Corner::TopRight),
+      (PhysicalPosition { x: 2.0, y: 1077.0 }, Corner::BottomLeft),
+      (PhysicalPosition { x: 1919.0, y: 1079.0 }, Corner::BottomRight),
+    ];
+    for (cursor, corner) in cases {
+      assert!(cursor_in_corner(cursor, ORIGIN, MONITOR, corner), "{:?}", corner);
+    }
+  }
+
+  #[test]
+  fn ignores_cursor_away_from_corner() {
+    let cursor = PhysicalPosition { x: 960.0, y: 0.0 };
+    assert!(!cursor_in_corner(cursor, ORIGIN, MONITOR, Corner::TopLeft));
+    assert!(!cursor_in_corner(cursor, ORIGIN, MONITOR, Corner::TopRight));
+  }
+
+  #[test]
+  fn respects_monitor_offset() {
+    let position = PhysicalPosition { x: 1920, y: 0 };
+    let cursor = PhysicalPosition { x: 1920.0, y: 0.0 };
+    assert!(cursor_in_corner(cursor, position, MONITOR, Corner::TopLeft));
+    assert!(!cursor_in_corner(cursor, ORIGIN, MONITOR, Corner::TopLeft));
+  }
+}
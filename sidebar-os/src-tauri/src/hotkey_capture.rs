@@ -0,0 +1,255 @@
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::hotkeys::{self, Platform};
+use crate::journal;
+use crate::quit;
+use crate::state::AppState;
+
+// Capture gives up on its own after this long.
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A running `start_hotkey_capture`.
+#[derive(Debug)]
+pub struct Capture {
+  generation: u64,
+  // Whether capture paused the app's shortcuts, and so resumes them.
+  paused: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Modifier {
+  Ctrl,
+  Alt,
+  Shift,
+  Super,
+}
+
+/// Rewrites `accelerator` the one way the settings page shows it: modifiers
+/// in Ctrl, Alt, Shift, Cmd order (`Super` off macOS), then the key, so
+/// `shift+control+KeyK` becomes `Ctrl+Shift+K`. `CmdOrCtrl` picks per
+/// `platform`.
+pub fn normalize_accelerator(accelerator: &str, platform: Platform) -> Result<String, String> {
+  let mut modifiers = Vec::new();
+  let mut key = None;
+  for token in accelerator.split('+').map(str::trim) {
+    let modifier = match token.to_lowercase().as_str() {
+      "ctrl" | "control" => Modifier::Ctrl,
+      "alt" | "option" => Modifier::Alt,
+      "shift" => Modifier::Shift,
+      "cmd" | "command" | "super" | "meta" => Modifier::Super,
+      "cmdorctrl" | "cmdorcontrol" | "commandorcontrol" | "commandorctrl" => {
+        if platform == Platform::Macos {
+          Modifier::Super
+        } else {
+          Modifier::Ctrl
+        }
+      }
+      "" => return Err(format!("Empty key in accelerator: {}", accelerator)),
+      _ => {
+        if key.replace(token).is_some() {
+          return Err(format!("More than one key in accelerator: {}", accelerator));
+        }
+        continue;
+      }
+    };
+    if !modifiers.contains(&modifier) {
+      modifiers.push(modifier);
+    }
+  }
+  let key = key.ok_or_else(|| format!("No key in accelerator: {}", accelerator))?;
+
+  let order = [Modifier::Ctrl, Modifier::Alt, Modifier::Shift, Modifier::Super];
+  let mut parts: Vec<String> = order
+    .iter()
+    .filter(|m| modifiers.contains(m))
+    .map(|m| match m {
+      Modifier::Ctrl => "Ctrl",
+      Modifier::Alt => "Alt",
+      Modifier::Shift => "Shift",
+      Modifier::Super if platform == Platform::Macos => "Cmd",
+      Modifier::Super => "Super",
+    })
+    .map(String::from)
+    .collect();
+  let key = key.strip_prefix("Key").or(key.strip_prefix("Digit")).unwrap_or(key);
+  let mut chars = key.chars();
+  let first = chars.next().map(|c| c.to_ascii_uppercase()).unwrap_or_default();
+  parts.push(format!("{}{}", first, chars.as_str()));
+  Ok(parts.join("+"))
+}
+
+/// Whether `accelerator` (normalized) holds a modifier besides Shift. Shift
+/// alone doesn't make a chord; it's just typing.
+pub fn is_chord(accelerator: &str) -> bool {
+  let mut parts: Vec<&str> = accelerator.split('+').collect();
+  parts.pop();
+  parts.iter().any(|m| *m != "Shift")
+}
+
+/// Ends the capture `generation` (any capture if None) and resumes the app's
+/// shortcuts. False if that capture already ended.
+fn finish(app: &AppHandle, generation: Option<u64>) -> bool {
+  let state = app.state::<AppState>();
+  let capture = {
+    let mut current = state.hotkey_capture.lock().unwrap();
+    match current.take() {
+      Some(c) if generation.is_none_or(|g| g == c.generation) => c,
+      other => {
+        *current = other;
+        return false;
+      }
+    }
+  };
+  if capture.paused {
+    let _ = hotkeys::resume_all_shortcuts(app.clone());
+  }
+  quit::sync_quit_key(app);
+  true
+}
+
+fn emit_ended(app: &AppHandle, reason: &str) {
+  let _ = journal::emit(app, "hotkey-capture-ended", serde_json::json!({ "reason": reason }));
+}
+
+/// Starts waiting for the next modifier+key chord pressed in the panel,
+/// which forwards it with `submit_hotkey_capture`; nothing is registered
+/// with the OS, so other apps keep their keys. The app's own shortcuts are
+/// paused meanwhile, so the chord doesn't also run its action. Ends with
+/// `hotkey-capture-ended` after 10 seconds or `cancel_hotkey_capture`.
+/// Starting again restarts it.
+#[tauri::command]
+pub fn start_hotkey_capture(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "start_hotkey_capture", move || {
+    log::info!("start_hotkey_capture invoked");
+
+    if finish(&app, None) {
+      emit_ended(&app, "restarted");
+    }
+    let state = app.state::<AppState>();
+    let generation = {
+      let mut next = state.next_capture.lock().map_err(|e| e.to_string())?;
+      *next += 1;
+      *next
+    };
+    let paused = state.paused_shortcuts.lock().map_err(|e| e.to_string())?.is_none();
+    if paused {
+      hotkeys::pause_all_shortcuts(app.clone())?;
+    }
+    *state.hotkey_capture.lock().map_err(|e| e.to_string())? = Some(Capture { generation, paused });
+    // The quit key is a real shortcut; the panel has to see that chord too.
+    quit::sync_quit_key(&app);
+    let _ = journal::emit(&app, "hotkey-capture-started", ());
+
+    let handle = app.clone();
+    std::thread::spawn(move || {
+      std::thread::sleep(CAPTURE_TIMEOUT);
+      if finish(&handle, Some(generation)) {
+        log::debug!("hotkey capture timed out");
+        emit_ended(&handle, "timeout");
+      }
+    });
+    Ok(ok_status())
+  })
+}
+
+/// The chord the panel saw during capture, as `Ctrl+Shift+KeyK`-style
+/// keydown names. Ends capture and sends it, normalized, as
+/// `hotkey-captured`; a chord that can't be a shortcut is refused and
+/// capture keeps waiting.
+#[tauri::command]
+pub fn submit_hotkey_capture(app: AppHandle, accelerator: String) -> Result<String, AppError> {
+  audit::run(app.clone(), "submit_hotkey_capture", move || {
+    log::info!("submit_hotkey_capture: accelerator={}", accelerator);
+
+    let generation = app
+      .state::<AppState>()
+      .hotkey_capture
+      .lock()
+      .map_err(|e| e.to_string())?
+      .as_ref()
+      .map(|c| c.generation)
+      .ok_or_else(|| AppError::InvalidArgument("No hotkey capture is running".to_string()))?;
+    let normalized =
+      normalize_accelerator(&accelerator, Platform::current()).map_err(AppError::InvalidArgument)?;
+    if !is_chord(&normalized) {
+      let message = format!("{} needs a modifier other than Shift", normalized);
+      return Err(AppError::InvalidArgument(message));
+    }
+    hotkeys::parse_accelerator(&normalized).map_err(AppError::InvalidArgument)?;
+
+    if !finish(&app, Some(generation)) {
+      return Err(AppError::InvalidArgument("No hotkey capture is running".to_string()));
+    }
+    log::info!("captured hotkey {}", normalized);
+    let payload = serde_json::json!({ "accelerator": normalized });
+    let _ = journal::emit(&app, "hotkey-captured", payload);
+    Ok(normalized)
+  })
+}
+
+/// Stops a running capture. Returns false if none was running.
+#[tauri::command]
+pub fn cancel_hotkey_capture(app: AppHandle) -> Result<bool, AppError> {
+  audit::run(app.clone(), "cancel_hotkey_capture", move || {
+    log::info!("cancel_hotkey_capture invoked");
+
+    let cancelled = finish(&app, None);
+    if cancelled {
+      emit_ended(&app, "cancelled");
+    }
+    Ok(cancelled)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn normalizes_modifier_order_and_names() {
+    let mac = |a: &str| normalize_accelerator(a, Platform::Macos);
+    let linux = |a: &str| normalize_accelerator(a, Platform::Linux);
+    assert_eq!(mac("shift+control+KeyK"), Ok("Ctrl+Shift+K".to_string()));
+    assert_eq!(mac("super+alt+Space"), Ok("Alt+Cmd+Space".to_string()));
+    assert_eq!(linux("super+alt+Space"), Ok("Alt+Super+Space".to_string()));
+    assert_eq!(mac("CmdOrCtrl+Digit1"), Ok("Cmd+1".to_string()));
+    assert_eq!(linux("CmdOrCtrl+Digit1"), Ok("Ctrl+1".to_string()));
+    assert_eq!(linux("ctrl+Ctrl+f5"), Ok("Ctrl+F5".to_string()));
+  }
+
+  #[test]
+  fn rejects_accelerators_without_exactly_one_key() {
+    let linux = |a: &str| normalize_accelerator(a, Platform::Linux);
+    assert!(linux("Ctrl+Shift").is_err());
+    assert!(linux("Ctrl+A+B").is_err());
+    assert!(linux("Ctrl++").is_err());
+  }
+
+  #[test]
+  fn keydown_chords_normalize_to_registrable_accelerators() {
+    let cases = [
+      ("Shift+Ctrl+KeyK", "Ctrl+Shift+K"),
+      ("Meta+Alt+Space", "Alt+Super+Space"),
+      ("Ctrl+Digit1", "Ctrl+1"),
+      ("Alt+Shift+F5", "Alt+Shift+F5"),
+    ];
+    for (pressed, expected) in cases {
+      let normalized = normalize_accelerator(pressed, Platform::Linux).unwrap();
+      assert_eq!(normalized, expected);
+      assert!(is_chord(&normalized), "{}", normalized);
+      assert!(hotkeys::parse_accelerator(&normalized).is_ok(), "{}", normalized);
+    }
+    assert_eq!(normalize_accelerator("Meta+KeyJ", Platform::Macos), Ok("Cmd+J".to_string()));
+  }
+
+  #[test]
+  fn shift_alone_is_not_a_chord() {
+    assert!(!is_chord("Shift+K"));
+    assert!(!is_chord("K"));
+    assert!(is_chord("Ctrl+Shift+K"));
+  }
+}
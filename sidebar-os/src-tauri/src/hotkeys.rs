@@ -0,0 +1,1461 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::audit;
+use crate::controller;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::journal;
+use crate::profiles;
+use crate::quit;
+use crate::state::AppState;
+use crate::tray;
+use crate::usage::ShowTrigger;
+use crate::validate::Validate;
+
+pub const HOTKEYS_KEY: &str = "hotkeys";
+// Platform the stored hotkeys were seeded for; see `seed_config`.
+pub const HOTKEYS_PLATFORM_KEY: &str = "hotkeys_platform";
+// Cmd+1 only tells the webview to toggle, leaving the window to it.
+pub const TOGGLE_NOTIFY_ONLY_KEY: &str = "toggle_collapse_notify_only";
+pub const HOTKEY_BEHAVIOR_KEY: &str = "hotkey_behavior";
+pub const HOTKEY_CYCLES_MONITORS_KEY: &str = "hotkey_cycles_monitors";
+// Show-panel presses closer together than this count as one.
+const SUMMON_DEBOUNCE: Duration = Duration::from_millis(250);
+const SUSPENDED_MESSAGE: &str = "Hotkeys are suspended; call resume_hotkeys first";
+
+// Seeded into the config on first run. There's no Cmd key off macOS.
+const MACOS_DEFAULTS: &[(ShortcutAction, &[&str])] = &[
+  (ShortcutAction::ShowPanel, &["Alt+Cmd+Space", "Ctrl+Space", "Cmd+Shift+Space"]),
+  (ShortcutAction::ToggleCollapse, &["Cmd+1"]),
+];
+const OTHER_DEFAULTS: &[(ShortcutAction, &[&str])] = &[
+  (ShortcutAction::ShowPanel, &["Ctrl+Alt+Space", "Ctrl+Shift+Space"]),
+  (ShortcutAction::ToggleCollapse, &["Ctrl+1"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+  Macos,
+  Windows,
+  Linux,
+}
+
+impl Platform {
+  pub fn current() -> Self {
+    if cfg!(target_os = "macos") {
+      Platform::Macos
+    } else if cfg!(target_os = "windows") {
+      Platform::Windows
+    } else {
+      Platform::Linux
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShortcutAction {
+  ShowPanel,
+  ToggleCollapse,
+  // Unbound by default.
+  CyclePosition,
+  // Unbound by default; Cmd+Q quits while the panel has focus instead. See
+  // `quit::sync_quit_key`.
+  QuitApp,
+  // Unbound by default; see `controller::show_explicitly`.
+  CollapsePanel,
+  ExpandPanel,
+  // Unbound by default. Held rather than pressed; see `controller::start_peek`.
+  PeekPanel,
+}
+
+/// What the show-panel hotkeys do when the panel is already in front.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HotkeyBehavior {
+  #[default]
+  ShowOnly,
+  Toggle,
+}
+
+/// Accelerators bound to an action. A bare accelerator string reads as a
+/// single enabled binding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BindingRepr")]
+pub struct HotkeyBinding {
+  pub accelerators: Vec<String>,
+  pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+  true
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BindingRepr {
+  Accelerator(String),
+  Full {
+    accelerators: Vec<String>,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+  },
+}
+
+impl From<BindingRepr> for HotkeyBinding {
+  fn from(repr: BindingRepr) -> Self {
+    match repr {
+      BindingRepr::Accelerator(accelerator) => {
+        HotkeyBinding { accelerators: vec![accelerator], enabled: true }
+      }
+      BindingRepr::Full { accelerators, enabled } => HotkeyBinding { accelerators, enabled },
+    }
+  }
+}
+
+/// A shortcut the OS (or the parser) refused. `action` is None for
+/// shortcuts that aren't part of the config.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShortcutFailure {
+  pub accelerator: String,
+  pub action: Option<ShortcutAction>,
+  pub error: String,
+}
+
+/// Why a hotkey command failed. Bad input goes back as `INVALID_ARGUMENT`,
+/// the rest as `INTERNAL`.
+#[derive(Debug, PartialEq)]
+pub enum HotkeyError {
+  UnknownAction(String),
+  InvalidAccelerator(String),
+  AlreadyTaken(String),
+  RegistrationFailed(String),
+  Other(String),
+}
+
+impl From<HotkeyError> for AppError {
+  fn from(e: HotkeyError) -> Self {
+    match e {
+      HotkeyError::UnknownAction(message)
+      | HotkeyError::InvalidAccelerator(message)
+      | HotkeyError::AlreadyTaken(message) => AppError::InvalidArgument(message),
+      HotkeyError::RegistrationFailed(message) | HotkeyError::Other(message) => {
+        AppError::Other(message)
+      }
+    }
+  }
+}
+
+/// Registration status of one configured accelerator; a row in the settings
+/// page's hotkey table, so keep the field names stable.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HotkeyStatus {
+  pub action: ShortcutAction,
+  pub accelerator: String,
+  pub registered: bool,
+  pub last_error: Option<String>,
+}
+
+/// Action and accelerator of a shortcut unregistered by
+/// `pause_all_shortcuts` or `suspend_hotkeys`.
+pub type PausedShortcut = (ShortcutAction, String);
+
+/// One row of `get_global_shortcut_list`. `action` is None for shortcuts
+/// outside the config, like in `ShortcutFailure`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShortcutInfo {
+  pub shortcut: String,
+  pub action: Option<ShortcutAction>,
+  pub active: bool,
+}
+
+/// Result of `test_hotkey`: whether the OS would let us have `accelerator`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HotkeyTest {
+  pub accelerator: String,
+  pub available: bool,
+  pub error: Option<String>,
+}
+
+/// A two-step shortcut: `second` pressed within `timeout_ms` of `first`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Chord {
+  pub first: String,
+  pub second: String,
+  pub action: ShortcutAction,
+  pub timeout_ms: u64,
+}
+
+/// Action name → accelerators, persisted under `hotkeys` in settings.json.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HotkeyConfig(pub BTreeMap<ShortcutAction, HotkeyBinding>);
+
+impl Default for HotkeyConfig {
+  fn default() -> Self {
+    HotkeyConfig::defaults_for(Platform::current())
+  }
+}
+
+impl HotkeyConfig {
+  /// The default bindings on `platform`.
+  pub fn defaults_for(platform: Platform) -> Self {
+    let table = match platform {
+      Platform::Macos => MACOS_DEFAULTS,
+      Platform::Windows | Platform::Linux => OTHER_DEFAULTS,
+    };
+    let binding = |accelerators: &[&str]| HotkeyBinding {
+      accelerators: accelerators.iter().map(|a| a.to_string()).collect(),
+      enabled: true,
+    };
+    HotkeyConfig(table.iter().map(|(action, keys)| (*action, binding(keys))).collect())
+  }
+
+  /// Enabled (action, accelerator) pairs paired with their parse result.
+  fn resolve(&self) -> Vec<(ShortcutAction, String, Result<Shortcut, String>)> {
+    self
+      .0
+      .iter()
+      .filter(|(_, binding)| binding.enabled)
+      .flat_map(|(action, binding)| {
+        binding.accelerators.iter().map(move |accelerator| {
+          (*action, accelerator.clone(), parse_accelerator(accelerator))
+        })
+      })
+      .collect()
+  }
+
+  /// The action an enabled binding runs for `shortcut`. Accelerators are
+  /// compared parsed, so "cmd+1" finds "Cmd+1".
+  pub fn action_for(&self, shortcut: &Shortcut) -> Option<ShortcutAction> {
+    self
+      .resolve()
+      .into_iter()
+      .find(|(_, _, parsed)| parsed.as_ref() == Ok(shortcut))
+      .map(|(action, _, _)| action)
+  }
+
+  /// An action other than `action` whose enabled binding uses `shortcut`.
+  pub fn taken_by(&self, action: ShortcutAction, shortcut: &Shortcut) -> Option<ShortcutAction> {
+    self
+      .resolve()
+      .into_iter()
+      .find(|(other, _, parsed)| *other != action && parsed.as_ref() == Ok(shortcut))
+      .map(|(other, _, _)| other)
+  }
+
+  /// The first accelerator two enabled actions share, with both actions.
+  pub fn duplicate(&self) -> Option<(String, ShortcutAction, ShortcutAction)> {
+    let resolved = self.resolve();
+    resolved.iter().enumerate().find_map(|(i, (action, accelerator, parsed))| {
+      let parsed = parsed.as_ref().ok()?;
+      resolved[i + 1..]
+        .iter()
+        .find(|(other, _, p)| other != action && p.as_ref() == Ok(parsed))
+        .map(|(other, _, _)| (accelerator.clone(), *action, *other))
+    })
+  }
+
+  /// Enabled action → accelerators, as sent with `hotkeys-changed`.
+  pub fn effective(&self) -> BTreeMap<ShortcutAction, Vec<String>> {
+    self
+      .0
+      .iter()
+      .filter(|(_, binding)| binding.enabled)
+      .map(|(action, binding)| (*action, binding.accelerators.clone()))
+      .collect()
+  }
+}
+
+/// Status of each configured accelerator, from what we registered and the
+/// last failure seen for it.
+pub fn hotkey_statuses(
+  config: &HotkeyConfig,
+  registered: &[String],
+  errors: &BTreeMap<String, String>,
+) -> Vec<HotkeyStatus> {
+  config
+    .0
+    .iter()
+    .flat_map(|(action, binding)| {
+      binding.accelerators.iter().map(|accelerator| HotkeyStatus {
+        action: *action,
+        accelerator: accelerator.clone(),
+        registered: registered.contains(accelerator),
+        last_error: errors.get(accelerator).cloned(),
+      })
+    })
+    .collect()
+}
+
+/// What registering `config` leaves in effect: its enabled accelerators,
+/// minus the ones that didn't parse or that the OS refused last time.
+pub fn effective_shortcuts(
+  config: &HotkeyConfig,
+  errors: &BTreeMap<String, String>,
+) -> Vec<PausedShortcut> {
+  config
+    .resolve()
+    .into_iter()
+    .filter(|(_, accelerator, parsed)| parsed.is_ok() && !errors.contains_key(accelerator))
+    .map(|(action, accelerator, _)| (action, accelerator))
+    .collect()
+}
+
+/// Every configured shortcut, disabled ones included. `is_active` says
+/// whether it's registered now.
+pub fn shortcut_list(config: &HotkeyConfig, is_active: impl Fn(&str) -> bool) -> Vec<ShortcutInfo> {
+  config
+    .0
+    .iter()
+    .flat_map(|(action, binding)| {
+      binding.accelerators.iter().map(|accelerator| ShortcutInfo {
+        shortcut: accelerator.clone(),
+        action: Some(*action),
+        active: binding.enabled && is_active(accelerator),
+      })
+    })
+    .collect()
+}
+
+/// How long a chord may wait for its second key. The wait parks a thread
+/// and keeps `second` away from every other app, so it has to stay short.
+pub const CHORD_TIMEOUT_MS: std::ops::RangeInclusive<u64> = 100..=5000;
+
+pub fn check_chord_timeout(timeout_ms: u64) -> Result<(), String> {
+  if !CHORD_TIMEOUT_MS.contains(&timeout_ms) {
+    return Err(format!(
+      "Chord timeout must be {}-{} ms, got {}",
+      CHORD_TIMEOUT_MS.start(),
+      CHORD_TIMEOUT_MS.end(),
+      timeout_ms
+    ));
+  }
+  Ok(())
+}
+
+/// Checks a new chord against the config and the chords already registered.
+/// `first` has to be free: once it's registered for the chord the OS hands
+/// it to us instead of the focused app.
+pub fn check_chord(
+  config: &HotkeyConfig,
+  chords: &[Chord],
+  first: &str,
+  second: &str,
+) -> Result<(Shortcut, Shortcut), String> {
+  let first_key = parse_accelerator(first)?;
+  let second_key = parse_accelerator(second)?;
+  if first_key == second_key {
+    return Err("A chord needs two different shortcuts".to_string());
+  }
+  if let Some(action) = config.action_for(&first_key) {
+    return Err(format!("{} is already bound to {:?}", first, action));
+  }
+  let taken = chords.iter().any(|c| parse_accelerator(&c.first).ok() == Some(first_key));
+  if taken {
+    return Err(format!("{} already starts a chord", first));
+  }
+  Ok((first_key, second_key))
+}
+
+pub fn parse_accelerator(accelerator: &str) -> Result<Shortcut, String> {
+  Shortcut::from_str(accelerator).map_err(|e| e.to_string())
+}
+
+pub fn validate(config: &HotkeyConfig) -> Result<(), String> {
+  for (action, binding) in &config.0 {
+    for accelerator in &binding.accelerators {
+      parse_accelerator(accelerator)
+        .map_err(|e| format!("Invalid accelerator {:?} for {:?}: {}", accelerator, action, e))?;
+    }
+  }
+  if let Some((accelerator, first, second)) = config.duplicate() {
+    return Err(format!("{} is bound to both {:?} and {:?}", accelerator, first, second));
+  }
+  Ok(())
+}
+
+/// Tries to register `shortcut` and lets it go again. Ones we already hold
+/// count as available.
+fn probe(app: &AppHandle, shortcut: Shortcut) -> Result<(), String> {
+  let shortcuts = app.global_shortcut();
+  if shortcuts.is_registered(shortcut) {
+    return Ok(());
+  }
+  shortcuts.register(shortcut).map_err(|e| e.to_string())?;
+  shortcuts.unregister(shortcut).map_err(|e| e.to_string())
+}
+
+pub fn load_config(app: &AppHandle) -> HotkeyConfig {
+  let Some(value) = profiles::settings_store(app).ok().and_then(|store| store.get(HOTKEYS_KEY)) else {
+    return HotkeyConfig::default();
+  };
+
+  match serde_json::from_value(value) {
+    Ok(config) => config,
+    Err(e) => {
+      log::warn!("stored hotkey config is unreadable ({}); using defaults", e);
+      HotkeyConfig::default()
+    }
+  }
+}
+
+/// Whether the stored hotkeys should be replaced by `current`'s defaults:
+/// nothing is stored yet, or it was seeded on another platform (a settings
+/// file synced from a Mac). Configs from before the platform was recorded
+/// are kept.
+pub fn needs_reseed(has_config: bool, seeded_for: Option<Platform>, current: Platform) -> bool {
+  !has_config || seeded_for.is_some_and(|platform| platform != current)
+}
+
+fn save_seeded(app: &AppHandle, config: &HotkeyConfig) -> Result<(), String> {
+  let store = profiles::settings_store(app).map_err(|e| e.to_string())?;
+  store.set(HOTKEYS_KEY, serde_json::to_value(config).map_err(|e| e.to_string())?);
+  store.set(
+    HOTKEYS_PLATFORM_KEY,
+    serde_json::to_value(Platform::current()).map_err(|e| e.to_string())?,
+  );
+  store.save().map_err(|e| e.to_string())
+}
+
+/// Writes this platform's defaults if `needs_reseed` says so, and records the
+/// platform for older configs that don't have it.
+fn seed_config(app: &AppHandle) {
+  let Ok(store) = profiles::settings_store(app) else {
+    return;
+  };
+  let has_config = store.has(HOTKEYS_KEY);
+  let seeded_for: Option<Platform> =
+    store.get(HOTKEYS_PLATFORM_KEY).and_then(|value| serde_json::from_value(value).ok());
+  let current = Platform::current();
+
+  let result = if needs_reseed(has_config, seeded_for, current) {
+    if let Some(platform) = seeded_for {
+      log::info!("hotkeys were seeded for {:?}; using {:?} defaults", platform, current);
+    }
+    save_seeded(app, &HotkeyConfig::defaults_for(current))
+  } else if seeded_for.is_none() {
+    save_seeded(app, &load_config(app))
+  } else {
+    Ok(())
+  };
+  if let Err(e) = result {
+    log::warn!("could not seed hotkey config: {}", e);
+  }
+}
+
+fn notify_only(app: &AppHandle) -> bool {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(TOGGLE_NOTIFY_ONLY_KEY))
+    .and_then(|value| value.as_bool())
+    .unwrap_or(crate::settings::DEFAULT_TOGGLE_NOTIFY_ONLY)
+}
+
+/// Cmd+1: the controller shows, collapses or expands the panel, unless
+/// `toggle_collapse_notify_only` leaves it to the webview as before.
+fn toggle_collapse(app: &AppHandle) {
+  if notify_only(app) {
+    if let Err(e) = journal::emit_to(app, "panel", "toggle-collapse", ()) {
+      log::error!("failed to emit toggle-collapse: {}", e);
+    }
+    return;
+  }
+  if let Err(e) = controller::toggle_panel(app) {
+    log::error!("toggle failed: {}", e);
+  }
+}
+
+pub fn load_behavior(app: &AppHandle) -> HotkeyBehavior {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(HOTKEY_BEHAVIOR_KEY))
+    .and_then(|value| serde_json::from_value(value).ok())
+    .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummonAction {
+  Show,
+  Hide,
+  NextMonitor,
+}
+
+/// What a show-panel press does. Only a panel that's already in front gets
+/// hidden (toggle mode) or moved on (monitor cycling, which wins when there's
+/// more than one monitor); one behind another app comes forward instead.
+pub fn summon_action(
+  behavior: HotkeyBehavior,
+  cycle_monitors: bool,
+  monitor_count: usize,
+  visible: bool,
+  focused: bool,
+) -> SummonAction {
+  if !(visible && focused) {
+    SummonAction::Show
+  } else if cycle_monitors && monitor_count > 1 {
+    SummonAction::NextMonitor
+  } else if behavior == HotkeyBehavior::Toggle {
+    SummonAction::Hide
+  } else {
+    SummonAction::Show
+  }
+}
+
+fn cycles_monitors(app: &AppHandle) -> bool {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(HOTKEY_CYCLES_MONITORS_KEY))
+    .and_then(|value| value.as_bool())
+    .unwrap_or(crate::settings::DEFAULT_HOTKEY_CYCLES_MONITORS)
+}
+
+fn debounced(last: Option<Instant>, now: Instant) -> bool {
+  last.is_some_and(|at| now.duration_since(at) < SUMMON_DEBOUNCE)
+}
+
+/// Show-panel hotkey: show and focus, hide, or move to the next monitor
+/// (see `summon_action`). The lock is held until the show or hide is done, so
+/// presses can't interleave, and near-simultaneous ones are dropped.
+fn summon(app: &AppHandle) {
+  let state = app.state::<AppState>();
+  let mut last = state.last_summon.lock().unwrap();
+  let now = Instant::now();
+  if debounced(*last, now) {
+    log::debug!("ignoring repeated show-panel press");
+    return;
+  }
+  *last = Some(now);
+
+  let window = app.get_webview_window("panel");
+  let visible = window.as_ref().is_some_and(|w| {
+    w.is_visible().unwrap_or(false) && !w.is_minimized().unwrap_or(false)
+  });
+  let focused = window.as_ref().is_some_and(|w| w.is_focused().unwrap_or(false));
+  let monitor_count = window
+    .as_ref()
+    .and_then(|w| w.available_monitors().ok())
+    .map_or(0, |monitors| monitors.len());
+  match summon_action(load_behavior(app), cycles_monitors(app), monitor_count, visible, focused) {
+    SummonAction::Show => controller::show_expanded(app, ShowTrigger::Hotkey),
+    SummonAction::Hide => controller::hide_panel(app),
+    SummonAction::NextMonitor => {
+      if let Err(e) = controller::move_to_next_monitor(app) {
+        log::warn!("could not move to the next monitor: {}", e);
+      }
+    }
+  }
+}
+
+pub fn run_action(app: &AppHandle, action: ShortcutAction) {
+  match action {
+    ShortcutAction::ShowPanel => summon(app),
+    ShortcutAction::ToggleCollapse => toggle_collapse(app),
+    ShortcutAction::CyclePosition => {
+      if let Err(e) = controller::cycle_position(app) {
+        log::warn!("could not cycle the panel position: {}", e);
+      }
+    }
+    ShortcutAction::QuitApp => quit::request(app),
+    ShortcutAction::CollapsePanel | ShortcutAction::ExpandPanel => {
+      let target = if action == ShortcutAction::CollapsePanel { "collapsed" } else { "expanded" };
+      if let Err(e) = controller::show_explicitly(app, target) {
+        log::error!("could not show the panel {}: {}", target, e);
+      }
+    }
+    ShortcutAction::PeekPanel => {
+      if let Err(e) = controller::start_peek(app) {
+        log::warn!("could not peek at the panel: {}", e);
+      }
+    }
+  }
+}
+
+fn register_one(
+  app: &AppHandle,
+  action: ShortcutAction,
+  accelerator: &str,
+  shortcut: Shortcut,
+) -> Result<(), String> {
+  let label = accelerator.to_string();
+  app
+    .global_shortcut()
+    .on_shortcut(shortcut, move |app, _shortcut, event| {
+      // Releases come through here too; only a peek cares about them.
+      if event.state != ShortcutState::Pressed {
+        if action == ShortcutAction::PeekPanel {
+          controller::end_peek(app);
+        }
+        return;
+      }
+      log::info!("global hotkey {} triggered; running {:?}", label, action);
+      run_action(app, action);
+    })
+    .map_err(|e| e.to_string())?;
+  let state = app.state::<AppState>();
+  state.registered_shortcuts.lock().unwrap().push(accelerator.to_string());
+  state.hotkey_errors.lock().unwrap().remove(accelerator);
+  Ok(())
+}
+
+/// Unregisters whichever of `accelerators` we registered.
+fn unregister_all(app: &AppHandle, accelerators: &[String]) {
+  let state = app.state::<AppState>();
+  let mut registered = state.registered_shortcuts.lock().unwrap();
+  for accelerator in accelerators {
+    if let Some(index) = registered.iter().position(|r| r == accelerator) {
+      registered.remove(index);
+      if let Err(e) = app.global_shortcut().unregister(accelerator.as_str()) {
+        log::warn!("failed to unregister {}: {}", accelerator, e);
+      }
+    }
+  }
+}
+
+/// Registers every enabled accelerator from the stored config, each running
+/// its action. Accelerators that fail to parse or register are skipped, and
+/// the failures reported together as `hotkey-registration-errors`.
+pub fn register_from_config(app: &AppHandle) {
+  seed_config(app);
+  if suspended(app) {
+    log::debug!("hotkeys are suspended; not registering");
+    return;
+  }
+  let config = load_config(app);
+  let mut errors = Vec::new();
+
+  for (action, accelerator, parsed) in config.resolve() {
+    let shortcut = match parsed {
+      Ok(shortcut) => shortcut,
+      Err(e) => {
+        log::warn!("skipping invalid accelerator {:?} for {:?}: {}", accelerator, action, e);
+        errors.push(record_failure(app, &accelerator, Some(action), e));
+        continue;
+      }
+    };
+
+    if let Err(e) = register_one(app, action, &accelerator, shortcut) {
+      log::warn!("failed to register {} for {:?}: {}", accelerator, action, e);
+      errors.push(record_failure(app, &accelerator, Some(action), e));
+    }
+  }
+
+  if !errors.is_empty() && *app.state::<AppState>().frontend_ready.lock().unwrap() {
+    emit_errors(app, errors);
+  }
+}
+
+fn emit_errors(app: &AppHandle, errors: Vec<ShortcutFailure>) {
+  let _ = journal::emit(app, "hotkey-registration-errors", serde_json::json!({ "errors": errors }));
+}
+
+/// Keeps a failed registration for diagnostics and tells the frontend about
+/// it, now if it's listening or once it calls `frontend_ready`.
+pub fn record_failure(
+  app: &AppHandle,
+  accelerator: &str,
+  action: Option<ShortcutAction>,
+  error: String,
+) -> ShortcutFailure {
+  let failure = ShortcutFailure {
+    accelerator: accelerator.to_string(),
+    action,
+    error,
+  };
+  let state = app.state::<AppState>();
+  state.shortcut_failures.lock().unwrap().push(failure.clone());
+  state.hotkey_errors.lock().unwrap().insert(failure.accelerator.clone(), failure.error.clone());
+  if *state.frontend_ready.lock().unwrap() {
+    let _ = journal::emit(app, "shortcut-registration-failed", &failure);
+  }
+  failure
+}
+
+/// Emits every failure recorded before the frontend was listening.
+pub fn emit_failures(app: &AppHandle) {
+  let failures = app.state::<AppState>().shortcut_failures.lock().unwrap().clone();
+  for failure in &failures {
+    let _ = journal::emit(app, "shortcut-registration-failed", failure);
+  }
+  if !failures.is_empty() {
+    emit_errors(app, failures);
+  }
+}
+
+/// Drops every accelerator registered from the config and registers the
+/// current config again, e.g. after the active profile changed.
+pub fn reregister(app: &AppHandle) {
+  let state = app.state::<AppState>();
+  let previous = state.registered_shortcuts.lock().unwrap().clone();
+  // Config failures are re-evaluated below; others stay.
+  state.shortcut_failures.lock().unwrap().retain(|f| f.action.is_none());
+  unregister_all(app, &previous);
+  register_from_config(app);
+
+  // Nothing was registered; resuming should bring back the new config.
+  let errors = state.hotkey_errors.lock().unwrap().clone();
+  if let Some(saved) = state.suspended_hotkeys.lock().unwrap().as_mut() {
+    *saved = effective_shortcuts(&load_config(app), &errors);
+  }
+}
+
+/// Whether `suspend_hotkeys` is in effect. Nothing registers app hotkeys
+/// while it is.
+pub fn suspended(app: &AppHandle) -> bool {
+  app.state::<AppState>().suspended_hotkeys.lock().unwrap().is_some()
+}
+
+/// Unregisters every configured shortcut until
+/// `resume_all_shortcuts`; e.g. while a text field or modal has focus.
+/// Returns how many were paused (0 if already paused).
+#[tauri::command]
+pub fn pause_all_shortcuts(app: AppHandle) -> Result<u32, AppError> {
+  audit::run(app.clone(), "pause_all_shortcuts", move || {
+    log::info!("pause_all_shortcuts invoked");
+
+    let state = app.state::<AppState>();
+    let mut paused = state.paused_shortcuts.lock().map_err(|e| e.to_string())?;
+    if paused.is_some() {
+      return Ok(0);
+    }
+
+    let config = load_config(&app);
+    let registered = state.registered_shortcuts.lock().map_err(|e| e.to_string())?.clone();
+    let saved: Vec<PausedShortcut> = registered
+      .iter()
+      .filter_map(|accelerator| {
+        let action = config.action_for(&parse_accelerator(accelerator).ok()?)?;
+        Some((action, accelerator.clone()))
+      })
+      .collect();
+    unregister_all(&app, &registered);
+
+    let count = saved.len() as u32;
+    *paused = Some(saved);
+    drop(paused);
+    quit::sync_quit_key(&app);
+    Ok(count)
+  })
+}
+
+/// Re-registers what `pause_all_shortcuts` unregistered. Returns how many
+/// came back; failures are reported like at startup.
+#[tauri::command]
+pub fn resume_all_shortcuts(app: AppHandle) -> Result<u32, AppError> {
+  audit::run(app.clone(), "resume_all_shortcuts", move || {
+    log::info!("resume_all_shortcuts invoked");
+
+    let saved = app.state::<AppState>().paused_shortcuts.lock().map_err(|e| e.to_string())?.take();
+    let mut resumed = 0;
+    for (action, accelerator) in saved.unwrap_or_default() {
+      let result = parse_accelerator(&accelerator)
+        .and_then(|shortcut| register_one(&app, action, &accelerator, shortcut));
+      match result {
+        Ok(()) => resumed += 1,
+        Err(e) => {
+          log::warn!("failed to resume {}: {}", accelerator, e);
+          record_failure(&app, &accelerator, Some(action), e);
+        }
+      }
+    }
+    quit::sync_quit_key(&app);
+    Ok(resumed)
+  })
+}
+
+/// Unregisters every app hotkey and chord until `resume_hotkeys`, e.g. while
+/// gaming or screen-sharing. Unlike `pause_all_shortcuts` this also keeps
+/// `register_hotkey`, profile switches and the tray from registering any.
+/// Returns how many were suspended (0 if already suspended).
+#[tauri::command]
+pub fn suspend_hotkeys(app: AppHandle) -> Result<u32, AppError> {
+  audit::run(app.clone(), "suspend_hotkeys", move || {
+    log::info!("suspend_hotkeys invoked");
+
+    let state = app.state::<AppState>();
+    let mut suspended = state.suspended_hotkeys.lock().map_err(|e| e.to_string())?;
+    if suspended.is_some() {
+      return Ok(0);
+    }
+
+    let config = load_config(&app);
+    let registered = state.registered_shortcuts.lock().map_err(|e| e.to_string())?.clone();
+    let mut saved: Vec<PausedShortcut> = registered
+      .iter()
+      .filter_map(|accelerator| {
+        let action = config.action_for(&parse_accelerator(accelerator).ok()?)?;
+        Some((action, accelerator.clone()))
+      })
+      .collect();
+    unregister_all(&app, &registered);
+    // Paused ones come back on resume too; `resume_all_shortcuts` finds nothing.
+    if let Some(paused) = state.paused_shortcuts.lock().map_err(|e| e.to_string())?.take() {
+      saved.extend(paused);
+    }
+
+    for chord in state.chords.lock().map_err(|e| e.to_string())?.iter() {
+      if let Err(e) = app.global_shortcut().unregister(chord.first.as_str()) {
+        log::warn!("failed to unregister chord key {}: {}", chord.first, e);
+      }
+    }
+    let armed = state.armed_chord.lock().map_err(|e| e.to_string())?.as_ref().map(|(g, _)| *g);
+    if let Some(generation) = armed {
+      disarm_chord(&app, generation);
+    }
+
+    let count = saved.len() as u32;
+    *suspended = Some(saved);
+    drop(suspended);
+    quit::sync_quit_key(&app);
+
+    tray::refresh_menu(&app);
+    let _ = journal::emit(&app, "hotkeys-suspended", serde_json::json!({ "count": count }));
+    Ok(count)
+  })
+}
+
+/// Registers again exactly what `suspend_hotkeys` took down, chords
+/// included; ones that had failed before stay off. Returns how many hotkeys
+/// came back (0 if not suspended).
+#[tauri::command]
+pub fn resume_hotkeys(app: AppHandle) -> Result<u32, AppError> {
+  audit::run(app.clone(), "resume_hotkeys", move || {
+    log::info!("resume_hotkeys invoked");
+
+    let state = app.state::<AppState>();
+    let Some(saved) = state.suspended_hotkeys.lock().map_err(|e| e.to_string())?.take() else {
+      return Ok(0);
+    };
+
+    let mut resumed = 0;
+    for (action, accelerator) in saved {
+      let result = parse_accelerator(&accelerator)
+        .and_then(|shortcut| register_one(&app, action, &accelerator, shortcut));
+      match result {
+        Ok(()) => resumed += 1,
+        Err(e) => {
+          log::warn!("failed to resume {}: {}", accelerator, e);
+          record_failure(&app, &accelerator, Some(action), e);
+        }
+      }
+    }
+    let chords = state.chords.lock().map_err(|e| e.to_string())?.clone();
+    for chord in chords {
+      let listened = parse_accelerator(&chord.first)
+        .and_then(|first_key| listen_chord(&app, first_key, chord.clone()));
+      if let Err(e) = listened {
+        log::warn!("failed to resume chord {} {}: {}", chord.first, chord.second, e);
+      }
+    }
+
+    quit::sync_quit_key(&app);
+    tray::refresh_menu(&app);
+    let _ = journal::emit(&app, "hotkeys-resumed", serde_json::json!({ "count": resumed }));
+    Ok(resumed)
+  })
+}
+
+/// Whether each configured hotkey registered with the OS, and why not if it
+/// didn't (e.g. Ctrl+Space held by the IME switcher).
+#[tauri::command]
+pub fn list_registered_hotkeys(app: AppHandle) -> Result<Vec<HotkeyStatus>, AppError> {
+  audit::run(app.clone(), "list_registered_hotkeys", move || {
+    let state = app.state::<AppState>();
+    let registered = state.registered_shortcuts.lock().map_err(|e| e.to_string())?.clone();
+    let errors = state.hotkey_errors.lock().map_err(|e| e.to_string())?.clone();
+    Ok(hotkey_statuses(&load_config(&app), &registered, &errors))
+  })
+}
+
+/// Lists every global shortcut and whether it's currently registered, for
+/// the settings page.
+#[tauri::command]
+pub fn get_global_shortcut_list(app: AppHandle) -> Result<Vec<ShortcutInfo>, AppError> {
+  audit::run(app.clone(), "get_global_shortcut_list", move || {
+    let shortcuts = app.global_shortcut();
+    let is_active = |accelerator: &str| {
+      parse_accelerator(accelerator).is_ok_and(|shortcut| shortcuts.is_registered(shortcut))
+    };
+    Ok(shortcut_list(&load_config(&app), is_active))
+  })
+}
+
+/// Runs the action bound to `shortcut` as if it had been pressed, for the
+/// settings page's "Test" buttons.
+#[tauri::command]
+pub fn test_shortcut(app: AppHandle, shortcut: String) -> CommandResult {
+  audit::run(app.clone(), "test_shortcut", move || {
+    log::info!("test_shortcut: shortcut={}", shortcut);
+
+    let parsed = parse_accelerator(&shortcut).map_err(AppError::InvalidArgument)?;
+    let action = load_config(&app)
+      .action_for(&parsed)
+      .ok_or(format!("No action is bound to {}", shortcut))?;
+    run_action(&app, action);
+    Ok(ok_status())
+  })
+}
+
+/// Drops the armed chord's second key if `generation` is still the armed
+/// one. Whoever gets here first (the second key or the timeout) wins.
+fn disarm_chord(app: &AppHandle, generation: u64) -> bool {
+  let state = app.state::<AppState>();
+  let mut armed = state.armed_chord.lock().unwrap();
+  match armed.take() {
+    Some((armed_generation, second)) if armed_generation == generation => {
+      if let Err(e) = app.global_shortcut().unregister(second.as_str()) {
+        log::warn!("failed to unregister chord key {}: {}", second, e);
+      }
+      true
+    }
+    other => {
+      *armed = other;
+      false
+    }
+  }
+}
+
+/// Registers `chord.second` until it's pressed or `timeout_ms` runs out.
+/// Runs off the shortcut handler's thread: the plugin holds its shortcut
+/// table while calling handlers, so (un)registering from one would deadlock.
+fn arm_chord(app: &AppHandle, chord: Chord) {
+  let state = app.state::<AppState>();
+  let generation = {
+    let mut next = state.next_chord_arm.lock().unwrap();
+    *next += 1;
+    *next
+  };
+  if let Some((previous, _)) = *state.armed_chord.lock().unwrap() {
+    disarm_chord(app, previous);
+  }
+
+  let action = chord.action;
+  let registered = app.global_shortcut().on_shortcut(
+    chord.second.as_str(),
+    move |app, _shortcut, event| {
+      if event.state != ShortcutState::Pressed {
+        return;
+      }
+      let app = app.clone();
+      std::thread::spawn(move || {
+        if disarm_chord(&app, generation) {
+          log::info!("chord completed; running {:?}", action);
+          run_action(&app, action);
+        }
+      });
+    },
+  );
+  if let Err(e) = registered {
+    log::warn!("could not arm chord {} {}: {}", chord.first, chord.second, e);
+    return;
+  }
+  *state.armed_chord.lock().unwrap() = Some((generation, chord.second.clone()));
+
+  std::thread::sleep(std::time::Duration::from_millis(chord.timeout_ms));
+  if disarm_chord(app, generation) {
+    log::debug!("chord {} timed out waiting for {}", chord.first, chord.second);
+  }
+}
+
+/// Registers a two-step shortcut: pressing `first` waits up to `timeout_ms`
+/// (see `CHORD_TIMEOUT_MS`) for `second`, and runs `action` if it comes.
+/// `second` is only claimed while waiting, so it reaches other apps the rest
+/// of the time.
+///
+/// A timed-out `first` does not pass through to the focused app; it counts
+/// as nothing pressed. The OS consumes a registered global shortcut, and
+/// replaying it would mean synthesizing keystrokes per platform (CGEventPost,
+/// SendInput, XTest) with `first` unregistered meanwhile, which needs
+/// accessibility permission on macOS and doesn't work on Wayland. Pick a
+/// `first` nothing else uses.
+#[tauri::command]
+pub fn register_chord_shortcut(
+  app: AppHandle,
+  first: String,
+  second: String,
+  action: ShortcutAction,
+  timeout_ms: u64,
+) -> CommandResult {
+  audit::run(app.clone(), "register_chord_shortcut", move || {
+    log::info!(
+      "register_chord_shortcut: first={} second={} action={:?} timeout_ms={}",
+      first,
+      second,
+      action,
+      timeout_ms
+    );
+    action.validate()?;
+    check_chord_timeout(timeout_ms).map_err(AppError::InvalidArgument)?;
+
+    if suspended(&app) {
+      return Err(SUSPENDED_MESSAGE.into());
+    }
+    let state = app.state::<AppState>();
+    let mut chords = state.chords.lock().map_err(|e| e.to_string())?;
+    let (first_key, _) =
+      check_chord(&load_config(&app), &chords, &first, &second).map_err(AppError::InvalidArgument)?;
+    let chord = Chord { first, second, action, timeout_ms };
+
+    listen_chord(&app, first_key, chord.clone())?;
+    chords.push(chord);
+    Ok(ok_status())
+  })
+}
+
+/// Registers `first_key` to arm `chord` when pressed.
+fn listen_chord(app: &AppHandle, first_key: Shortcut, chord: Chord) -> Result<(), String> {
+  app
+    .global_shortcut()
+    .on_shortcut(first_key, move |app, _shortcut, event| {
+      if event.state != ShortcutState::Pressed {
+        return;
+      }
+      log::debug!("chord {} started", chord.first);
+      let (app, chord) = (app.clone(), chord.clone());
+      std::thread::spawn(move || arm_chord(&app, chord));
+    })
+    .map_err(|e| e.to_string())
+}
+
+fn parse_action(action: &str) -> Result<ShortcutAction, HotkeyError> {
+  serde_json::from_value(serde_json::json!(action))
+    .map_err(|_| HotkeyError::UnknownAction(format!("Unknown hotkey action: {}", action)))
+}
+
+fn save_config(app: &AppHandle, config: &HotkeyConfig) -> Result<(), String> {
+  let store = profiles::settings_store(app).map_err(|e| e.to_string())?;
+  store.set(HOTKEYS_KEY, serde_json::to_value(config).map_err(|e| e.to_string())?);
+  store.save().map_err(|e| e.to_string())
+}
+
+fn emit_changed(app: &AppHandle, config: &HotkeyConfig) {
+  let _ = journal::emit(app, "hotkeys-changed", config.effective());
+}
+
+/// Registers the stored config again and tells the frontend, for writes that
+/// bypass the hotkey commands (`settings::save_settings`).
+pub fn reload(app: &AppHandle) {
+  reregister(app);
+  emit_changed(app, &load_config(app));
+}
+
+/// Binds `action` to `accelerator` right away and persists it. The action's
+/// old accelerators are unregistered first; if the OS refuses the new one
+/// they're put back.
+#[tauri::command]
+pub fn register_hotkey(
+  app: AppHandle,
+  action: String,
+  accelerator: String,
+  force: Option<bool>,
+) -> CommandResult {
+  audit::run(app.clone(), "register_hotkey", move || {
+    log::info!(
+      "register_hotkey: action={}, accelerator={}, force={:?}",
+      action,
+      accelerator,
+      force
+    );
+
+    if suspended(&app) {
+      return Err(HotkeyError::Other(SUSPENDED_MESSAGE.to_string()).into());
+    }
+    let action = parse_action(&action)?;
+    let shortcut = parse_accelerator(&accelerator).map_err(HotkeyError::InvalidAccelerator)?;
+    let mut config = load_config(&app);
+    if let Some(other) = config.taken_by(action, &shortcut) {
+      let message = format!("{} is already used by {:?}", accelerator, other);
+      return Err(HotkeyError::AlreadyTaken(message).into());
+    }
+
+    let previous = config.0.get(&action).map(|b| b.accelerators.clone()).unwrap_or_default();
+    unregister_all(&app, &previous);
+    if let Err(e) = register_one(&app, action, &accelerator, shortcut) {
+      if force.unwrap_or(false) {
+        log::warn!("failed to register {} for {:?}: {}; saving anyway", accelerator, action, e);
+        record_failure(&app, &accelerator, Some(action), e);
+        config.0.insert(action, HotkeyBinding { accelerators: vec![accelerator], enabled: true });
+        save_config(&app, &config)?;
+        emit_changed(&app, &config);
+        return Ok(ok_status());
+      }
+      log::warn!("failed to register {} for {:?}: {}; restoring previous", accelerator, action, e);
+      for (_, old, parsed) in config.resolve().into_iter().filter(|(a, _, _)| *a == action) {
+        if let Ok(old_shortcut) = parsed {
+          let _ = register_one(&app, action, &old, old_shortcut);
+        }
+      }
+      return Err(HotkeyError::RegistrationFailed(e).into());
+    }
+
+    config.0.insert(action, HotkeyBinding { accelerators: vec![accelerator], enabled: true });
+    save_config(&app, &config)?;
+    emit_changed(&app, &config);
+    Ok(ok_status())
+  })
+}
+
+/// Checks whether `accelerator` can be registered right now, e.g. before
+/// offering it in the settings page. Another app holding it shows up as
+/// `available: false` with the OS error.
+#[tauri::command]
+pub fn test_hotkey(app: AppHandle, accelerator: String) -> Result<HotkeyTest, AppError> {
+  audit::run(app.clone(), "test_hotkey", move || {
+    log::info!("test_hotkey: accelerator={}", accelerator);
+
+    let shortcut = parse_accelerator(&accelerator).map_err(HotkeyError::InvalidAccelerator)?;
+    let error = probe(&app, shortcut).err();
+    Ok(HotkeyTest { accelerator, available: error.is_none(), error })
+  })
+}
+
+/// Unregisters `action`'s accelerators and disables its binding, keeping the
+/// accelerators so it can be turned back on.
+#[tauri::command]
+pub fn unregister_hotkey(app: AppHandle, action: String) -> CommandResult {
+  audit::run(app.clone(), "unregister_hotkey", move || {
+    log::info!("unregister_hotkey: action={}", action);
+
+    let action = parse_action(&action)?;
+    let mut config = load_config(&app);
+    let Some(binding) = config.0.get_mut(&action) else {
+      return Ok(ok_status());
+    };
+    unregister_all(&app, &binding.accelerators);
+    if let Some(saved) = app.state::<AppState>().suspended_hotkeys.lock().unwrap().as_mut() {
+      saved.retain(|(a, _)| *a != action);
+    }
+    binding.enabled = false;
+    save_config(&app, &config)?;
+    emit_changed(&app, &config);
+    Ok(ok_status())
+  })
+}
+
+/// Switches the show-panel hotkeys between `show-only` and `toggle`.
+#[tauri::command]
+pub fn set_hotkey_behavior(app: AppHandle, value: HotkeyBehavior) -> CommandResult {
+  audit::run(app.clone(), "set_hotkey_behavior", move || {
+    log::info!("set_hotkey_behavior: value={:?}", value);
+
+    let store = profiles::settings_store(&app)?;
+    store.set(HOTKEY_BEHAVIOR_KEY, serde_json::to_value(value).map_err(|e| e.to_string())?);
+    store.save()?;
+    Ok(ok_status())
+  })
+}
+
+/// Puts back this platform's default hotkeys and registers them.
+#[tauri::command]
+pub fn reset_hotkeys_to_default(app: AppHandle) -> Result<HotkeyConfig, AppError> {
+  audit::run(app.clone(), "reset_hotkeys_to_default", move || {
+    log::info!("reset_hotkeys_to_default invoked");
+
+    let config = HotkeyConfig::defaults_for(Platform::current());
+    save_seeded(&app, &config)?;
+    reregister(&app);
+    emit_changed(&app, &config);
+    Ok(config)
+  })
+}
+
+#[tauri::command]
+pub fn get_hotkey_config(app: AppHandle) -> Result<HotkeyConfig, AppError> {
+  audit::run(app.clone(), "get_hotkey_config", move || {
+    Ok(load_config(&app))
+  })
+}
+
+/// Persists a new hotkey config and registers it right away. Accelerators
+/// another app holds are refused unless `force` is set.
+#[tauri::command]
+pub fn set_hotkey_config(
+  app: AppHandle,
+  config: HotkeyConfig,
+  force: Option<bool>,
+) -> CommandResult {
+  audit::run(app.clone(), "set_hotkey_config", move || {
+    log::info!("set_hotkey_config: {:?}, force={:?}", config, force);
+
+    config.validate()?;
+    if !force.unwrap_or(false) {
+      for (action, accelerator, parsed) in config.resolve() {
+        if let Err(e) = parsed.and_then(|shortcut| probe(&app, shortcut)) {
+          return Err(format!("{} for {:?} can't be registered: {}", accelerator, action, e).into());
+        }
+      }
+    }
+    save_config(&app, &config)?;
+    reregister(&app);
+    emit_changed(&app, &config);
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn resolved_defaults() -> Vec<(ShortcutAction, String)> {
+    resolved_defaults_for(Platform::current())
+  }
+
+  fn resolved_defaults_for(platform: Platform) -> Vec<(ShortcutAction, String)> {
+    HotkeyConfig::defaults_for(platform)
+      .resolve()
+      .into_iter()
+      .map(|(action, accelerator, parsed)| {
+        assert!(parsed.is_ok(), "{} should parse", accelerator);
+        (action, accelerator)
+      })
+      .collect()
+  }
+
+  #[test]
+  fn defaults_match_the_original_hotkeys() {
+    assert_eq!(
+      resolved_defaults_for(Platform::Macos),
+      vec![
+        (ShortcutAction::ShowPanel, "Alt+Cmd+Space".to_string()),
+        (ShortcutAction::ShowPanel, "Ctrl+Space".to_string()),
+        (ShortcutAction::ShowPanel, "Cmd+Shift+Space".to_string()),
+        (ShortcutAction::ToggleCollapse, "Cmd+1".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn defaults_avoid_cmd_off_macos() {
+    let expected = vec![
+      (ShortcutAction::ShowPanel, "Ctrl+Alt+Space".to_string()),
+      (ShortcutAction::ShowPanel, "Ctrl+Shift+Space".to_string()),
+      (ShortcutAction::ToggleCollapse, "Ctrl+1".to_string()),
+    ];
+    assert_eq!(resolved_defaults_for(Platform::Windows), expected);
+    assert_eq!(resolved_defaults_for(Platform::Linux), expected);
+  }
+
+  #[test]
+  fn configs_from_another_platform_are_reseeded() {
+    assert!(needs_reseed(false, None, Platform::Windows));
+    assert!(needs_reseed(true, Some(Platform::Macos), Platform::Windows));
+    assert!(!needs_reseed(true, Some(Platform::Linux), Platform::Linux));
+    assert!(!needs_reseed(true, None, Platform::Windows));
+  }
+
+  #[test]
+  fn chord_timeouts_stay_short() {
+    assert!(check_chord_timeout(100).is_ok());
+    assert!(check_chord_timeout(5000).is_ok());
+    assert!(check_chord_timeout(99).is_err());
+    assert!(check_chord_timeout(5001).is_err());
+    assert!(check_chord_timeout(u64::MAX).is_err());
+  }
+
+  #[test]
+  fn chords_need_a_free_distinct_first_key() {
+    let config = HotkeyConfig::default();
+    let bound = &config.0[&ShortcutAction::ShowPanel].accelerators[0];
+    assert!(check_chord(&config, &[], "Ctrl+K", "Ctrl+S").is_ok());
+    assert!(check_chord(&config, &[], "Ctrl+K", "ctrl+k").is_err());
+    assert!(check_chord(&config, &[], bound, "Ctrl+S").is_err());
+    assert!(check_chord(&config, &[], "Ctrl+K", "Nope+S").is_err());
+
+    let existing = Chord {
+      first: "Ctrl+K".to_string(),
+      second: "Ctrl+S".to_string(),
+      action: ShortcutAction::ShowPanel,
+      timeout_ms: 1000,
+    };
+    assert!(check_chord(&config, &[existing], "ctrl+k", "Ctrl+T").is_err());
+  }
+
+  #[test]
+  fn toggle_mode_hides_only_a_focused_visible_panel() {
+    let action = |behavior, visible, focused| summon_action(behavior, false, 1, visible, focused);
+    assert_eq!(action(HotkeyBehavior::Toggle, true, true), SummonAction::Hide);
+    assert_eq!(action(HotkeyBehavior::Toggle, true, false), SummonAction::Show);
+    assert_eq!(action(HotkeyBehavior::Toggle, false, false), SummonAction::Show);
+    assert_eq!(action(HotkeyBehavior::ShowOnly, true, true), SummonAction::Show);
+
+    let now = Instant::now();
+    assert!(!debounced(None, now));
+    assert!(debounced(Some(now), now + Duration::from_millis(100)));
+    assert!(!debounced(Some(now), now + SUMMON_DEBOUNCE));
+  }
+
+  #[test]
+  fn monitor_cycling_needs_a_second_monitor() {
+    let (show_only, toggle) = (HotkeyBehavior::ShowOnly, HotkeyBehavior::Toggle);
+    assert_eq!(summon_action(show_only, true, 2, true, true), SummonAction::NextMonitor);
+    assert_eq!(summon_action(toggle, true, 2, true, true), SummonAction::NextMonitor);
+    assert_eq!(summon_action(toggle, true, 1, true, true), SummonAction::Hide);
+    assert_eq!(summon_action(show_only, true, 2, true, false), SummonAction::Show);
+  }
+
+  #[test]
+  fn round_trips_through_json() {
+    let json = serde_json::json!({
+      "show-panel": { "accelerators": ["Ctrl+Alt+Space"] },
+      "toggle-collapse": { "accelerators": ["Ctrl+1"], "enabled": false }
+    });
+
+    let config: HotkeyConfig = serde_json::from_value(json.clone()).unwrap();
+    assert!(config.0[&ShortcutAction::ShowPanel].enabled);
+    assert!(!config.0[&ShortcutAction::ToggleCollapse].enabled);
+
+    let resolved = config.resolve();
+    assert_eq!(resolved.len(), 1);
+    assert_eq!(resolved[0].1, "Ctrl+Alt+Space");
+  }
+
+  #[test]
+  fn bare_accelerators_bind_cycle_position() {
+    let config: HotkeyConfig =
+      serde_json::from_value(serde_json::json!({ "cycle-position": "Ctrl+2" })).unwrap();
+    let binding = &config.0[&ShortcutAction::CyclePosition];
+    assert_eq!(binding.accelerators, vec!["Ctrl+2".to_string()]);
+    assert!(binding.enabled);
+    assert!(!HotkeyConfig::default().0.contains_key(&ShortcutAction::CyclePosition));
+  }
+
+  #[test]
+  fn collapse_and_expand_are_unbound_by_default() {
+    let json = serde_json::json!({ "collapse-panel": "Ctrl+3", "expand-panel": "Ctrl+4" });
+    let config: HotkeyConfig = serde_json::from_value(json).unwrap();
+    let statuses = hotkey_statuses(&config, &["Ctrl+4".to_string()], &BTreeMap::new());
+    let listed: Vec<_> = statuses.iter().map(|s| (s.action, s.registered)).collect();
+    assert_eq!(
+      listed,
+      vec![(ShortcutAction::CollapsePanel, false), (ShortcutAction::ExpandPanel, true)]
+    );
+    let actions = HotkeyConfig::default().0.into_keys().collect::<Vec<_>>();
+    assert!(!actions.contains(&ShortcutAction::CollapsePanel));
+    assert!(!actions.contains(&ShortcutAction::ExpandPanel));
+  }
+
+  #[test]
+  fn shortcut_list_covers_disabled_bindings() {
+    let mut config = HotkeyConfig::default();
+    config.0.get_mut(&ShortcutAction::ToggleCollapse).unwrap().enabled = false;
+
+    let list = shortcut_list(&config, |_| true);
+    assert_eq!(list.len(), resolved_defaults().len());
+    // Escape is handled in the panel now, not grabbed globally.
+    assert!(list.iter().all(|s| s.shortcut != "Escape"));
+    let toggle = list.iter().find(|s| s.action == Some(ShortcutAction::ToggleCollapse)).unwrap();
+    assert!(!toggle.active);
+  }
+
+  #[test]
+  fn finds_actions_by_parsed_shortcut() {
+    let json = serde_json::json!({
+      "show-panel": { "accelerators": ["Ctrl+Alt+Space"] },
+      "toggle-collapse": { "accelerators": ["Ctrl+1"], "enabled": false }
+    });
+    let config: HotkeyConfig = serde_json::from_value(json).unwrap();
+
+    let action = |s: &str| config.action_for(&parse_accelerator(s).unwrap());
+    assert_eq!(action("alt+ctrl+space"), Some(ShortcutAction::ShowPanel));
+    assert_eq!(action("Ctrl+1"), None);
+    assert_eq!(action("Ctrl+2"), None);
+  }
+
+  #[test]
+  fn accelerators_taken_by_another_action_conflict() {
+    let json = serde_json::json!({
+      "show-panel": { "accelerators": ["Ctrl+Alt+Space"] },
+      "toggle-collapse": { "accelerators": ["Ctrl+1"], "enabled": false }
+    });
+    let config: HotkeyConfig = serde_json::from_value(json).unwrap();
+    let shortcut = |s: &str| parse_accelerator(s).unwrap();
+
+    let taken = config.taken_by(ShortcutAction::ToggleCollapse, &shortcut("Ctrl+Alt+Space"));
+    assert_eq!(taken, Some(ShortcutAction::ShowPanel));
+    // Rebinding an action onto its own accelerator is fine, as is a disabled one's.
+    assert_eq!(config.taken_by(ShortcutAction::ShowPanel, &shortcut("Ctrl+Alt+Space")), None);
+    assert_eq!(config.taken_by(ShortcutAction::ShowPanel, &shortcut("Ctrl+1")), None);
+    assert!(parse_action("show-panel").is_ok());
+    assert!(matches!(parse_action("launch-rockets"), Err(HotkeyError::UnknownAction(_))));
+    let taken = AppError::from(HotkeyError::AlreadyTaken("Cmd+1".to_string()));
+    assert_eq!(taken, AppError::InvalidArgument("Cmd+1".to_string()));
+  }
+
+  #[test]
+  fn duplicate_accelerators_across_actions_are_caught() {
+    let config = |show: &[&str], collapse: &[&str], enabled: bool| -> HotkeyConfig {
+      serde_json::from_value(serde_json::json!({
+        "show-panel": { "accelerators": show },
+        "toggle-collapse": { "accelerators": collapse, "enabled": enabled }
+      }))
+      .unwrap()
+    };
+
+    let clash = config(&["Ctrl+Alt+Space", "Ctrl+1"], &["ctrl+1"], true);
+    assert_eq!(
+      clash.duplicate(),
+      Some(("Ctrl+1".to_string(), ShortcutAction::ShowPanel, ShortcutAction::ToggleCollapse))
+    );
+    assert!(validate(&clash).is_err());
+
+    // Disabled bindings don't count, and neither do repeats within one action.
+    assert_eq!(config(&["Ctrl+1"], &["Ctrl+1"], false).duplicate(), None);
+    assert_eq!(config(&["Ctrl+1", "ctrl+1"], &["Ctrl+2"], true).duplicate(), None);
+    assert!(validate(&HotkeyConfig::default()).is_ok());
+  }
+
+  #[test]
+  fn statuses_pair_registrations_with_last_errors() {
+    let json = serde_json::json!({
+      "show-panel": { "accelerators": ["Ctrl+Space", "Ctrl+Alt+Space"] },
+    });
+    let config: HotkeyConfig = serde_json::from_value(json).unwrap();
+    let errors = BTreeMap::from([("Ctrl+Space".to_string(), "taken".to_string())]);
+
+    let statuses = hotkey_statuses(&config, &["Ctrl+Alt+Space".to_string()], &errors);
+    assert_eq!(statuses.len(), 2);
+    assert!(!statuses[0].registered);
+    assert_eq!(statuses[0].last_error.as_deref(), Some("taken"));
+    assert!(statuses[1].registered);
+    assert_eq!(statuses[1].last_error, None);
+  }
+
+  #[test]
+  fn effective_shortcuts_skip_failed_and_disabled_ones() {
+    let json = serde_json::json!({
+      "show-panel": { "accelerators": ["Ctrl+Space", "Ctrl+Alt+Space", "Cmd+Nope"] },
+      "toggle-collapse": { "accelerators": ["Ctrl+1"], "enabled": false },
+    });
+    let config: HotkeyConfig = serde_json::from_value(json).unwrap();
+    let errors = BTreeMap::from([("Ctrl+Space".to_string(), "taken".to_string())]);
+
+    assert_eq!(
+      effective_shortcuts(&config, &errors),
+      vec![(ShortcutAction::ShowPanel, "Ctrl+Alt+Space".to_string())]
+    );
+  }
+
+  #[test]
+  fn invalid_accelerators_are_rejected_but_resolve_keeps_going() {
+    let mut config = HotkeyConfig::default();
+    config
+      .0
+      .get_mut(&ShortcutAction::ShowPanel)
+      .unwrap()
+      .accelerators
+      .push("Cmd+Nope".to_string());
+
+    assert!(validate(&config).is_err());
+
+    let resolved = config.resolve();
+    assert_eq!(resolved.len(), resolved_defaults().len() + 1);
+    assert_eq!(resolved.iter().filter(|(_, _, parsed)| parsed.is_err()).count(), 1);
+  }
+}
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+#[cfg(not(target_os = "windows"))]
+use crate::state::AppState;
+use crate::validate::Validate;
+
+/// A rectangle in physical pixels, relative to the panel's top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rect {
+  pub x: i32,
+  pub y: i32,
+  pub width: u32,
+  pub height: u32,
+}
+
+impl Rect {
+  #[cfg(any(not(target_os = "windows"), test))]
+  pub fn contains(&self, x: f64, y: f64) -> bool {
+    x >= self.x as f64
+      && y >= self.y as f64
+      && x < self.x as f64 + self.width as f64
+      && y < self.y as f64 + self.height as f64
+  }
+}
+
+#[cfg(target_os = "windows")]
+fn apply(window: &tauri::WebviewWindow, rects: &[Rect]) -> Result<(), String> {
+  use windows_sys::Win32::Graphics::Gdi::{
+    CombineRgn, CreateRectRgn, DeleteObject, SetWindowRgn, HRGN, RGN_OR,
+  };
+
+  let hwnd = window.hwnd().map_err(|e| e.to_string())?.0 as _;
+  if rects.is_empty() {
+    // A null region puts the whole window back.
+    unsafe { SetWindowRgn(hwnd, std::ptr::null_mut(), 1) };
+    return Ok(());
+  }
+
+  unsafe {
+    let region: HRGN = CreateRectRgn(0, 0, 0, 0);
+    for rect in rects {
+      let part = CreateRectRgn(
+        rect.x,
+        rect.y,
+        rect.x + rect.width as i32,
+        rect.y + rect.height as i32,
+      );
+      CombineRgn(region, region, part, RGN_OR);
+      DeleteObject(part);
+    }
+    // On success the system owns the region, so it isn't deleted here.
+    if SetWindowRgn(hwnd, region, 1) == 0 {
+      DeleteObject(region);
+      return Err("SetWindowRgn failed".to_string());
+    }
+  }
+  Ok(())
+}
+
+// Elsewhere there's no input-shape API we can reach, so a watcher flips
+// cursor-event passthrough depending on whether the cursor is over a rect.
+#[cfg(not(target_os = "windows"))]
+fn apply(window: &tauri::WebviewWindow, rects: &[Rect]) -> Result<(), String> {
+  let app = window.app_handle();
+  let state = app.state::<AppState>();
+  *state.input_region.lock().map_err(|e| e.to_string())? =
+    (!rects.is_empty()).then(|| rects.to_vec());
+
+  if rects.is_empty() {
+    return window.set_ignore_cursor_events(false).map_err(|e| e.to_string());
+  }
+
+  let mut started = state.input_region_watcher.lock().map_err(|e| e.to_string())?;
+  if !*started {
+    *started = true;
+    start_watcher(app.clone());
+  }
+  Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn start_watcher(app: AppHandle) {
+  use std::time::Duration;
+
+  std::thread::spawn(move || {
+    let mut ignoring = false;
+    loop {
+      std::thread::sleep(Duration::from_millis(50));
+
+      let region = app.state::<AppState>().input_region.lock().unwrap().clone();
+      let Some(window) = app.get_webview_window("panel") else {
+        continue;
+      };
+      let Some(rects) = region else {
+        ignoring = false;
+        continue;
+      };
+
+      let (Ok(cursor), Ok(origin)) = (app.cursor_position(), window.inner_position()) else {
+        continue;
+      };
+      let (x, y) = (cursor.x - origin.x as f64, cursor.y - origin.y as f64);
+      let ignore = !rects.iter().any(|r| r.contains(x, y));
+      if ignore != ignoring {
+        let _ = window.set_ignore_cursor_events(ignore);
+        ignoring = ignore;
+      }
+    }
+  });
+}
+
+/// Limits where the panel takes input; clicks outside every rect go to
+/// whatever is underneath. An empty list makes the whole panel interactive.
+#[tauri::command]
+pub fn set_input_region(app: AppHandle, rects: Vec<Rect>) -> CommandResult {
+  audit::run(app.clone(), "set_input_region", move || {
+    log::info!("set_input_region: {} rects", rects.len());
+
+    rects.validate()?;
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    apply(&window, &rects)?;
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn contains_is_half_open() {
+    let rect = Rect { x: 10, y: 10, width: 100, height: 50 };
+    assert!(rect.contains(10.0, 10.0));
+    assert!(rect.contains(109.5, 59.5));
+    assert!(!rect.contains(110.0, 30.0));
+    assert!(!rect.contains(50.0, 60.0));
+    assert!(!rect.contains(9.9, 30.0));
+  }
+}
@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::state::AppState;
+
+pub const DEFAULT_JOURNAL_SIZE: usize = 100;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalEntry {
+  pub event: String,
+  pub target: Option<String>,
+  pub timestamp_ms: u64,
+  pub payload: serde_json::Value,
+}
+
+/// Bounded record of the events the backend has emitted to the webview.
+/// A max size of 0 disables recording entirely.
+#[derive(Debug)]
+pub struct EventJournal {
+  entries: VecDeque<JournalEntry>,
+  max: usize,
+}
+
+impl Default for EventJournal {
+  fn default() -> Self {
+    Self::new(DEFAULT_JOURNAL_SIZE)
+  }
+}
+
+impl EventJournal {
+  pub fn new(max: usize) -> Self {
+    Self {
+      entries: VecDeque::with_capacity(max),
+      max,
+    }
+  }
+
+  pub fn record(&mut self, entry: JournalEntry) {
+    if self.max == 0 {
+      return;
+    }
+    self.entries.push_back(entry);
+    self.trim();
+  }
+
+  pub fn clear(&mut self) -> usize {
+    let count = self.entries.len();
+    self.entries.clear();
+    count
+  }
+
+  pub fn set_max(&mut self, max: usize) {
+    self.max = max;
+    self.trim();
+  }
+
+  pub fn entries(&self) -> Vec<JournalEntry> {
+    self.entries.iter().cloned().collect()
+  }
+
+  fn trim(&mut self) {
+    while self.entries.len() > self.max {
+      self.entries.pop_front();
+    }
+  }
+}
+
+fn record<S: Serialize>(app: &AppHandle, target: Option<&str>, event: &str, payload: &S) {
+  let state = app.state::<AppState>();
+  if let Ok(mut journal) = state.event_journal.lock() {
+    journal.record(JournalEntry {
+      event: event.to_string(),
+      target: target.map(str::to_string),
+      timestamp_ms: crate::now_ms(),
+      payload: serde_json::to_value(payload).unwrap_or(serde_json::Value::Null),
+    });
+  };
+}
+
+/// `app.emit` that also records the event in the journal.
+pub fn emit<S: Serialize + Clone>(app: &AppHandle, event: &str, payload: S) -> tauri::Result<()> {
+  record(app, None, event, &payload);
+  app.emit(event, payload)
+}
+
+/// `app.emit_to` that also records the event in the journal.
+pub fn emit_to<S: Serialize + Clone>(
+  app: &AppHandle,
+  target: &str,
+  event: &str,
+  payload: S,
+) -> tauri::Result<()> {
+  record(app, Some(target), event, &payload);
+  app.emit_to(target, event, payload)
+}
+
+#[tauri::command]
+pub fn get_event_journal(app: AppHandle) -> Result<Vec<JournalEntry>, AppError> {
+  audit::run(app.clone(), "get_event_journal", move || {
+    let state = app.state::<AppState>();
+    let journal = state.event_journal.lock().map_err(|e| e.to_string())?;
+    Ok(journal.entries())
+  })
+}
+
+#[tauri::command]
+pub fn clear_event_journal(app: AppHandle) -> Result<u32, AppError> {
+  audit::run(app.clone(), "clear_event_journal", move || {
+    log::info!("clear_event_journal invoked");
+
+    let state = app.state::<AppState>();
+    let mut journal = state.event_journal.lock().map_err(|e| e.to_string())?;
+    let cleared = journal.clear();
+
+    log::debug!("cleared {} journal entries", cleared);
+    Ok(cleared as u32)
+  })
+}
+
+#[tauri::command]
+pub fn set_event_journal_size(app: AppHandle, max: usize) -> CommandResult {
+  audit::run(app.clone(), "set_event_journal_size", move || {
+    log::info!("set_event_journal_size: max={}", max);
+
+    let state = app.state::<AppState>();
+    let mut journal = state.event_journal.lock().map_err(|e| e.to_string())?;
+    journal.set_max(max);
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entry(event: &str) -> JournalEntry {
+    JournalEntry {
+      event: event.to_string(),
+      target: None,
+      timestamp_ms: 0,
+      payload: serde_json::Value::Null,
+    }
+  }
+
+  #[test]
+  fn drops_oldest_entries_past_max() {
+    let mut journal = EventJournal::new(2);
+    journal.record(entry("a"));
+    journal.record(entry("b"));
+    journal.record(entry("c"));
+
+    let events: Vec<_> = journal.entries().into_iter().map(|e| e.event).collect();
+    assert_eq!(events, vec!["b", "c"]);
+  }
+
+  #[test]
+  fn shrinking_max_trims_and_zero_disables() {
+    let mut journal = EventJournal::new(5);
+    for name in ["a", "b", "c", "d"] {
+      journal.record(entry(name));
+    }
+
+    journal.set_max(1);
+    assert_eq!(journal.entries().len(), 1);
+    assert_eq!(journal.entries()[0].event, "d");
+
+    journal.set_max(0);
+    journal.record(entry("e"));
+    assert!(journal.entries().is_empty());
+  }
+
+  #[test]
+  fn clear_returns_drained_count() {
+    let mut journal = EventJournal::default();
+    journal.record(entry("a"));
+    journal.record(entry("b"));
+
+    assert_eq!(journal.clear(), 2);
+    assert_eq!(journal.clear(), 0);
+  }
+}
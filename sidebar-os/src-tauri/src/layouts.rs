@@ -0,0 +1,832 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Monitor, PhysicalSize, Position, Size, Wry};
+use tauri_plugin_store::Store;
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::placement::{self, Placement};
+// Only the module: with the trait in scope, its `Arc` impl would shadow
+// `Store`'s own methods on the `Arc<Store>` handles used below.
+use crate::position_store;
+use crate::sizing::{self, ModeSize};
+use crate::state::AppState;
+use crate::validate::Validate;
+use crate::{
+  autosave, controller, history, journal, monitors, profiles, settings, stacking, tray, WindowPos,
+};
+
+// Everything layout-related for every mode, keyed by mode.
+pub const LAYOUT_KEY: &str = "layout";
+// Flat keys from before `layout`. Migrated at startup; still read as a
+// fallback (and folded in on the next write) for one schema version.
+pub const LEGACY_POSITION_PREFIX: &str = "custom_position_";
+pub const LEGACY_PLACEMENT_PREFIX: &str = "placement_";
+// Monitor fingerprint (hex) → last time that setup was connected.
+const SEEN_LAYOUTS_KEY: &str = "seen_monitor_layouts";
+pub const STALE_DAYS_KEY: &str = "stale_layout_days";
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+pub const LAYOUT_PROFILE_PREFIX: &str = "layout_profile_";
+
+/// A complete named layout: where, how big, on which monitor and whether
+/// it floats above everything.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutProfile {
+  pub placement: Placement,
+  #[serde(default)]
+  pub width: Option<u32>,
+  #[serde(default)]
+  pub height: Option<u32>,
+  // Monitor name; falls back to the current one when it isn't connected.
+  #[serde(default)]
+  pub monitor: Option<String>,
+  #[serde(default = "default_always_on_top")]
+  pub always_on_top: bool,
+}
+
+fn default_always_on_top() -> bool {
+  true
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NamedLayoutProfile {
+  pub name: String,
+  #[serde(flatten)]
+  pub profile: LayoutProfile,
+}
+
+/// Where a mode puts the panel: a saved custom position (with the size and
+/// monitor it was saved on), a registered size and an anchor.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LayoutEntry {
+  #[serde(default)]
+  pub position: Option<WindowPos>,
+  // Where the panel was last dragged to; see `autosave`.
+  #[serde(default)]
+  pub autosaved_position: Option<WindowPos>,
+  #[serde(default)]
+  pub size: Option<ModeSize>,
+  #[serde(default)]
+  pub placement: Option<Placement>,
+  // Epoch ms; None for entries migrated from the legacy keys.
+  #[serde(default)]
+  pub created_at: Option<u64>,
+  #[serde(default)]
+  pub updated_at: Option<u64>,
+}
+
+impl LayoutEntry {
+  fn is_empty(&self) -> bool {
+    self.position.is_none()
+      && self.autosaved_position.is_none()
+      && self.size.is_none()
+      && self.placement.is_none()
+  }
+}
+
+pub type Layout = BTreeMap<String, LayoutEntry>;
+
+pub fn is_legacy_key(key: &str) -> bool {
+  key.starts_with(LEGACY_POSITION_PREFIX)
+    || key.starts_with(LEGACY_PLACEMENT_PREFIX)
+    || key.starts_with(sizing::MODE_SIZE_PREFIX)
+}
+
+/// Applies one legacy flat key to `layout`; null clears that field. Returns
+/// Ok(false) for keys that aren't legacy layout keys and Err for values that
+/// don't parse.
+pub fn merge_legacy(layout: &mut Layout, key: &str, value: &Value) -> Result<bool, String> {
+  fn parse<T: serde::de::DeserializeOwned>(key: &str, value: &Value) -> Result<Option<T>, String> {
+    if value.is_null() {
+      return Ok(None);
+    }
+    serde_json::from_value(value.clone()).map(Some).map_err(|e| format!("{}: {}", key, e))
+  }
+
+  if let Some(mode) = key.strip_prefix(LEGACY_POSITION_PREFIX) {
+    let position = parse(key, value)?;
+    layout.entry(mode.to_string()).or_default().position = position;
+  } else if let Some(mode) = key.strip_prefix(LEGACY_PLACEMENT_PREFIX) {
+    let placement = parse(key, value)?;
+    layout.entry(mode.to_string()).or_default().placement = placement;
+  } else if let Some(mode) = key.strip_prefix(sizing::MODE_SIZE_PREFIX) {
+    let size = parse(key, value)?;
+    layout.entry(mode.to_string()).or_default().size = size;
+  } else {
+    return Ok(false);
+  }
+  Ok(true)
+}
+
+/// The stored `layout` object, ignoring it if it's corrupt.
+fn stored_layout<'a>(entries: impl IntoIterator<Item = (&'a String, &'a Value)>) -> Layout {
+  entries
+    .into_iter()
+    .find(|(key, _)| key.as_str() == LAYOUT_KEY)
+    .and_then(|(_, value)| match serde_json::from_value(value.clone()) {
+      Ok(layout) => Some(layout),
+      Err(e) => {
+        log::warn!("ignoring unreadable layout object: {}", e);
+        None
+      }
+    })
+    .unwrap_or_default()
+}
+
+/// The effective layout for a set of store entries: `layout` wins, and any
+/// field it doesn't have falls back to a legacy flat key.
+pub fn resolve<'a>(entries: impl IntoIterator<Item = (&'a String, &'a Value)> + Clone) -> Layout {
+  let mut layout = Layout::new();
+  for (key, value) in entries.clone() {
+    let _ = merge_legacy(&mut layout, key, value);
+  }
+  for (mode, stored) in stored_layout(entries) {
+    let entry = layout.entry(mode).or_default();
+    entry.position = stored.position.or(entry.position.take());
+    entry.size = stored.size.or(entry.size);
+    entry.placement = stored.placement.or(entry.placement);
+    entry.created_at = stored.created_at;
+    entry.updated_at = stored.updated_at;
+  }
+  layout.retain(|_, entry| !entry.is_empty());
+  layout
+}
+
+fn load_layout(store: &dyn position_store::PositionStore) -> Layout {
+  let entries: Vec<(String, Value)> =
+    store.keys().into_iter().filter_map(|key| Some((key.clone(), store.get(&key)?))).collect();
+  resolve(entries.iter().map(|(k, v)| (k, v)))
+}
+
+/// Writes `layout` back and drops the legacy keys it now covers.
+fn save_layout(store: &dyn position_store::PositionStore, layout: &Layout) -> Result<(), String> {
+  store.set(LAYOUT_KEY, serde_json::to_value(layout).map_err(|e| e.to_string())?);
+  for key in store.keys() {
+    if is_legacy_key(&key) {
+      store.delete(&key);
+    }
+  }
+  store.save()
+}
+
+fn layout_store(app: &AppHandle) -> Result<Arc<Store<Wry>>, String> {
+  profiles::settings_store(app).map_err(|e| e.to_string())
+}
+
+/// Names of every mode with something stored.
+pub fn mode_names(app: &AppHandle) -> Vec<String> {
+  position_store::resolve(app)
+    .map(|store| load_layout(&*store).into_keys().collect())
+    .unwrap_or_default()
+}
+
+/// The layout entry for `mode`; empty when nothing is stored.
+pub fn load_entry(app: &AppHandle, mode: &str) -> LayoutEntry {
+  position_store::resolve(app)
+    .map(|store| load_entry_from(&*store, mode))
+    .unwrap_or_default()
+}
+
+/// `load_entry` against a given store.
+pub fn load_entry_from(store: &dyn position_store::PositionStore, mode: &str) -> LayoutEntry {
+  load_layout(store).remove(mode).unwrap_or_default()
+}
+
+/// Read-modify-write of one mode's entry, stamping its timestamps. Entries
+/// left with nothing in them are removed.
+pub fn update_entry(
+  app: &AppHandle,
+  mode: &str,
+  update: impl FnOnce(&mut LayoutEntry),
+) -> Result<(), String> {
+  let store = position_store::resolve(app)?;
+  if update_entry_in(&*store, mode, crate::now_ms(), update)? {
+    tray::refresh_menu(app);
+  }
+  Ok(())
+}
+
+/// `update_entry` against a given store. Returns whether `mode` was added
+/// or removed.
+pub fn update_entry_in(
+  store: &dyn position_store::PositionStore,
+  mode: &str,
+  now: u64,
+  update: impl FnOnce(&mut LayoutEntry),
+) -> Result<bool, String> {
+  let mut layout = load_layout(store);
+  let added_or_removed = update_layout(&mut layout, mode, now, update);
+  save_layout(store, &layout)?;
+  Ok(added_or_removed)
+}
+
+/// The in-memory half of `update_entry`. Returns whether `mode` was added to
+/// or removed from the layout.
+pub fn update_layout(
+  layout: &mut Layout,
+  mode: &str,
+  now: u64,
+  update: impl FnOnce(&mut LayoutEntry),
+) -> bool {
+  let existed = layout.contains_key(mode);
+  let entry = layout.entry(mode.to_string()).or_default();
+  update(entry);
+
+  entry.created_at.get_or_insert(now);
+  entry.updated_at = Some(now);
+  if entry.is_empty() {
+    layout.remove(mode);
+  }
+  existed != layout.contains_key(mode)
+}
+
+/// Applies legacy flat keys written through `save_settings` to the layout.
+pub fn write_legacy(app: &AppHandle, entries: &[(&String, &Value)]) -> Result<(), String> {
+  let store = layout_store(app)?;
+  let mut layout = load_layout(&store);
+  for (key, value) in entries {
+    merge_legacy(&mut layout, key, value)?;
+  }
+  layout.retain(|_, entry| !entry.is_empty());
+  save_layout(&store, &layout)?;
+  tray::refresh_menu(app);
+  Ok(())
+}
+
+/// Startup migration (schema 1 → 2): folds every legacy flat key into the
+/// `layout` object. Corrupt legacy values are dropped. Returns how many keys
+/// were migrated and how many were dropped.
+pub fn migrate_legacy_keys(store: &Store<Wry>) -> Result<(usize, usize), String> {
+  let entries = store.entries();
+  let (layout, migrated, dropped) = fold_legacy(entries.iter().map(|(k, v)| (k, v)));
+  save_layout(store, &layout)?;
+  Ok((migrated, dropped))
+}
+
+/// The pure part of the migration. Legacy values override whatever is in
+/// `layout` already, since only older builds write them.
+pub fn fold_legacy<'a>(
+  entries: impl IntoIterator<Item = (&'a String, &'a Value)> + Clone,
+) -> (Layout, usize, usize) {
+  let mut layout = stored_layout(entries.clone());
+  let (mut migrated, mut dropped) = (0, 0);
+  for (key, value) in entries {
+    match merge_legacy(&mut layout, key, value) {
+      Ok(true) => migrated += 1,
+      Ok(false) => {}
+      Err(e) => {
+        log::warn!("dropping unreadable legacy layout key {}", e);
+        dropped += 1;
+      }
+    }
+  }
+  layout.retain(|_, entry| !entry.is_empty());
+  (layout, migrated, dropped)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedLayout {
+  pub mode: String,
+  #[serde(flatten)]
+  pub position: WindowPos,
+}
+
+/// Fingerprint of the monitors connected right now.
+pub fn current_fingerprint(app: &AppHandle) -> Option<u64> {
+  app.available_monitors().ok().map(|m| monitors::fingerprint(&m))
+}
+
+/// An entry is stale once its monitor setup hasn't been seen for `max_age_ms`.
+/// Entries without a fingerprint, or with one we never tracked, are kept.
+pub fn is_stale(
+  fingerprint: Option<u64>,
+  seen: &HashMap<String, u64>,
+  now_ms: u64,
+  max_age_ms: u64,
+) -> bool {
+  let Some(fingerprint) = fingerprint else {
+    return false;
+  };
+  match seen.get(&format!("{:x}", fingerprint)) {
+    Some(last_seen) => now_ms.saturating_sub(*last_seen) > max_age_ms,
+    None => false,
+  }
+}
+
+fn load_seen(app: &AppHandle) -> HashMap<String, u64> {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(SEEN_LAYOUTS_KEY))
+    .and_then(|value| serde_json::from_value(value).ok())
+    .unwrap_or_default()
+}
+
+/// Records that the current monitor setup is connected now.
+pub fn mark_seen(app: &AppHandle) {
+  let (Some(fingerprint), Ok(store)) = (current_fingerprint(app), profiles::settings_store(app)) else {
+    return;
+  };
+  let mut seen = load_seen(app);
+  seen.insert(format!("{:x}", fingerprint), crate::now_ms());
+  if let Ok(value) = serde_json::to_value(&seen) {
+    store.set(SEEN_LAYOUTS_KEY, value);
+    let _ = store.save();
+  }
+}
+
+fn saved_layouts(app: &AppHandle) -> Result<Vec<SavedLayout>, String> {
+  let store = layout_store(app)?;
+  // BTreeMap, so already sorted by mode.
+  Ok(
+    load_layout(&store)
+      .into_iter()
+      .filter_map(|(mode, entry)| Some(SavedLayout { mode, position: entry.position? }))
+      .collect(),
+  )
+}
+
+/// Startup pass: flags saved positions whose monitor setup has been gone for
+/// longer than `stale_layout_days`, and unflags ones whose setup is back.
+pub fn refresh_staleness(app: &AppHandle) {
+  mark_seen(app);
+
+  let Ok(store) = profiles::settings_store(app) else {
+    return;
+  };
+  let days = store
+    .get(STALE_DAYS_KEY)
+    .and_then(|value| value.as_u64())
+    .unwrap_or(settings::DEFAULT_STALE_LAYOUT_DAYS);
+  let seen = load_seen(app);
+  let now = crate::now_ms();
+
+  let mut layout = load_layout(&store);
+  let mut changed = 0;
+  let positions = layout
+    .values_mut()
+    .flat_map(|entry| [entry.position.as_mut(), entry.autosaved_position.as_mut()])
+    .flatten();
+  for position in positions {
+    let stale = is_stale(position.fingerprint, &seen, now, days * DAY_MS);
+    if stale != position.stale {
+      position.stale = stale;
+      changed += 1;
+    }
+  }
+
+  if changed > 0 {
+    log::info!("updated stale flag on {} saved layouts", changed);
+    if let Err(e) = save_layout(&store, &layout) {
+      log::warn!("failed to save stale flags: {}", e);
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModeInfo {
+  pub name: String,
+  pub position: Option<WindowPos>,
+  pub size: Option<(u32, u32)>,
+}
+
+/// Every mode the layout knows about, so the frontend doesn't have to
+/// hardcode mode names. Legacy keys are included via the read fallback.
+#[tauri::command]
+pub fn get_all_modes(app: AppHandle) -> Result<Vec<ModeInfo>, AppError> {
+  audit::run(app.clone(), "get_all_modes", move || {
+    let store = layout_store(&app)?;
+    Ok(
+      load_layout(&store)
+        .into_iter()
+        .map(|(name, entry)| ModeInfo {
+          name,
+          position: entry.position,
+          size: entry.size.map(|s| (s.width, s.height)),
+        })
+        .collect(),
+    )
+  })
+}
+
+/// Removes everything stored for `mode` (position, size, anchor). If it was
+/// the current mode the panel falls back to "default".
+#[tauri::command]
+pub fn delete_mode(app: AppHandle, mode: String) -> CommandResult {
+  audit::run(app.clone(), "delete_mode", move || {
+    log::info!("delete_mode: mode={}", mode);
+
+    let store = layout_store(&app)?;
+    let mut layout = load_layout(&store);
+    layout.remove(&mode);
+    save_layout(&store, &layout)?;
+
+    let state = app.state::<AppState>();
+    let was_current = {
+      let mut current = state.current_mode.lock().map_err(|e| e.to_string())?;
+      let was_current = *current == mode;
+      if was_current {
+        *current = "default".to_string();
+      }
+      was_current
+    };
+    if was_current {
+      controller::reapply_layout(&app);
+    }
+
+    tray::refresh_menu(&app);
+    let _ = journal::emit(&app, "mode-deleted", serde_json::json!({ "mode": mode }));
+    Ok(ok_status())
+  })
+}
+
+/// A mode's layout as shared between installs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModeExport {
+  pub schema_version: u32,
+  pub mode: String,
+  #[serde(flatten)]
+  pub entry: LayoutEntry,
+}
+
+/// Parses and checks an exported mode. Staleness is local to the install
+/// it came from, so it is cleared.
+pub fn parse_export(json: &str) -> Result<ModeExport, String> {
+  let mut export: ModeExport = serde_json::from_str(json).map_err(|e| e.to_string())?;
+  if export.schema_version > settings::SCHEMA_VERSION {
+    return Err(format!("Exported by a newer version (schema {})", export.schema_version));
+  }
+  if export.mode.trim().is_empty() {
+    return Err("Mode name must not be empty".to_string());
+  }
+  if export.entry.is_empty() {
+    return Err(format!("Nothing to import for mode {}", export.mode));
+  }
+  let entry = &mut export.entry;
+  let positions = [entry.position.as_mut(), entry.autosaved_position.as_mut()];
+  for position in positions.into_iter().flatten() {
+    position.stale = false;
+  }
+  Ok(export)
+}
+
+#[tauri::command]
+pub fn export_mode(app: AppHandle, mode: String) -> Result<String, AppError> {
+  audit::run(app.clone(), "export_mode", move || {
+    log::info!("export_mode: mode={}", mode);
+
+    let store = layout_store(&app)?;
+    let entry = load_layout(&store).remove(&mode).ok_or(format!("Unknown mode: {}", mode))?;
+    let export = ModeExport { schema_version: settings::SCHEMA_VERSION, mode, entry };
+    Ok(serde_json::to_string_pretty(&export)?)
+  })
+}
+
+/// Imports a mode from `export_mode` output and returns its name.
+#[tauri::command]
+pub fn import_mode(app: AppHandle, json: String, overwrite: bool) -> Result<String, AppError> {
+  audit::run(app.clone(), "import_mode", move || {
+    log::info!("import_mode: overwrite={}", overwrite);
+
+    let export = parse_export(&json).map_err(AppError::InvalidArgument)?;
+    let store = layout_store(&app)?;
+    let mut layout = load_layout(&store);
+    if layout.contains_key(&export.mode) && !overwrite {
+      return Err(AppError::InvalidArgument(format!("Mode already exists: {}", export.mode)));
+    }
+
+    let now = crate::now_ms();
+    let entry = LayoutEntry { created_at: Some(now), updated_at: Some(now), ..export.entry };
+    layout.insert(export.mode.clone(), entry);
+    save_layout(&store, &layout)?;
+    tray::refresh_menu(&app);
+    Ok(export.mode)
+  })
+}
+
+/// Moves `old`'s entry to `new`. Fails if `new` is taken or `old` has nothing.
+pub fn rename_entry(layout: &mut Layout, old: &str, new: &str) -> Result<(), String> {
+  if new.trim().is_empty() {
+    return Err("Mode name must not be empty".to_string());
+  }
+  if layout.contains_key(new) {
+    return Err(format!("Mode already exists: {}", new));
+  }
+  let entry = layout.remove(old).ok_or(format!("Unknown mode: {}", old))?;
+  layout.insert(new.to_string(), entry);
+  Ok(())
+}
+
+/// Renames a mode's stored layout in a single write, following the current
+/// mode along if it was the one renamed.
+#[tauri::command]
+pub fn rename_mode(app: AppHandle, old_name: String, new_name: String) -> CommandResult {
+  audit::run(app.clone(), "rename_mode", move || {
+    log::info!("rename_mode: {} -> {}", old_name, new_name);
+
+    let store = layout_store(&app)?;
+    let mut layout = load_layout(&store);
+    rename_entry(&mut layout, &old_name, &new_name).map_err(AppError::InvalidArgument)?;
+    save_layout(&store, &layout)?;
+
+    let state = app.state::<AppState>();
+    let mut current = state.current_mode.lock().map_err(|e| e.to_string())?;
+    if *current == old_name {
+      *current = new_name.clone();
+    }
+    drop(current);
+
+    tray::refresh_menu(&app);
+    let _ = journal::emit(
+      &app,
+      "mode-renamed",
+      serde_json::json!({ "old": old_name, "new": new_name }),
+    );
+    Ok(ok_status())
+  })
+}
+
+#[tauri::command]
+pub fn list_custom_positions(app: AppHandle) -> Result<Vec<SavedLayout>, AppError> {
+  audit::run(app.clone(), "list_custom_positions", move || {
+    Ok(saved_layouts(&app)?)
+  })
+}
+
+/// Deletes every saved position currently flagged stale. Returns how many.
+#[tauri::command]
+pub fn prune_stale_layouts(app: AppHandle) -> Result<u32, AppError> {
+  audit::run(app.clone(), "prune_stale_layouts", move || {
+    log::info!("prune_stale_layouts invoked");
+
+    let store = layout_store(&app)?;
+    let mut layout = load_layout(&store);
+    let mut pruned = 0;
+    for entry in layout.values_mut() {
+      if entry.position.as_ref().is_some_and(|p| p.stale) {
+        entry.position = None;
+        pruned += 1;
+      }
+    }
+    layout.retain(|_, entry| !entry.is_empty());
+    save_layout(&store, &layout)?;
+    tray::refresh_menu(&app);
+
+    log::debug!("pruned {} stale layouts", pruned);
+    Ok(pruned)
+  })
+}
+
+fn layout_profile_key(name: &str) -> String {
+  format!("{}{}", LAYOUT_PROFILE_PREFIX, name)
+}
+
+fn load_layout_profile(app: &AppHandle, name: &str) -> Result<LayoutProfile, String> {
+  let store = profiles::settings_store(app).map_err(|e| e.to_string())?;
+  let value = store
+    .get(layout_profile_key(name))
+    .ok_or(format!("Unknown layout profile: {}", name))?;
+  serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn save_layout_profile(app: AppHandle, name: String, profile: LayoutProfile) -> CommandResult {
+  audit::run(app.clone(), "save_layout_profile", move || {
+    log::info!("save_layout_profile: name={}, profile={:?}", name, profile);
+
+    profiles::validate_name(&name)?;
+    profile.validate()?;
+    let store = profiles::settings_store(&app)?;
+    let value = serde_json::to_value(&profile).map_err(|e| e.to_string())?;
+    store.set(layout_profile_key(&name), value);
+    store.save().map_err(|e| e.to_string())?;
+    Ok(ok_status())
+  })
+}
+
+#[tauri::command]
+pub fn list_layout_profiles(app: AppHandle) -> Result<Vec<NamedLayoutProfile>, AppError> {
+  audit::run(app.clone(), "list_layout_profiles", move || {
+    let store = profiles::settings_store(&app)?;
+    let mut layouts: Vec<NamedLayoutProfile> = store
+      .entries()
+      .into_iter()
+      .filter_map(|(key, value)| {
+        let name = key.strip_prefix(LAYOUT_PROFILE_PREFIX)?.to_string();
+        let profile = serde_json::from_value(value).ok()?;
+        Some(NamedLayoutProfile { name, profile })
+      })
+      .collect();
+    layouts.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(layouts)
+  })
+}
+
+#[tauri::command]
+pub fn delete_layout_profile(app: AppHandle, name: String) -> Result<bool, AppError> {
+  audit::run(app.clone(), "delete_layout_profile", move || {
+    log::info!("delete_layout_profile: name={}", name);
+
+    let store = profiles::settings_store(&app)?;
+    let existed = store.delete(layout_profile_key(&name));
+    store.save().map_err(|e| e.to_string())?;
+    Ok(existed)
+  })
+}
+
+/// Applies size, monitor, anchor and always-on-top together. If any step
+/// fails the window goes back to how it was.
+#[tauri::command]
+pub fn apply_layout_profile(app: AppHandle, name: String) -> CommandResult {
+  audit::run(app.clone(), "apply_layout_profile", move || {
+    log::info!("apply_layout_profile: name={}", name);
+
+    let profile = load_layout_profile(&app, &name)?;
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+
+    // Resolve everything that can fail before touching the window.
+    let monitors = window.available_monitors().map_err(|e| e.to_string())?;
+    let wanted = profile
+      .monitor
+      .as_ref()
+      .and_then(|name| monitors.iter().find(|m| m.name() == Some(name)).cloned());
+    let monitor: Monitor = match wanted {
+      Some(monitor) => monitor,
+      None => monitors::panel_monitor(&window)?
+        .or_else(|| monitors.into_iter().next())
+        .ok_or(AppError::MonitorNotFound)?,
+    };
+    let before_position = window.outer_position().map_err(|e| e.to_string())?;
+    let before_size = window.outer_size().map_err(|e| e.to_string())?;
+    let before_on_top = stacking::is_always_on_top(&window).map_err(|e| e.to_string())?;
+    history::record(&app, &window);
+
+    autosave::suppress(&window);
+    let apply = || -> Result<(), String> {
+      if let (Some(width), Some(height)) = (profile.width, profile.height) {
+        window
+          .set_size(Size::Physical(PhysicalSize { width, height }))
+          .map_err(|e| e.to_string())?;
+      }
+      placement::apply_placement_on(&window, &monitor, profile.placement)?;
+      stacking::set_always_on_top(&window, profile.always_on_top).map_err(|e| e.to_string())
+    };
+
+    if let Err(e) = apply() {
+      log::warn!("layout profile {} failed ({}); restoring previous layout", name, e);
+      let _ = window.set_size(Size::Physical(before_size));
+      let _ = window.set_position(Position::Physical(before_position));
+      let _ = stacking::set_always_on_top(&window, before_on_top);
+      return Err(e.into());
+    }
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::position_store::{MemoryStore, PositionStore};
+
+  const NOW: u64 = 100 * DAY_MS;
+
+  fn seen(fingerprint: u64, last_seen: u64) -> HashMap<String, u64> {
+    HashMap::from([(format!("{:x}", fingerprint), last_seen)])
+  }
+
+  #[test]
+  fn stale_after_max_age_without_the_setup() {
+    let seen = seen(0xabc, NOW - 31 * DAY_MS);
+    assert!(is_stale(Some(0xabc), &seen, NOW, 30 * DAY_MS));
+    assert!(!is_stale(Some(0xabc), &seen, NOW, 40 * DAY_MS));
+  }
+
+  #[test]
+  fn recently_seen_setup_is_fresh() {
+    let seen = seen(0xabc, NOW - DAY_MS);
+    assert!(!is_stale(Some(0xabc), &seen, NOW, 30 * DAY_MS));
+  }
+
+  fn pos(x: i32, y: i32) -> WindowPos {
+    serde_json::from_value(serde_json::json!({ "x": x, "y": y })).unwrap()
+  }
+
+  #[test]
+  fn saving_a_position_keys_it_by_mode_and_stamps_it() {
+    let mut layout = Layout::new();
+    assert!(update_layout(&mut layout, "reading", 5, |e| e.position = Some(pos(1, 2))));
+    assert_eq!(layout["reading"].position, Some(pos(1, 2)));
+    assert_eq!((layout["reading"].created_at, layout["reading"].updated_at), (Some(5), Some(5)));
+
+    assert!(!update_layout(&mut layout, "reading", 9, |e| e.position = Some(pos(3, 4))));
+    assert_eq!((layout["reading"].created_at, layout["reading"].updated_at), (Some(5), Some(9)));
+
+    assert!(update_layout(&mut layout, "reading", 10, |e| e.position = None));
+    assert!(layout.is_empty());
+  }
+
+  #[test]
+  fn saved_positions_round_trip_through_the_store() {
+    let store = MemoryStore::default();
+    assert!(update_entry_in(&store, "reading", 5, |e| e.position = Some(pos(1, 2))).unwrap());
+    assert_eq!(load_entry_from(&store, "reading").position, Some(pos(1, 2)));
+    assert_eq!(load_entry_from(&store, "other").position, None);
+  }
+
+  #[test]
+  fn clearing_a_position_removes_it_from_the_store() {
+    let store = MemoryStore::default();
+    let legacy = format!("{}reading", LEGACY_POSITION_PREFIX);
+    store.set(&legacy, serde_json::json!({ "x": 3, "y": 4 }));
+    update_entry_in(&store, "reading", 5, |e| e.position = Some(pos(1, 2))).unwrap();
+    assert!(!store.has(&legacy));
+
+    assert!(update_entry_in(&store, "reading", 6, |e| e.position = None).unwrap());
+    assert_eq!(load_entry_from(&store, "reading"), LayoutEntry::default());
+    assert_eq!(store.get(LAYOUT_KEY), Some(serde_json::json!({})));
+  }
+
+  #[test]
+  fn positions_resolve_from_the_layout_or_legacy_keys() {
+    let stored = serde_json::json!({ "reading": { "position": { "x": 1, "y": 2 } } });
+    let entries: Vec<(String, Value)> = vec![
+      (LAYOUT_KEY.to_string(), stored),
+      (format!("{}old", LEGACY_POSITION_PREFIX), serde_json::json!({ "x": 3, "y": 4 })),
+      (format!("{}broken", LEGACY_POSITION_PREFIX), serde_json::json!("nope")),
+    ];
+    let layout = resolve(entries.iter().map(|(k, v)| (k, v)));
+    assert_eq!(layout["reading"].position, Some(pos(1, 2)));
+    assert_eq!(layout["old"].position, Some(pos(3, 4)));
+    assert!(!layout.contains_key("broken"));
+  }
+
+  #[test]
+  fn layout_profile_defaults_for_missing_fields() {
+    let profile: LayoutProfile = serde_json::from_value(serde_json::json!({
+      "placement": { "anchor": "right-center", "margin": 40 }
+    }))
+    .unwrap();
+    assert_eq!(profile.width, None);
+    assert_eq!(profile.monitor, None);
+    assert!(profile.always_on_top);
+  }
+
+  #[test]
+  fn rename_refuses_taken_or_missing_modes() {
+    let placement = Placement { anchor: placement::Anchor::Center, margin: 0 };
+    let entry = LayoutEntry { placement: Some(placement), ..Default::default() };
+    let mut layout = Layout::from([("a".to_string(), entry.clone()), ("b".to_string(), entry)]);
+
+    assert!(rename_entry(&mut layout, "a", "b").is_err());
+    assert!(rename_entry(&mut layout, "missing", "c").is_err());
+    rename_entry(&mut layout, "a", "c").unwrap();
+    assert!(!layout.contains_key("a"));
+    assert_eq!(layout["c"].placement, Some(placement));
+  }
+
+  #[test]
+  fn exported_modes_round_trip_without_stale_flags() {
+    let json = serde_json::json!({
+      "schema_version": 2,
+      "mode": "reading",
+      "position": { "x": 1, "y": 2, "stale": true },
+      "size": { "width": 400, "height": 300 },
+    })
+    .to_string();
+    let export = parse_export(&json).unwrap();
+    assert_eq!(export.mode, "reading");
+    assert!(!export.entry.position.unwrap().stale);
+    assert_eq!(export.entry.size, Some(ModeSize { width: 400, height: 300 }));
+  }
+
+  #[test]
+  fn autosaved_position_alone_keeps_an_entry() {
+    let entry: LayoutEntry = serde_json::from_value(serde_json::json!({
+      "autosaved_position": { "x": 10, "y": 20 }
+    }))
+    .unwrap();
+    assert!(!entry.is_empty());
+    assert_eq!(entry.position, None);
+
+    let old: LayoutEntry =
+      serde_json::from_value(serde_json::json!({ "size": { "width": 1, "height": 1 } })).unwrap();
+    assert_eq!(old.autosaved_position, None);
+  }
+
+  #[test]
+  fn rejects_empty_or_future_exports() {
+    assert!(parse_export(r#"{ "schema_version": 2, "mode": "x" }"#).is_err());
+    let future = r#"{ "schema_version": 99, "mode": "x", "size": { "width": 1, "height": 1 } }"#;
+    assert!(parse_export(future).is_err());
+    assert!(parse_export("not json").is_err());
+  }
+
+  #[test]
+  fn unknown_or_missing_fingerprints_are_kept() {
+    let seen = seen(0xabc, 0);
+    assert!(!is_stale(None, &seen, NOW, DAY_MS));
+    assert!(!is_stale(Some(0xdef), &seen, NOW, DAY_MS));
+  }
+}
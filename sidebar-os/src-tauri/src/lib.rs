@@ -1,59 +1,118 @@
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
-use tauri::{Emitter, Listener, Manager, PhysicalPosition, PhysicalSize, Position};
-use tauri_plugin_global_shortcut::GlobalShortcutExt;
+use tauri::{Listener, Manager, PhysicalPosition, PhysicalSize, Position};
 use tauri_plugin_log::{Target, TargetKind};
-use tauri_plugin_store::StoreExt;
 use serde::{Deserialize, Serialize};
 
-#[tauri::command]
-fn position_window_top_center(app: tauri::AppHandle) -> Result<(), String> {
-  log::info!("position_window_top_center invoked");
-
-  let window = app.get_webview_window("panel")
-    .ok_or("Window not found")?;
-
-  let monitor = window.current_monitor()
-    .map_err(|e| e.to_string())?
-    .ok_or("No monitor found")?;
-
-  let monitor_size = monitor.size().to_owned();
-  let monitor_position = monitor.position().to_owned();
-  let window_size = window.outer_size()
-    .map_err(|e| e.to_string())?;
-
-  log::debug!(
-    "monitor size={}x{}, pos=({}, {}), window size={}x{}",
-    monitor_size.width,
-    monitor_size.height,
-    monitor_position.x,
-    monitor_position.y,
-    window_size.width,
-    window_size.height
-  );
-
-  // macOS with Tao/Tauri reports positions with a top-left origin for the screen
-  // coordinates. Using bottom-left origin here was placing the window near the
-  // bottom. Force top-origin calculation for consistent "top-center" placement.
-  let (final_x, final_y) = calculate_top_center_position(
-    monitor_position,
-    monitor_size,
-    window_size,
-    40,
-    false,
-  );
-
-  log::debug!("final collapsed position resolved to ({}, {})", final_x, final_y);
-
-  window
-    .set_position(Position::Physical(PhysicalPosition { x: final_x, y: final_y }))
-    .map_err(|e| e.to_string())?;
-
-  let _ = window.show();
-  let _ = window.set_always_on_top(true);
-  let _ = window.set_focus();
-  log::debug!("panel set visible and focused");
+mod audit;
+mod autosave;
+mod background;
+mod controller;
+mod diagnostics;
+mod double_tap;
+mod error;
+mod error_codes;
+mod escape;
+mod history;
+mod hot_corner;
+mod hotkey_capture;
+mod hotkeys;
+mod input_region;
+mod journal;
+mod layouts;
+mod migrations;
+mod monitors;
+mod onboarding;
+mod paths;
+mod pin;
+mod placement;
+mod position_store;
+mod profiles;
+mod quit;
+mod rate_limit;
+mod resize;
+mod screenshot;
+mod secrets;
+mod settings;
+mod sizing;
+mod snapshots;
+mod stacking;
+mod state;
+mod theme;
+mod timing;
+mod title;
+mod tray;
+mod ui_prefs;
+mod usage;
+mod validate;
+mod webview;
+
+use error::{ok_status, AppError, CommandResult};
+use placement::{Anchor, Placement};
+use rate_limit::{Verdict, DEBUG_LOG_PER_SECOND};
+use state::AppState;
+use usage::ShowTrigger;
+use validate::Validate;
+
+pub(crate) fn now_ms() -> u64 {
+  std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .map(|d| d.as_millis() as u64)
+    .unwrap_or(0)
+}
 
-  Ok(())
+#[tauri::command]
+fn position_window_top_center(app: tauri::AppHandle) -> CommandResult {
+  audit::run(app.clone(), "position_window_top_center", move || {
+    log::info!("position_window_top_center invoked");
+
+    let window = app.get_webview_window("panel")
+      .ok_or(AppError::WindowNotFound)?;
+    history::record(&app, &window);
+
+    let monitor = monitors::panel_monitor(&window)?
+      .ok_or(AppError::MonitorNotFound)?;
+
+    let (monitor_position, monitor_size) = monitors::usable_area(&app, &monitor);
+    let window_size = window.outer_size()
+      .map_err(|e| e.to_string())?;
+
+    log::debug!(
+      "monitor size={}x{}, pos=({}, {}), window size={}x{}",
+      monitor_size.width,
+      monitor_size.height,
+      monitor_position.x,
+      monitor_position.y,
+      window_size.width,
+      window_size.height
+    );
+
+    // macOS with Tao/Tauri reports positions with a top-left origin for the screen
+    // coordinates. Using bottom-left origin here was placing the window near the
+    // bottom. Force top-origin calculation for consistent "top-center" placement.
+    let margin = placement::default_placement("hovered").margin;
+    let (final_x, final_y) = calculate_top_center_position(
+      monitor_position,
+      monitor_size,
+      window_size,
+      margin,
+      false,
+    );
+
+    log::debug!("final collapsed position resolved to ({}, {})", final_x, final_y);
+    placement::remember(&app, Placement { anchor: Anchor::TopCenter, margin });
+
+    autosave::suppress(&window);
+    window
+      .set_position(Position::Physical(PhysicalPosition { x: final_x, y: final_y }))
+      .map_err(|e| e.to_string())?;
+
+    let _ = window.show();
+    let _ = stacking::set_always_on_top(&window, true);
+    let _ = window.set_focus();
+    log::debug!("panel set visible and focused");
+
+    Ok(ok_status())
+  })
 }
 
 fn calculate_top_center_position(
@@ -82,188 +141,285 @@ fn calculate_top_center_position(
   (clamped_x, clamped_y)
 }
 
-#[tauri::command]
-fn center_window(app: tauri::AppHandle) -> Result<(), String> {
-  log::info!("center_window invoked");
-
-  let window = app.get_webview_window("panel")
-    .ok_or("Window not found")?;
-
-  window.center()
-    .map_err(|e| e.to_string())?;
-
-  log::debug!("panel centered");
-  Ok(())
-}
-
-#[tauri::command]
-fn position_window_right_center(app: tauri::AppHandle, margin: Option<i32>) -> Result<(), String> {
-  log::info!("position_window_right_center invoked");
-
-  let window = app
-    .get_webview_window("panel")
-    .ok_or("Window not found")?;
-
-  let monitor = window
-    .current_monitor()
-    .map_err(|e| e.to_string())?
-    .ok_or("No monitor found")?;
-
-  let monitor_size = monitor.size().to_owned();
-  let monitor_position = monitor.position().to_owned();
-  let window_size = window.outer_size().map_err(|e| e.to_string())?;
-
-  let m = margin.unwrap_or(40);
+fn calculate_corner_position(
+  monitor_position: PhysicalPosition<i32>,
+  monitor_size: PhysicalSize<u32>,
+  window_size: PhysicalSize<u32>,
+  margin: i32,
+  corner: hot_corner::Corner,
+) -> (i32, i32) {
+  use hot_corner::Corner;
 
-  // top-left origin coordinates
-  let desired_x = monitor_position.x + (monitor_size.width as i32 - window_size.width as i32) - m;
+  let available_width = monitor_size.width as i32 - window_size.width as i32;
   let available_height = monitor_size.height as i32 - window_size.height as i32;
-  let desired_y = monitor_position.y + available_height / 2; // vertical center
-
-  let min_x = monitor_position.x;
-  let max_x = monitor_position.x + (monitor_size.width as i32 - window_size.width as i32);
-  let min_y = monitor_position.y;
-  let max_y = monitor_position.y + available_height;
-
-  let clamped_x = desired_x.clamp(min_x, max_x);
-  let clamped_y = desired_y.clamp(min_y, max_y);
 
-  window
-    .set_position(Position::Physical(PhysicalPosition {
-      x: clamped_x,
-      y: clamped_y,
-    }))
-    .map_err(|e| e.to_string())?;
+  let desired_x = match corner {
+    Corner::TopLeft | Corner::BottomLeft => monitor_position.x + margin,
+    Corner::TopRight | Corner::BottomRight => monitor_position.x + available_width - margin,
+  };
+  let desired_y = match corner {
+    Corner::TopLeft | Corner::TopRight => monitor_position.y + margin,
+    Corner::BottomLeft | Corner::BottomRight => monitor_position.y + available_height - margin,
+  };
 
-  let _ = window.show();
-  let _ = window.set_always_on_top(true);
-  let _ = window.set_focus();
-  log::debug!("panel moved to right-center at ({}, {})", clamped_x, clamped_y);
+  let clamped_x = desired_x.clamp(monitor_position.x, monitor_position.x + available_width.max(0));
+  let clamped_y = desired_y.clamp(monitor_position.y, monitor_position.y + available_height.max(0));
 
-  Ok(())
+  (clamped_x, clamped_y)
 }
 
 #[tauri::command]
-fn position_window_left_center(app: tauri::AppHandle, margin: Option<i32>) -> Result<(), String> {
-  log::info!("position_window_left_center invoked");
+fn center_window(app: tauri::AppHandle) -> CommandResult {
+  audit::run(app.clone(), "center_window", move || {
+    log::info!("center_window invoked");
 
-  let window = app
-    .get_webview_window("panel")
-    .ok_or("Window not found")?;
+    let window = app.get_webview_window("panel")
+      .ok_or(AppError::WindowNotFound)?;
+    history::record(&app, &window);
 
-  let monitor = window
-    .current_monitor()
-    .map_err(|e| e.to_string())?
-    .ok_or("No monitor found")?;
+    window.center()
+      .map_err(|e| e.to_string())?;
+    placement::remember(&app, Placement { anchor: Anchor::Center, margin: 0 });
 
-  let monitor_size = monitor.size().to_owned();
-  let monitor_position = monitor.position().to_owned();
-  let window_size = window.outer_size().map_err(|e| e.to_string())?;
-
-  let m = margin.unwrap_or(40);
-
-  // top-left origin coordinates; left edge + margin
-  let desired_x = monitor_position.x + m;
-  let available_height = monitor_size.height as i32 - window_size.height as i32;
-  let desired_y = monitor_position.y + available_height / 2; // vertical center
+    log::debug!("panel centered");
+    Ok(ok_status())
+  })
+}
 
-  let min_x = monitor_position.x;
-  let max_x = monitor_position.x + (monitor_size.width as i32 - window_size.width as i32);
-  let min_y = monitor_position.y;
-  let max_y = monitor_position.y + available_height;
+#[tauri::command]
+fn position_window_right_center(app: tauri::AppHandle, margin: Option<i32>) -> CommandResult {
+  audit::run(app.clone(), "position_window_right_center", move || {
+    log::info!("position_window_right_center invoked");
+
+    let window = app
+      .get_webview_window("panel")
+      .ok_or(AppError::WindowNotFound)?;
+    history::record(&app, &window);
+
+    let monitor = monitors::panel_monitor(&window)?.ok_or(AppError::MonitorNotFound)?;
+
+    let (monitor_position, monitor_size) = monitors::usable_area(&app, &monitor);
+    let window_size = window.outer_size().map_err(|e| e.to_string())?;
+
+    let m = margin.unwrap_or(placement::default_placement("sidepanel_right").margin);
+    let right = Placement { anchor: Anchor::RightCenter, margin: m };
+    let (clamped_x, clamped_y) =
+      placement::calculate_anchor_position(monitor_position, monitor_size, window_size, right);
+
+    autosave::suppress(&window);
+    window
+      .set_position(Position::Physical(PhysicalPosition {
+        x: clamped_x,
+        y: clamped_y,
+      }))
+      .map_err(|e| e.to_string())?;
+
+    let _ = window.show();
+    let _ = stacking::set_always_on_top(&window, true);
+    let _ = window.set_focus();
+    log::debug!("panel moved to right-center at ({}, {})", clamped_x, clamped_y);
+    placement::remember(&app, Placement { anchor: Anchor::RightCenter, margin: m });
+
+    Ok(ok_status())
+  })
+}
 
-  let clamped_x = desired_x.clamp(min_x, max_x);
-  let clamped_y = desired_y.clamp(min_y, max_y);
+#[tauri::command]
+fn position_window_left_center(app: tauri::AppHandle, margin: Option<i32>) -> CommandResult {
+  audit::run(app.clone(), "position_window_left_center", move || {
+    log::info!("position_window_left_center invoked");
+
+    let window = app
+      .get_webview_window("panel")
+      .ok_or(AppError::WindowNotFound)?;
+    history::record(&app, &window);
+
+    let monitor = monitors::panel_monitor(&window)?.ok_or(AppError::MonitorNotFound)?;
+
+    let (monitor_position, monitor_size) = monitors::usable_area(&app, &monitor);
+    let window_size = window.outer_size().map_err(|e| e.to_string())?;
+
+    let m = margin.unwrap_or(placement::default_placement("sidepanel_left").margin);
+    let left = Placement { anchor: Anchor::LeftCenter, margin: m };
+    let (clamped_x, clamped_y) =
+      placement::calculate_anchor_position(monitor_position, monitor_size, window_size, left);
+
+    autosave::suppress(&window);
+    window
+      .set_position(Position::Physical(PhysicalPosition {
+        x: clamped_x,
+        y: clamped_y,
+      }))
+      .map_err(|e| e.to_string())?;
+
+    let _ = window.show();
+    let _ = stacking::set_always_on_top(&window, true);
+    let _ = window.set_focus();
+    log::debug!("panel moved to left-center at ({}, {})", clamped_x, clamped_y);
+    placement::remember(&app, Placement { anchor: Anchor::LeftCenter, margin: m });
+
+    Ok(ok_status())
+  })
+}
 
-  window
-    .set_position(Position::Physical(PhysicalPosition {
-      x: clamped_x,
-      y: clamped_y,
-    }))
-    .map_err(|e| e.to_string())?;
+#[tauri::command]
+fn debug_log(app: tauri::AppHandle, level: String, message: String) -> CommandResult {
+  audit::run(app.clone(), "debug_log", move || {
+    let now = std::time::Instant::now();
+    let verdict = app.state::<AppState>().debug_log_limit.lock().unwrap().check(now);
+    match verdict {
+      Verdict::Log => {}
+      Verdict::Drop => return Ok(ok_status()),
+      Verdict::DropAndWarn(dropped) => {
+        log::warn!(
+          "debug_log is over {} lines a second; {} dropped",
+          DEBUG_LOG_PER_SECOND,
+          dropped
+        );
+        let payload = serde_json::json!({ "dropped": dropped });
+        let _ = journal::emit(&app, "log-rate-limit-exceeded", payload);
+        return Ok(ok_status());
+      }
+    }
 
-  let _ = window.show();
-  let _ = window.set_always_on_top(true);
-  let _ = window.set_focus();
-  log::debug!("panel moved to left-center at ({}, {})", clamped_x, clamped_y);
+    let secrets = app.state::<AppState>().known_secrets.lock().unwrap().clone();
+    let redacted = secrets::redact(&message, &secrets);
+    let trimmed = redacted.trim();
+    match level.to_lowercase().as_str() {
+      "error" => log::error!(target: "webview", "{trimmed}"),
+      "warn" => log::warn!(target: "webview", "{trimmed}"),
+      "debug" => log::debug!(target: "webview", "{trimmed}"),
+      "trace" => log::trace!(target: "webview", "{trimmed}"),
+      _ => log::info!(target: "webview", "{trimmed}"),
+    }
+    Ok(ok_status())
+  })
+}
 
-  Ok(())
+// Position storage structures
+// Entries saved before size tracking only have x/y; the rest default to None
+// and those restore position-only.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct WindowPos {
+  pub x: i32,
+  pub y: i32,
+  #[serde(default)]
+  pub width: Option<u32>,
+  #[serde(default)]
+  pub height: Option<u32>,
+  #[serde(default)]
+  pub monitor: Option<String>,
+  #[serde(default)]
+  pub scale_factor: Option<f64>,
+  // Monitor setup the entry was saved on; see `layouts`.
+  #[serde(default)]
+  pub fingerprint: Option<u64>,
+  // Set at startup once that setup hasn't been seen for a while. Stale
+  // entries are skipped when restoring but kept until pruned.
+  #[serde(default)]
+  pub stale: bool,
 }
 
-#[tauri::command]
-fn debug_log(level: String, message: String) {
-  let trimmed = message.trim();
-  match level.to_lowercase().as_str() {
-    "error" => log::error!(target: "webview", "{trimmed}"),
-    "warn" => log::warn!(target: "webview", "{trimmed}"),
-    "debug" => log::debug!(target: "webview", "{trimmed}"),
-    "trace" => log::trace!(target: "webview", "{trimmed}"),
-    _ => log::info!(target: "webview", "{trimmed}"),
+/// A saved position at (x, y) with the panel's current size and monitor.
+pub(crate) fn window_pos_at(app: &tauri::AppHandle, x: i32, y: i32) -> WindowPos {
+  let window = app.get_webview_window("panel");
+  let size = window.as_ref().and_then(|w| w.outer_size().ok());
+  let monitor = window.as_ref().and_then(|w| w.current_monitor().ok().flatten());
+  WindowPos {
+    x,
+    y,
+    width: size.map(|s| s.width),
+    height: size.map(|s| s.height),
+    monitor: monitor.as_ref().and_then(|m| m.name().cloned()),
+    scale_factor: monitor.as_ref().map(|m| m.scale_factor()),
+    fingerprint: layouts::current_fingerprint(app),
+    stale: false,
   }
 }
 
-// Position storage structures
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct WindowPos {
-  x: i32,
-  y: i32,
+#[tauri::command]
+fn save_custom_position(app: tauri::AppHandle, mode: String, x: i32, y: i32) -> CommandResult {
+  audit::run(app.clone(), "save_custom_position", move || {
+    log::info!("save_custom_position: mode={}, x={}, y={}", mode, x, y);
+
+    let pos = window_pos_at(&app, x, y);
+    pos.validate()?;
+    layouts::update_entry(&app, &mode, |entry| entry.position = Some(pos))?;
+
+    layouts::mark_seen(&app);
+    log::info!("Custom position saved for mode: {}", mode);
+    Ok(ok_status())
+  })
 }
 
-#[tauri::command]
-fn save_custom_position(app: tauri::AppHandle, mode: String, x: i32, y: i32) -> Result<(), String> {
-  log::info!("save_custom_position: mode={}, x={}, y={}", mode, x, y);
+pub(crate) fn load_custom_position(app: &tauri::AppHandle, mode: &str) -> Option<WindowPos> {
+  let pos = layouts::load_entry(app, mode).position?;
+  if pos.stale {
+    log::debug!("ignoring stale custom position for {}", mode);
+    return None;
+  }
+  Some(pos)
+}
 
-  let store = app.store("settings.json").map_err(|e| e.to_string())?;
-  let key = format!("custom_position_{}", mode);
-  let pos = WindowPos { x, y };
+/// Restores the saved custom position for `mode`: size first so the position
+/// lands where it was saved. Returns false if nothing is saved.
+pub(crate) fn restore_mode_snapshot(app: &tauri::AppHandle, mode: &str) -> Result<bool, String> {
+  let Some(pos) = load_custom_position(app, mode) else {
+    log::info!("No window snapshot found for mode: {}", mode);
+    return Ok(false);
+  };
+  let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+  history::record(app, &window);
 
-  let value = serde_json::to_value(&pos).map_err(|e| e.to_string())?;
-  store.set(key, value);
-  store.save().map_err(|e| e.to_string())?;
+  if let (Some(width), Some(height)) = (pos.width, pos.height) {
+    window
+      .set_size(tauri::Size::Physical(PhysicalSize { width, height }))
+      .map_err(|e| e.to_string())?;
+  }
+  autosave::suppress(&window);
+  window
+    .set_position(Position::Physical(PhysicalPosition { x: pos.x, y: pos.y }))
+    .map_err(|e| e.to_string())?;
 
-  log::info!("Custom position saved for mode: {}", mode);
-  Ok(())
+  log::debug!("restored {} snapshot: {:?}", mode, pos);
+  Ok(true)
 }
 
 #[tauri::command]
-fn get_custom_position(app: tauri::AppHandle, mode: String) -> Result<Option<(i32, i32)>, String> {
-  log::info!("get_custom_position: mode={}", mode);
-
-  let store = app.store("settings.json").map_err(|e| e.to_string())?;
-  let key = format!("custom_position_{}", mode);
-
-  match store.get(key) {
-    Some(value) => {
-      let pos: WindowPos = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
-      log::info!("Custom position found for mode {}: ({}, {})", mode, pos.x, pos.y);
-      Ok(Some((pos.x, pos.y)))
-    }
-    None => {
-      log::info!("No custom position found for mode: {}", mode);
-      Ok(None)
+fn get_custom_position(app: tauri::AppHandle, mode: String) -> Result<Option<(i32, i32)>, AppError> {
+  audit::run(app.clone(), "get_custom_position", move || {
+    log::info!("get_custom_position: mode={}", mode);
+
+    match layouts::load_entry(&app, &mode).position {
+      Some(pos) => {
+        log::info!("Custom position found for mode {}: ({}, {})", mode, pos.x, pos.y);
+        Ok(Some((pos.x, pos.y)))
+      }
+      None => {
+        log::info!("No custom position found for mode: {}", mode);
+        Ok(None)
+      }
     }
-  }
+  })
 }
 
 #[tauri::command]
-fn clear_custom_position(app: tauri::AppHandle, mode: String) -> Result<(), String> {
-  log::info!("clear_custom_position: mode={}", mode);
-
-  let store = app.store("settings.json").map_err(|e| e.to_string())?;
-  let key = format!("custom_position_{}", mode);
+fn clear_custom_position(app: tauri::AppHandle, mode: String) -> CommandResult {
+  audit::run(app.clone(), "clear_custom_position", move || {
+    log::info!("clear_custom_position: mode={}", mode);
 
-  store.delete(key);
-  store.save().map_err(|e| e.to_string())?;
+    layouts::update_entry(&app, &mode, |entry| entry.position = None)?;
 
-  log::info!("Custom position cleared for mode: {}", mode);
-  Ok(())
+    log::info!("Custom position cleared for mode: {}", mode);
+    Ok(ok_status())
+  })
 }
 
 #[tauri::command]
-fn has_custom_position(app: tauri::AppHandle, mode: String) -> Result<bool, String> {
-  let store = app.store("settings.json").map_err(|e| e.to_string())?;
-  let key = format!("custom_position_{}", mode);
-  Ok(store.has(key))
+fn has_custom_position(app: tauri::AppHandle, mode: String) -> Result<bool, AppError> {
+  audit::run(app.clone(), "has_custom_position", move || {
+    Ok(layouts::load_entry(&app, &mode).position.is_some())
+  })
 }
 
 pub fn run() {
@@ -271,13 +427,10 @@ pub fn run() {
     .plugin(tauri_plugin_global_shortcut::Builder::new().build())
     .plugin(tauri_plugin_store::Builder::new().build())
     .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-      if let Some(win) = app.get_webview_window("panel") {
-        let _ = win.show();
-        let _ = win.set_focus();
-        let _ = app.emit("panel-should-expand", ());
-      }
+      controller::show_expanded(app, ShowTrigger::SingleInstance);
     }))
-    .invoke_handler(tauri::generate_handler![
+    .manage(AppState::default())
+    .invoke_handler(audit::audited(timing::timed(tauri::generate_handler![
       position_window_top_center,
       center_window,
       position_window_right_center,
@@ -286,21 +439,162 @@ pub fn run() {
       save_custom_position,
       get_custom_position,
       clear_custom_position,
-      has_custom_position
-    ])
+      has_custom_position,
+      journal::get_event_journal,
+      journal::clear_event_journal,
+      journal::set_event_journal_size,
+      hot_corner::set_hot_corner,
+      double_tap::set_double_tap_modifier,
+      hotkeys::get_hotkey_config,
+      hotkeys::set_hotkey_config,
+      diagnostics::get_memory_usage,
+      diagnostics::get_cpu_usage,
+      diagnostics::is_panel_visible,
+      diagnostics::health_check,
+      diagnostics::get_uptime_seconds,
+      tray::set_busy,
+      placement::set_mode_placement,
+      placement::get_mode_placement,
+      profiles::list_profiles,
+      profiles::create_profile,
+      profiles::switch_profile,
+      profiles::delete_profile,
+      webview::reload_webview,
+      webview::navigate_webview,
+      sizing::set_window_size_percent,
+      secrets::secret_set,
+      secrets::secret_get,
+      secrets::secret_delete,
+      controller::frontend_ready,
+      snapshots::restore_window_snapshot,
+      webview::execute_js,
+      webview::execute_js_result,
+      stacking::set_above_app,
+      layouts::list_custom_positions,
+      layouts::prune_stale_layouts,
+      screenshot::take_screenshot,
+      screenshot::save_screenshot_to_file,
+      diagnostics::get_window_flags,
+      history::undo_window_action,
+      history::redo_window_action,
+      history::get_undo_stack_depth,
+      controller::set_panel_mode,
+      settings::save_settings,
+      pin::pin_to_position,
+      pin::unpin_from_position,
+      layouts::save_layout_profile,
+      layouts::apply_layout_profile,
+      layouts::list_layout_profiles,
+      layouts::delete_layout_profile,
+      settings::get_default_settings,
+      stacking::set_visible_on_fullscreen_spaces,
+      placement::preview_position,
+      placement::best_fit_anchor,
+      paths::get_app_paths,
+      pin::set_window_movable,
+      input_region::set_input_region,
+      settings::get_settings_snapshot,
+      controller::minimize_panel,
+      controller::unminimize_panel,
+      sizing::register_mode_size,
+      sizing::apply_mode_size,
+      ui_prefs::ui_pref_set,
+      ui_prefs::ui_pref_get,
+      ui_prefs::ui_pref_list,
+      controller::switch_mode,
+      monitors::get_work_area,
+      layouts::get_all_modes,
+      usage::get_usage_stats,
+      usage::reset_usage_stats,
+      layouts::delete_mode,
+      title::set_window_title,
+      onboarding::get_onboarding_state,
+      onboarding::set_onboarding_step,
+      onboarding::complete_onboarding,
+      onboarding::reset_onboarding,
+      layouts::rename_mode,
+      webview::webview_to_screen,
+      theme::get_theme,
+      theme::get_effective_theme,
+      theme::set_theme,
+      layouts::export_mode,
+      layouts::import_mode,
+      resize::set_resize_directions,
+      snapshots::save_window_snapshot,
+      snapshots::list_window_snapshots,
+      snapshots::delete_window_snapshot,
+      hotkeys::get_global_shortcut_list,
+      webview::toggle_devtools,
+      hotkeys::test_shortcut,
+      monitors::monitors_changed_since,
+      hotkeys::register_hotkey,
+      hotkeys::unregister_hotkey,
+      hotkeys::pause_all_shortcuts,
+      hotkeys::resume_all_shortcuts,
+      controller::apply_window_geometry,
+      hotkeys::list_registered_hotkeys,
+      history::capture_geometry,
+      history::restore_geometry,
+      hotkeys::register_chord_shortcut,
+      hotkeys::test_hotkey,
+      webview::set_debug_mode,
+      webview::open_devtools,
+      webview::close_devtools,
+      webview::is_devtools_open,
+      stacking::set_skip_taskbar,
+      stacking::set_always_on_top_level,
+      hotkeys::set_hotkey_behavior,
+      webview::get_store_keys,
+      webview::get_store_value,
+      webview::set_store_value,
+      escape::handle_escape,
+      escape::set_escape_action,
+      diagnostics::get_tauri_config_summary,
+      stacking::suspend_always_on_top,
+      stacking::resume_always_on_top,
+      hotkeys::reset_hotkeys_to_default,
+      diagnostics::get_environment_variables,
+      paths::get_store_dir,
+      paths::set_store_dir,
+      hotkey_capture::start_hotkey_capture,
+      hotkey_capture::cancel_hotkey_capture,
+      hotkey_capture::submit_hotkey_capture,
+      hotkeys::suspend_hotkeys,
+      hotkeys::resume_hotkeys,
+      quit::confirm_quit,
+      quit::quit_app,
+      audit::get_audit_log,
+      audit::clear_audit_log,
+      background::set_background_color,
+      monitors::set_pinned_monitor
+    ])))
     .setup(|app| {
+      // Resolve the settings profile before anything reads from the store
+      profiles::load_active(app.handle());
+      settings::seed_defaults(app.handle());
+      migrations::run(app.handle());
+      layouts::refresh_staleness(app.handle());
+
       if cfg!(debug_assertions) {
         app.handle().plugin(
           tauri_plugin_log::Builder::default()
             // In dev, crank log level to Debug so we capture bridge/api events in the Tauri console.
-            .level(log::LevelFilter::Debug)
+            // Trace is let through too; debug mode decides which one is in effect.
+            .level(log::LevelFilter::Trace)
             .targets([
               Target::new(TargetKind::Stdout),
-              Target::new(TargetKind::LogDir { file_name: None })
+              match paths::portable_root() {
+                Some(_) => Target::new(TargetKind::Folder {
+                  path: paths::log_dir(app.handle())?,
+                  file_name: None,
+                }),
+                None => Target::new(TargetKind::LogDir { file_name: None }),
+              },
             ])
             .build(),
         )?;
       }
+      webview::restore_debug_mode(app.handle());
 
       // Prevent default close behavior that hides the window
       if let Some(window) = app.get_webview_window("panel") {
@@ -311,114 +605,73 @@ pub fn run() {
       }
 
       let app_handle = app.handle();
-      // Auto-show panel on launch for first-run convenience
-      if let Some(w) = app.get_webview_window("panel") {
-        let _ = w.show();
-        let _ = w.set_focus();
-        let _ = app.emit("panel-should-expand", ());
-      }
+      background::restore_background(app_handle);
+      // Expand on first run (and until onboarding is done), otherwise restore
+      // the panel's last state
+      controller::show_on_launch(app_handle);
       // Register tray icon with menu
-      let show_item = tauri::menu::MenuItemBuilder::with_id("show", "Show Window").build(app)?;
-      let quit_item = tauri::menu::MenuItemBuilder::with_id("quit", "Quit").build(app)?;
-      let menu = tauri::menu::MenuBuilder::new(app)
-        .item(&show_item)
-        .separator()
-        .item(&quit_item)
-        .build()?;
-
-      let tray = tauri::tray::TrayIconBuilder::with_id("tray")
+      let menu = tray::build_menu(app_handle, &layouts::mode_names(app_handle))?;
+
+      tauri::tray::TrayIconBuilder::with_id(tray::TRAY_ID)
         .icon(app_handle.default_window_icon().unwrap().clone())
         .menu(&menu)
         .on_menu_event(|tray, event| {
           match event.id.as_ref() {
-            "show" => {
-              let app = tray.app_handle();
-              if let Some(w) = app.get_webview_window("panel") {
-                let _ = w.show();
-                let _ = w.set_focus();
-                let _ = w.set_always_on_top(true);
-                let _ = app.emit("panel-should-expand", ());
+            "show" => controller::show_expanded(tray.app_handle(), ShowTrigger::Tray),
+            tray::HOTKEYS_ITEM_ID => {
+              let app = tray.app_handle().clone();
+              let result = if hotkeys::suspended(&app) {
+                hotkeys::resume_hotkeys(app)
+              } else {
+                hotkeys::suspend_hotkeys(app)
+              };
+              if let Err(e) = result {
+                log::warn!("could not toggle hotkeys from the tray: {}", e);
               }
             }
             "quit" => {
               log::info!("quit menu item selected; exiting");
-              std::process::exit(0);
+              quit::shutdown(tray.app_handle());
+            }
+            id => {
+              if let Some(mode) = tray::mode_for_menu_id(id) {
+                let _ = journal::emit(
+                  tray.app_handle(),
+                  "panel-mode-switch-requested",
+                  serde_json::json!({ "mode": mode }),
+                );
+              }
             }
-            _ => {}
           }
         })
         .on_tray_icon_event(|tray, event| {
           // Click always shows window
           if let tauri::tray::TrayIconEvent::Click { .. } = event {
-            let app = tray.app_handle();
-            if let Some(w) = app.get_webview_window("panel") {
-              let _ = w.show();
-              let _ = w.set_focus();
-              let _ = w.set_always_on_top(true);
-              let _ = app.emit("panel-should-expand", ());
-            }
+            controller::show_expanded(tray.app_handle(), ShowTrigger::Tray);
           }
         })
         .build(app)?;
-      let _ = tray.set_tooltip(Some("Demo AI - Click to Show"));
-
-      // Global hotkeys to always show panel (not toggle)
-      let app_handle2 = app.handle().clone();
-      for hotkey in ["Alt+Cmd+Space", "Ctrl+Space", "Cmd+Shift+Space"] {
-        let app_handle2 = app_handle2.clone();
-        let _ = app_handle
-          .global_shortcut()
-          .on_shortcut(hotkey, move |_id, _shortcut, _event| {
-          log::info!("global hotkey {} triggered; focusing panel", hotkey);
-          if let Some(w) = app_handle2.get_webview_window("panel") {
-            let _ = w.show();
-            let _ = w.set_focus();
-            let _ = w.set_always_on_top(true);
-            let _ = app_handle2.emit("panel-should-expand", ());
-          }
-          });
-      }
-
-      // Handle Cmd+1 key to toggle collapsed state
-      let app_handle3 = app.handle().clone();
+      let _ = tray::set_tooltip(app_handle, tray::DEFAULT_TOOLTIP);
+      theme::restore_theme(app_handle);
+      autosave::start(app_handle);
 
-      let _ = app_handle
-        .global_shortcut()
-        .on_shortcut("Cmd+1", move |_id, _shortcut, _event| {
-          log::info!("Cmd+1 key pressed via global shortcut");
+      // Global hotkeys (show panel, Cmd+1 toggle) come from the stored config
+      hotkeys::register_from_config(app_handle);
 
-          // Verify panel window exists
-          if let Some(w) = app_handle3.get_webview_window("panel") {
-            log::info!("✓ Panel window found, emitting toggle-collapse event");
+      // Escape is handled in the panel only while it has focus
+      escape::track_focus(app_handle);
 
-            // Emit directly to the panel; fall back to window.emit if that fails
-            match app_handle3.emit_to("panel", "toggle-collapse", ()) {
-              Ok(_) => {
-                log::info!("✅ Event emitted successfully via emit_to()");
-              }
-              Err(e) => {
-                log::error!("❌ Failed to emit via emit_to(): {}", e);
-                match w.emit("toggle-collapse", ()) {
-                  Ok(_) => log::info!("✅ Event emitted via window.emit() fallback"),
-                  Err(e2) => log::error!("❌ Failed to emit via window.emit(): {}", e2),
-                }
-              }
-            }
+      hot_corner::load_from_store(app.handle());
+      hot_corner::start_watcher(app.handle().clone());
+      double_tap::load_from_store(app.handle());
+      double_tap::start_watcher(app.handle().clone());
+      monitors::start_watcher(app.handle().clone());
 
-            // Also try eval to directly call JavaScript
-            let _ = w.eval("console.log('🔥 DIRECT EVAL FROM RUST: Cmd+1 pressed!')");
-          } else {
-            log::error!("❌ Panel window not found! Cannot emit event.");
-          }
-        });
-
-      // Block ESC key from closing the window
-      let _ = app_handle
-        .global_shortcut()
-        .on_shortcut("Escape", move |_id, _shortcut, _event| {
-          log::info!("ESC key intercepted and blocked");
-          // Do nothing - this prevents ESC from closing the window
-        });
+      pin::restore_movable(app.handle());
+      title::restore_title(app.handle());
+      stacking::reapply(app.handle());
+      #[cfg(target_os = "macos")]
+      stacking::restore_fullscreen_visibility(app.handle());
 
       // macOS all-workspaces will be added later using appropriate APIs
       Ok(())
@@ -466,4 +719,46 @@ mod tests {
     assert_eq!(x, 110);
     assert_eq!(y, 50);
   }
+
+  #[test]
+  fn right_center_keeps_oversized_windows_on_screen() {
+    let pos = PhysicalPosition { x: 1920, y: 0 };
+    let monitor = PhysicalSize { width: 1280, height: 800 };
+    let right = |width, height, margin| {
+      let window = PhysicalSize { width, height };
+      let placement = Placement { anchor: Anchor::RightCenter, margin };
+      placement::calculate_anchor_position(pos, monitor, window, placement)
+    };
+
+    assert_eq!(right(400, 600, 40), (2760, 100));
+    assert_eq!(right(400, 600, 2000), (1920, 100));
+    // Wider and taller than the monitor: pinned to its top-left.
+    assert_eq!(right(1400, 900, 40), (1920, 0));
+  }
+
+  #[test]
+  fn legacy_position_entries_still_deserialize() {
+    let pos: WindowPos = serde_json::from_value(serde_json::json!({ "x": 10, "y": -20 })).unwrap();
+    assert_eq!((pos.x, pos.y), (10, -20));
+    assert_eq!(pos.width, None);
+    assert_eq!(pos.height, None);
+    assert_eq!(pos.monitor, None);
+    assert_eq!(pos.scale_factor, None);
+    assert_eq!(pos.fingerprint, None);
+    assert!(!pos.stale);
+  }
+
+  #[test]
+  fn corner_position_applies_margin_from_each_edge() {
+    use hot_corner::Corner;
+
+    let pos = PhysicalPosition { x: 0, y: 0 };
+    let monitor = PhysicalSize { width: 1920, height: 1080 };
+    let window = PhysicalSize { width: 420, height: 110 };
+
+    assert_eq!(calculate_corner_position(pos, monitor, window, 20, Corner::TopLeft), (20, 20));
+    assert_eq!(calculate_corner_position(pos, monitor, window, 20, Corner::TopRight), (1480, 20));
+    assert_eq!(calculate_corner_position(pos, monitor, window, 20, Corner::BottomLeft), (20, 950));
+    assert_eq!(calculate_corner_position(pos, monitor, window, 20, Corner::BottomRight), (1480, 950));
+  }
 }
@@ -0,0 +1,133 @@
+use tauri::AppHandle;
+
+use crate::{layouts, profiles, settings};
+
+// Stores without it predate versioning and count as schema 1.
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Brings the active profile's store up to `settings::SCHEMA_VERSION`, one
+/// step at a time. Runs after seeding so a fresh store goes through the same
+/// steps (as no-ops) and ends up stamped with the current version.
+pub fn run(app: &AppHandle) {
+  let Ok(store) = profiles::settings_store(app) else {
+    return;
+  };
+  let mut version = store
+    .get(SCHEMA_VERSION_KEY)
+    .and_then(|value| value.as_u64())
+    .unwrap_or(1) as u32;
+  if version >= settings::SCHEMA_VERSION {
+    return;
+  }
+
+  while version < settings::SCHEMA_VERSION {
+    let step = match version {
+      1 => layouts::migrate_legacy_keys(&store).map(|(migrated, dropped)| {
+        log::info!("migrated {} legacy layout keys ({} unreadable, dropped)", migrated, dropped);
+      }),
+      _ => Ok(()),
+    };
+    if let Err(e) = step {
+      log::warn!("settings migration from schema {} failed: {}", version, e);
+      return;
+    }
+    version += 1;
+    store.set(SCHEMA_VERSION_KEY, version);
+  }
+
+  if let Err(e) = store.save() {
+    log::warn!("failed to save migrated settings: {}", e);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use serde_json::{json, Value};
+
+  use crate::layouts::{self, LayoutEntry};
+  use crate::placement::Anchor;
+  use crate::sizing::ModeSize;
+
+  fn fixture(value: Value) -> Vec<(String, Value)> {
+    serde_json::from_value::<HashMap<String, Value>>(value).unwrap().into_iter().collect()
+  }
+
+  fn fold(entries: &[(String, Value)]) -> (layouts::Layout, usize, usize) {
+    layouts::fold_legacy(entries.iter().map(|(k, v)| (k, v)))
+  }
+
+  #[test]
+  fn gathers_every_layout_key_by_mode() {
+    let entries = fixture(json!({
+      "custom_position_expanded": {
+        "x": 10, "y": 20, "width": 800, "height": 600, "monitor": "DELL"
+      },
+      "placement_expanded": { "anchor": "center", "margin": 0 },
+      "mode_size_collapsed": { "width": 220, "height": 160 },
+      "auto_recenter": true,
+    }));
+    let (layout, migrated, dropped) = fold(&entries);
+    assert_eq!((migrated, dropped), (3, 0));
+
+    let expanded = &layout["expanded"];
+    let position = expanded.position.as_ref().unwrap();
+    assert_eq!((position.x, position.y, position.monitor.as_deref()), (10, 20, Some("DELL")));
+    assert_eq!(expanded.placement.unwrap().anchor, Anchor::Center);
+    assert_eq!(layout["collapsed"].size, Some(ModeSize { width: 220, height: 160 }));
+    assert!(!layout.contains_key("auto_recenter"));
+  }
+
+  #[test]
+  fn partial_legacy_entries_keep_what_they_have() {
+    // Pre-size-tracking positions only had x/y.
+    let entries = fixture(json!({ "custom_position_hovered": { "x": 5, "y": 6 } }));
+    let (layout, _, _) = fold(&entries);
+    let hovered = &layout["hovered"];
+    assert_eq!(hovered.position.as_ref().unwrap().width, None);
+    assert_eq!(hovered.placement, None);
+    assert_eq!(hovered.size, None);
+  }
+
+  #[test]
+  fn corrupt_legacy_values_are_dropped() {
+    let entries = fixture(json!({
+      "custom_position_expanded": "garbage",
+      "placement_expanded": { "anchor": "nowhere", "margin": 0 },
+      "mode_size_collapsed": { "width": 220, "height": 160 },
+    }));
+    let (layout, migrated, dropped) = fold(&entries);
+    assert_eq!((migrated, dropped), (1, 2));
+    assert!(!layout.contains_key("expanded"));
+    assert!(layout.contains_key("collapsed"));
+  }
+
+  #[test]
+  fn legacy_keys_override_an_existing_layout_object() {
+    let entries = fixture(json!({
+      "layout": {
+        "sidepanel_left": { "placement": { "anchor": "left-center", "margin": 40 } },
+        "hovered": { "placement": { "anchor": "top-center", "margin": 40 } },
+      },
+      "placement_hovered": { "anchor": "top-left", "margin": 8 },
+    }));
+    let (layout, _, _) = fold(&entries);
+    assert_eq!(layout["hovered"].placement.unwrap().margin, 8);
+    assert_eq!(layout["sidepanel_left"].placement.unwrap().anchor, Anchor::LeftCenter);
+  }
+
+  #[test]
+  fn reads_prefer_the_layout_object_over_leftover_legacy_keys() {
+    let entries = fixture(json!({
+      "layout": { "hovered": { "placement": { "anchor": "top-center", "margin": 40 } } },
+      "placement_hovered": { "anchor": "top-left", "margin": 8 },
+      "mode_size_hovered": { "width": 300, "height": 80 },
+    }));
+    let layout = layouts::resolve(entries.iter().map(|(k, v)| (k, v)));
+    let hovered: &LayoutEntry = &layout["hovered"];
+    assert_eq!(hovered.placement.unwrap().margin, 40);
+    // Fields the object doesn't have yet still come from the legacy key.
+    assert_eq!(hovered.size, Some(ModeSize { width: 300, height: 80 }));
+  }
+}
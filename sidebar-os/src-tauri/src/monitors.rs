@@ -0,0 +1,400 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Listener, Manager, Monitor, PhysicalPosition, PhysicalSize, WebviewWindow};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::journal;
+use crate::layouts;
+use crate::placement;
+use crate::profiles;
+use crate::settings;
+use crate::state::AppState;
+
+pub const AUTO_RECENTER_KEY: &str = "auto_recenter";
+pub const USE_WORK_AREA_KEY: &str = "use_work_area";
+pub const PINNED_MONITOR_KEY: &str = "pinned_monitor";
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+// A window showing less than this much of itself on every monitor counts
+// as stranded.
+const MIN_VISIBLE_PX: i32 = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct MonitorRect {
+  pub x: i32,
+  pub y: i32,
+  pub width: u32,
+  pub height: u32,
+}
+
+impl From<&Monitor> for MonitorRect {
+  fn from(monitor: &Monitor) -> Self {
+    MonitorRect {
+      x: monitor.position().x,
+      y: monitor.position().y,
+      width: monitor.size().width,
+      height: monitor.size().height,
+    }
+  }
+}
+
+impl MonitorRect {
+  /// The monitor's visible frame: without the menu bar and Dock on macOS
+  /// (`NSScreen.visibleFrame`) or the taskbar on Windows (`rcWork`).
+  pub fn work_area(monitor: &Monitor) -> Self {
+    let area = monitor.work_area();
+    MonitorRect {
+      x: area.position.x,
+      y: area.position.y,
+      width: area.size.width,
+      height: area.size.height,
+    }
+  }
+}
+
+/// Index of the monitor after `current`, going left to right (then top to
+/// bottom) and wrapping around. A window on no known monitor goes to the
+/// first one.
+pub fn next_monitor(monitors: &[MonitorRect], current: Option<MonitorRect>) -> Option<usize> {
+  let mut order: Vec<usize> = (0..monitors.len()).collect();
+  order.sort_by_key(|&i| (monitors[i].x, monitors[i].y));
+  match current.and_then(|c| order.iter().position(|&i| monitors[i] == c)) {
+    Some(at) => Some(order[(at + 1) % order.len()]),
+    None => order.first().copied(),
+  }
+}
+
+/// `full` with its top edge moved down to where `work_area` starts, e.g.
+/// below the macOS menu bar (taller on notched MacBooks). Only the top is
+/// trimmed; the Dock and taskbar are left to `use_work_area`.
+pub fn below_top_inset(full: MonitorRect, work_area: MonitorRect) -> MonitorRect {
+  let inset = (work_area.y - full.y).clamp(0, full.height as i32);
+  MonitorRect {
+    y: full.y + inset,
+    height: full.height - inset as u32,
+    ..full
+  }
+}
+
+/// Origin and size the positioning helpers lay the panel out in: the work
+/// area when `use_work_area` is on, else the full monitor minus any top
+/// inset like the menu bar.
+pub fn usable_area(
+  app: &AppHandle,
+  monitor: &Monitor,
+) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+  let use_work_area = profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(USE_WORK_AREA_KEY))
+    .and_then(|value| value.as_bool())
+    .unwrap_or(settings::DEFAULT_USE_WORK_AREA);
+  let work_area = MonitorRect::work_area(monitor);
+  let rect = if use_work_area {
+    work_area
+  } else {
+    below_top_inset(MonitorRect::from(monitor), work_area)
+  };
+  (
+    PhysicalPosition { x: rect.x, y: rect.y },
+    PhysicalSize { width: rect.width, height: rect.height },
+  )
+}
+
+/// Stable hash of the monitor layout (geometry plus scale factor).
+pub fn fingerprint(monitors: &[Monitor]) -> u64 {
+  let mut layout: Vec<(MonitorRect, u64)> = monitors
+    .iter()
+    .map(|m| (MonitorRect::from(m), m.scale_factor().to_bits()))
+    .collect();
+  layout.sort_by_key(|(rect, _)| (rect.x, rect.y));
+
+  let mut hasher = DefaultHasher::new();
+  layout.hash(&mut hasher);
+  hasher.finish()
+}
+
+pub fn is_offscreen(
+  window_position: PhysicalPosition<i32>,
+  window_size: PhysicalSize<u32>,
+  monitors: &[MonitorRect],
+) -> bool {
+  let left = window_position.x;
+  let top = window_position.y;
+  let right = left + window_size.width as i32;
+  let bottom = top + window_size.height as i32;
+
+  !monitors.iter().any(|m| {
+    let overlap_w = right.min(m.x + m.width as i32) - left.max(m.x);
+    let overlap_h = bottom.min(m.y + m.height as i32) - top.max(m.y);
+    overlap_w >= MIN_VISIBLE_PX.min(window_size.width as i32)
+      && overlap_h >= MIN_VISIBLE_PX.min(window_size.height as i32)
+  })
+}
+
+fn auto_recenter_enabled(app: &AppHandle) -> bool {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(AUTO_RECENTER_KEY))
+    .and_then(|value| value.as_bool())
+    .unwrap_or(settings::DEFAULT_AUTO_RECENTER)
+}
+
+/// Re-runs the last anchor on the primary monitor if the panel has ended up
+/// outside every connected display.
+fn recenter_if_offscreen(app: &AppHandle) {
+  if !auto_recenter_enabled(app) {
+    return;
+  }
+  let Some(window) = app.get_webview_window("panel") else {
+    return;
+  };
+  let (Ok(position), Ok(size), Ok(monitors)) = (
+    window.outer_position(),
+    window.outer_size(),
+    app.available_monitors(),
+  ) else {
+    return;
+  };
+
+  let rects: Vec<MonitorRect> = monitors.iter().map(MonitorRect::from).collect();
+  if !is_offscreen(position, size, &rects) {
+    return;
+  }
+
+  let Some(monitor) = pinned(app)
+    .or_else(|| app.primary_monitor().ok().flatten())
+    .or_else(|| monitors.into_iter().next())
+  else {
+    return;
+  };
+
+  match placement::apply_placement_on(&window, &monitor, placement::current(app)) {
+    Ok((x, y)) => {
+      log::info!("panel was off-screen; recentered at ({}, {})", x, y);
+      let _ = journal::emit(app, "panel-recentered", serde_json::json!({ "x": x, "y": y }));
+    }
+    Err(e) => log::warn!("failed to recenter off-screen panel: {}", e),
+  }
+}
+
+/// The monitor name a stored `pinned_monitor` value holds; blank counts as
+/// unset.
+pub fn pinned_name(value: Option<Value>) -> Option<String> {
+  let name = value?.as_str()?.trim().to_string();
+  (!name.is_empty()).then_some(name)
+}
+
+/// The pinned monitor, if one is set and connected.
+pub fn pinned(app: &AppHandle) -> Option<Monitor> {
+  let store = profiles::settings_store(app).ok()?;
+  let name = pinned_name(store.get(PINNED_MONITOR_KEY))?;
+  let monitor = app.available_monitors().ok()?.into_iter().find(|m| m.name() == Some(&name));
+  if monitor.is_none() {
+    log::debug!("pinned monitor {} isn't connected; ignoring the pin", name);
+  }
+  monitor
+}
+
+/// The monitor positioning works on: the pinned one when set and connected,
+/// else the one the panel is on. Use instead of `current_monitor`.
+pub fn panel_monitor(window: &WebviewWindow) -> Result<Option<Monitor>, String> {
+  match pinned(window.app_handle()) {
+    Some(monitor) => Ok(Some(monitor)),
+    None => window.current_monitor().map_err(|e| e.to_string()),
+  }
+}
+
+/// Watches for display changes: scale-change events on the panel plus a
+/// periodic comparison of the monitor layout.
+pub fn start_watcher(app: AppHandle) {
+  if let Some(window) = app.get_webview_window("panel") {
+    let handle = app.clone();
+    let _ = window.listen("tauri://scale-change", move |_event| {
+      log::debug!("scale change detected; checking panel placement");
+      record_fingerprint(&handle, layouts::current_fingerprint(&handle));
+      recenter_if_offscreen(&handle);
+    });
+  }
+
+  std::thread::spawn(move || {
+    let mut last = layouts::current_fingerprint(&app);
+    record_fingerprint(&app, last);
+    loop {
+      std::thread::sleep(POLL_INTERVAL);
+
+      let current = layouts::current_fingerprint(&app);
+      if current.is_some() && current != last {
+        log::info!("monitor configuration changed");
+        last = current;
+        record_fingerprint(&app, current);
+        layouts::mark_seen(&app);
+        recenter_if_offscreen(&app);
+      }
+    }
+  });
+}
+
+fn record_fingerprint(app: &AppHandle, fingerprint: Option<u64>) {
+  if fingerprint.is_some() {
+    *app.state::<AppState>().monitor_fingerprint.lock().unwrap() = fingerprint;
+  }
+}
+
+/// Compares a token from an earlier `monitors_changed_since` call with the
+/// current layout. Empty or unknown tokens count as changed.
+pub fn changed_since(token: &str, fingerprint: Option<u64>) -> (bool, String) {
+  let current = fingerprint.map(|f| format!("{:x}", f)).unwrap_or_default();
+  (token.is_empty() || token != current, current)
+}
+
+/// Whether the monitors changed since `token` was handed out, plus a token
+/// for the next call. Pass "" the first time.
+#[tauri::command]
+pub fn monitors_changed_since(app: AppHandle, token: String) -> Result<(bool, String), AppError> {
+  audit::run(app.clone(), "monitors_changed_since", move || {
+    let tracked = *app.state::<AppState>().monitor_fingerprint.lock().map_err(|e| e.to_string())?;
+    Ok(changed_since(&token, tracked.or_else(|| layouts::current_fingerprint(&app))))
+  })
+}
+
+/// Work area of the monitor at `monitor_index` in `available_monitors`, or
+/// of the primary monitor.
+#[tauri::command]
+pub fn get_work_area(app: AppHandle, monitor_index: Option<usize>) -> Result<MonitorRect, AppError> {
+  audit::run(app.clone(), "get_work_area", move || {
+    let monitor = match monitor_index {
+      Some(index) => app
+        .available_monitors()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .nth(index)
+        .ok_or(format!("No monitor at index {}", index))?,
+      None => app
+        .primary_monitor()
+        .map_err(|e| e.to_string())?
+        .ok_or(AppError::MonitorNotFound)?,
+    };
+    Ok(MonitorRect::work_area(&monitor))
+  })
+}
+
+/// Keeps the panel on the monitor named `name` (see `available_monitors`)
+/// wherever the cursor goes, and moves it there now; None lets it follow
+/// the cursor again. A pinned monitor that's unplugged is ignored until it
+/// comes back.
+#[tauri::command]
+pub fn set_pinned_monitor(app: AppHandle, name: Option<String>) -> CommandResult {
+  audit::run(app.clone(), "set_pinned_monitor", move || {
+    log::info!("set_pinned_monitor: name={:?}", name);
+
+    let store = profiles::settings_store(&app)?;
+    match pinned_name(name.map(Value::String)) {
+      Some(name) => {
+        let connected = app.available_monitors().map_err(|e| e.to_string())?;
+        if !connected.iter().any(|m| m.name() == Some(&name)) {
+          return Err(AppError::InvalidArgument(format!("No monitor named {}", name)));
+        }
+        store.set(PINNED_MONITOR_KEY, name);
+      }
+      None => {
+        store.delete(PINNED_MONITOR_KEY);
+      }
+    }
+    store.save().map_err(|e| e.to_string())?;
+
+    if let (Some(window), Some(monitor)) = (app.get_webview_window("panel"), pinned(&app)) {
+      placement::apply_placement_on(&window, &monitor, placement::current(&app))?;
+    }
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const MAIN: MonitorRect = MonitorRect { x: 0, y: 0, width: 1920, height: 1080 };
+  const EXTERNAL: MonitorRect = MonitorRect { x: 1920, y: 0, width: 2560, height: 1440 };
+  const WINDOW: PhysicalSize<u32> = PhysicalSize { width: 800, height: 600 };
+
+  #[test]
+  fn next_monitor_goes_left_to_right_and_wraps() {
+    let left = MonitorRect { x: -1440, y: 0, width: 1440, height: 900 };
+    let monitors = [EXTERNAL, MAIN, left];
+    assert_eq!(next_monitor(&monitors, Some(left)), Some(1));
+    assert_eq!(next_monitor(&monitors, Some(MAIN)), Some(0));
+    assert_eq!(next_monitor(&monitors, Some(EXTERNAL)), Some(2));
+    assert_eq!(next_monitor(&monitors, None), Some(2));
+    assert_eq!(next_monitor(&[], None), None);
+  }
+
+  #[test]
+  fn window_on_a_monitor_is_visible() {
+    let pos = PhysicalPosition { x: 100, y: 100 };
+    assert!(!is_offscreen(pos, WINDOW, &[MAIN]));
+  }
+
+  #[test]
+  fn window_left_on_unplugged_monitor_is_offscreen() {
+    let pos = PhysicalPosition { x: 2400, y: 200 };
+    assert!(!is_offscreen(pos, WINDOW, &[MAIN, EXTERNAL]));
+    assert!(is_offscreen(pos, WINDOW, &[MAIN]));
+  }
+
+  #[test]
+  fn tokens_change_with_the_layout() {
+    let (changed, token) = changed_since("", Some(0xabc));
+    assert!(changed);
+    assert_eq!(changed_since(&token, Some(0xabc)), (false, token.clone()));
+    assert!(changed_since(&token, Some(0xdef)).0);
+  }
+
+  #[test]
+  fn a_sliver_on_screen_still_counts_as_offscreen() {
+    let pos = PhysicalPosition { x: 1900, y: 100 };
+    assert!(is_offscreen(pos, WINDOW, &[MAIN]));
+
+    let pos = PhysicalPosition { x: 1800, y: 100 };
+    assert!(!is_offscreen(pos, WINDOW, &[MAIN]));
+  }
+
+  #[test]
+  fn top_center_lands_below_a_menu_bar_inset() {
+    let work_area = MonitorRect { y: 25, height: 1055, ..MAIN };
+    let area = below_top_inset(MAIN, work_area);
+    assert_eq!((area.y, area.height), (25, 1055));
+
+    let (_, y) = crate::calculate_top_center_position(
+      PhysicalPosition { x: area.x, y: area.y },
+      PhysicalSize { width: area.width, height: area.height },
+      PhysicalSize { width: 220, height: 160 },
+      40,
+      false,
+    );
+    assert_eq!(y, 65);
+  }
+
+  #[test]
+  fn no_top_inset_keeps_the_full_monitor() {
+    // A Dock at the bottom shrinks the work area but isn't a top inset.
+    let work_area = MonitorRect { height: 1000, ..MAIN };
+    assert_eq!(below_top_inset(MAIN, work_area), MAIN);
+  }
+
+  #[test]
+  fn blank_pins_count_as_unset() {
+    assert_eq!(pinned_name(Some(Value::from("DELL U2720Q"))), Some("DELL U2720Q".to_string()));
+    assert_eq!(pinned_name(Some(Value::from("  "))), None);
+    assert_eq!(pinned_name(Some(Value::Bool(true))), None);
+    assert_eq!(pinned_name(None), None);
+  }
+
+  #[test]
+  fn no_monitors_means_offscreen() {
+    assert!(is_offscreen(PhysicalPosition { x: 0, y: 0 }, WINDOW, &[]));
+  }
+}
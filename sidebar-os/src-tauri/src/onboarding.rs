@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::state::AppState;
+use crate::{journal, profiles};
+
+pub const ONBOARDING_KEY: &str = "onboarding";
+pub const HAS_RUN_BEFORE_KEY: &str = "has_run_before";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnboardingState {
+  #[serde(default)]
+  pub step: u32,
+  #[serde(default)]
+  pub completed: bool,
+}
+
+impl OnboardingState {
+  /// Moves to `step`. Steps only go forward (repeating the current one is
+  /// fine), and a completed onboarding stays completed until reset.
+  pub fn advance(self, step: u32) -> Result<Self, String> {
+    if self.completed {
+      return Err("Onboarding is already complete".to_string());
+    }
+    if step < self.step {
+      return Err(format!("Onboarding can't go back from step {} to {}", self.step, step));
+    }
+    Ok(OnboardingState { step, ..self })
+  }
+}
+
+pub fn load(app: &AppHandle) -> OnboardingState {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(ONBOARDING_KEY))
+    .and_then(|value| serde_json::from_value(value).ok())
+    .unwrap_or_default()
+}
+
+fn save(app: &AppHandle, state: OnboardingState) -> Result<(), String> {
+  let store = profiles::settings_store(app).map_err(|e| e.to_string())?;
+  store.set(ONBOARDING_KEY, serde_json::to_value(state).map_err(|e| e.to_string())?);
+  store.save().map_err(|e| e.to_string())
+}
+
+/// Sets `has_run_before`, returning true if this is the first launch.
+pub fn mark_launched(app: &AppHandle) -> bool {
+  let Ok(store) = profiles::settings_store(app) else {
+    return false;
+  };
+  let first_run = !store.get(HAS_RUN_BEFORE_KEY).and_then(|v| v.as_bool()).unwrap_or(false);
+  if first_run {
+    store.set(HAS_RUN_BEFORE_KEY, true);
+    if let Err(e) = store.save() {
+      log::warn!("could not save {}: {}", HAS_RUN_BEFORE_KEY, e);
+    }
+    if let Ok(mut flag) = app.state::<AppState>().first_run.lock() {
+      *flag = true;
+    }
+  }
+  first_run
+}
+
+/// Part of the frontend-ready handshake: announces a first launch and tells
+/// the webview where to resume if onboarding isn't done yet.
+pub fn emit_if_required(app: &AppHandle) {
+  if *app.state::<AppState>().first_run.lock().unwrap() {
+    let _ = journal::emit(app, "onboarding", serde_json::json!({ "first_run": true }));
+  }
+  let state = load(app);
+  if !state.completed {
+    let _ = journal::emit(app, "onboarding-required", serde_json::json!({ "step": state.step }));
+  }
+}
+
+#[tauri::command]
+pub fn get_onboarding_state(app: AppHandle) -> Result<OnboardingState, AppError> {
+  audit::run(app.clone(), "get_onboarding_state", move || {
+    Ok(load(&app))
+  })
+}
+
+#[tauri::command]
+pub fn set_onboarding_step(app: AppHandle, step: u32) -> Result<OnboardingState, AppError> {
+  audit::run(app.clone(), "set_onboarding_step", move || {
+    log::info!("set_onboarding_step: step={}", step);
+
+    let state = load(&app).advance(step)?;
+    save(&app, state)?;
+    Ok(state)
+  })
+}
+
+#[tauri::command]
+pub fn complete_onboarding(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "complete_onboarding", move || {
+    log::info!("complete_onboarding invoked");
+
+    let state = load(&app);
+    save(&app, OnboardingState { completed: true, ..state })?;
+    Ok(ok_status())
+  })
+}
+
+/// Starts onboarding over from step 0.
+#[tauri::command]
+pub fn reset_onboarding(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "reset_onboarding", move || {
+    log::info!("reset_onboarding invoked");
+
+    save(&app, OnboardingState::default())?;
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn steps_only_move_forward() {
+    let state = OnboardingState { step: 2, completed: false };
+    assert_eq!(state.advance(3).unwrap().step, 3);
+    assert_eq!(state.advance(2).unwrap().step, 2);
+    assert!(state.advance(1).is_err());
+  }
+
+  #[test]
+  fn completed_onboarding_needs_a_reset() {
+    let state = OnboardingState { step: 4, completed: true };
+    assert!(state.advance(5).is_err());
+    assert_eq!(OnboardingState::default().advance(0).unwrap().step, 0);
+  }
+}
@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::error::AppError;
+use crate::state::AppState;
+use crate::{journal, profiles};
+
+const PORTABLE_FLAG: &str = "--portable";
+const PORTABLE_MARKER: &str = "portable.marker";
+const PORTABLE_DATA_DIR: &str = "data";
+// Directory for the settings stores, e.g. an isolated test fixture.
+pub const SETTINGS_PATH_ENV: &str = "APP_SETTINGS_PATH";
+
+// Set by `set_store_dir`; for this run only.
+static STORE_DIR_OVERRIDE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Where persisted files go when running portable, or None for the normal
+/// OS locations. Portable mode is on with `--portable` or a `portable.marker`
+/// file next to the executable, and keeps everything in `./data` beside it.
+pub fn resolve_portable_root(
+  args: &[String],
+  exe_dir: &Path,
+  marker_exists: bool,
+) -> Option<PathBuf> {
+  let flagged = args.iter().skip(1).any(|arg| arg == PORTABLE_FLAG);
+  (flagged || marker_exists).then(|| exe_dir.join(PORTABLE_DATA_DIR))
+}
+
+/// Portable root for this process, detected once.
+pub fn portable_root() -> Option<&'static Path> {
+  static ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+  ROOT
+    .get_or_init(|| {
+      let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+      let args: Vec<String> = std::env::args().collect();
+      let marker_exists = exe_dir.join(PORTABLE_MARKER).exists();
+      let root = resolve_portable_root(&args, &exe_dir, marker_exists);
+      if let Some(root) = &root {
+        log::info!("portable mode: storing data in {}", root.display());
+      }
+      root
+    })
+    .as_deref()
+}
+
+/// `APP_SETTINGS_PATH`, read once. Empty counts as unset.
+fn env_store_dir() -> Option<&'static Path> {
+  static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+  DIR
+    .get_or_init(|| {
+      let dir = std::env::var_os(SETTINGS_PATH_ENV).filter(|v| !v.is_empty()).map(PathBuf::from);
+      if let Some(dir) = &dir {
+        log::info!("{} set: storing settings in {}", SETTINGS_PATH_ENV, dir.display());
+      }
+      dir
+    })
+    .as_deref()
+}
+
+/// Where the store files live: `set_store_dir`'s override, then
+/// `APP_SETTINGS_PATH`, then the portable root. None is the app data dir.
+pub fn resolve_store_dir(
+  override_dir: Option<&Path>,
+  env_dir: Option<&Path>,
+  portable: Option<&Path>,
+) -> Option<PathBuf> {
+  override_dir.or(env_dir).or(portable).map(Path::to_path_buf)
+}
+
+fn store_dir() -> Option<PathBuf> {
+  let override_dir = STORE_DIR_OVERRIDE.lock().unwrap().clone();
+  resolve_store_dir(override_dir.as_deref(), env_store_dir(), portable_root())
+}
+
+/// Path handed to the store plugin for `file`. Relative paths resolve into
+/// the app data dir as usual; with a store dir (see `resolve_store_dir`)
+/// they're made absolute.
+pub fn store_path(file: &str) -> PathBuf {
+  match store_dir() {
+    Some(dir) => dir.join(file),
+    None => PathBuf::from(file),
+  }
+}
+
+pub fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+  match portable_root() {
+    Some(root) => Ok(root.to_path_buf()),
+    None => app.path().app_data_dir().map_err(|e| e.to_string()),
+  }
+}
+
+pub fn log_dir(app: &AppHandle) -> Result<PathBuf, String> {
+  match portable_root() {
+    Some(root) => Ok(root.join("logs")),
+    None => app.path().app_log_dir().map_err(|e| e.to_string()),
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppPaths {
+  pub portable: bool,
+  pub data_dir: PathBuf,
+  pub settings_file: PathBuf,
+  pub log_dir: PathBuf,
+  pub screenshots_dir: PathBuf,
+}
+
+#[tauri::command]
+pub fn get_app_paths(app: AppHandle) -> Result<AppPaths, AppError> {
+  audit::run(app.clone(), "get_app_paths", move || {
+    let profile = app.state::<AppState>().active_profile.lock().map_err(|e| e.to_string())?.clone();
+    let settings_file =
+      tauri_plugin_store::resolve_store_path(&app, store_path(&profiles::store_file(&profile)))
+        .map_err(|e| e.to_string())?;
+    let data_dir = data_dir(&app)?;
+
+    Ok(AppPaths {
+      portable: portable_root().is_some(),
+      screenshots_dir: data_dir.join("screenshots"),
+      data_dir,
+      settings_file,
+      log_dir: log_dir(&app)?,
+    })
+  })
+}
+
+/// Directory the settings stores are read from right now.
+#[tauri::command]
+pub fn get_store_dir(app: AppHandle) -> Result<PathBuf, AppError> {
+  audit::run(app.clone(), "get_store_dir", move || {
+    match store_dir() {
+      Some(dir) => Ok(dir),
+      None => Ok(app.path().app_data_dir()?),
+    }
+  })
+}
+
+/// Re-opens every store under `path` (created if missing) and re-reads the
+/// settings from there. None goes back to the default location. Lasts until
+/// quit; use `APP_SETTINGS_PATH` to start somewhere else.
+#[tauri::command]
+pub fn set_store_dir(app: AppHandle, path: Option<PathBuf>) -> Result<PathBuf, AppError> {
+  audit::run(app.clone(), "set_store_dir", move || {
+    log::info!("set_store_dir: path={:?}", path);
+
+    if let Some(dir) = &path {
+      if !dir.is_absolute() {
+        return Err(AppError::InvalidArgument(format!(
+          "Store directory must be absolute: {}",
+          dir.display()
+        )));
+      }
+      std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    *STORE_DIR_OVERRIDE.lock().map_err(|e| e.to_string())? = path;
+
+    profiles::load_active(&app);
+    profiles::reload_settings(&app);
+    let dir = get_store_dir(app.clone())?;
+    let _ = journal::emit(&app, "store-dir-changed", serde_json::json!({ "path": dir }));
+    Ok(dir)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+  }
+
+  #[test]
+  fn flag_or_marker_enables_portable_mode() {
+    let exe_dir = Path::new("/media/usb/sidebar");
+    let expected = Some(exe_dir.join("data"));
+    assert_eq!(resolve_portable_root(&args(&["app", "--portable"]), exe_dir, false), expected);
+    assert_eq!(resolve_portable_root(&args(&["app"]), exe_dir, true), expected);
+  }
+
+  #[test]
+  fn normal_launch_is_not_portable() {
+    let exe_dir = Path::new("/Applications/sidebar");
+    assert_eq!(resolve_portable_root(&args(&["app"]), exe_dir, false), None);
+    assert_eq!(resolve_portable_root(&args(&["app", "--portable=no"]), exe_dir, false), None);
+  }
+
+  #[test]
+  fn store_dir_override_beats_env_and_portable() {
+    let (set, env, portable) = (Path::new("/a"), Path::new("/b"), Path::new("/c"));
+    assert_eq!(resolve_store_dir(Some(set), Some(env), Some(portable)), Some(set.into()));
+    assert_eq!(resolve_store_dir(None, Some(env), Some(portable)), Some(env.into()));
+    assert_eq!(resolve_store_dir(None, None, Some(portable)), Some(portable.into()));
+    assert_eq!(resolve_store_dir(None, None, None), None);
+  }
+
+  #[test]
+  fn program_name_is_not_a_flag() {
+    let exe_dir = Path::new("/tmp");
+    assert_eq!(resolve_portable_root(&args(&["--portable"]), exe_dir, false), None);
+  }
+}
@@ -0,0 +1,174 @@
+use tauri::{AppHandle, EventId, Listener, Manager, PhysicalPosition, Position};
+
+use crate::audit;
+use crate::autosave;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::journal;
+use crate::profiles;
+use crate::settings;
+use crate::stacking;
+use crate::state::AppState;
+
+const PINNED_POSITION_KEY: &str = "pinned_position";
+pub const WINDOW_MOVABLE_KEY: &str = "window_movable";
+
+/// Everything `pin_to_position` changed, so unpinning can put it back.
+#[derive(Debug)]
+pub struct Pin {
+  pub position: PhysicalPosition<i32>,
+  listener: EventId,
+  was_on_top: bool,
+  was_decorated: Option<bool>,
+}
+
+/// Pins the panel where it is: always on top, snapped back whenever it's
+/// moved, and optionally without decorations.
+#[tauri::command]
+pub fn pin_to_position(app: AppHandle, hide_decorations: Option<bool>) -> CommandResult {
+  audit::run(app.clone(), "pin_to_position", move || {
+    log::info!("pin_to_position: hide_decorations={:?}", hide_decorations);
+
+    let state = app.state::<AppState>();
+    if state.pin.lock().map_err(|e| e.to_string())?.is_some() {
+      return Err("Panel is already pinned".into());
+    }
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let position = window.outer_position().map_err(|e| e.to_string())?;
+    let was_on_top = stacking::is_always_on_top(&window).map_err(|e| e.to_string())?;
+    let was_decorated = match hide_decorations {
+      Some(true) => Some(window.is_decorated().map_err(|e| e.to_string())?),
+      _ => None,
+    };
+
+    // Window first, persisting last, so a failure leaves nothing half pinned.
+    let apply = || -> Result<(), AppError> {
+      autosave::suppress(&window);
+      window.set_position(Position::Physical(position)).map_err(|e| e.to_string())?;
+      stacking::set_always_on_top(&window, true).map_err(|e| e.to_string())?;
+      if was_decorated.is_some() {
+        window.set_decorations(false).map_err(|e| e.to_string())?;
+      }
+      let store = profiles::settings_store(&app)?;
+      store.set(PINNED_POSITION_KEY, serde_json::json!({ "x": position.x, "y": position.y }));
+      store.save().map_err(|e| e.to_string())?;
+      Ok(())
+    };
+
+    if let Err(e) = apply() {
+      log::warn!("pinning failed ({}); restoring the panel", e);
+      let _ = window.set_position(Position::Physical(position));
+      let _ = stacking::set_always_on_top(&window, was_on_top);
+      if let Some(decorated) = was_decorated {
+        let _ = window.set_decorations(decorated);
+      }
+      return Err(e);
+    }
+
+    let handle = app.clone();
+    let listener = window.listen("tauri://move", move |_event| {
+      let Some(window) = handle.get_webview_window("panel") else {
+        return;
+      };
+      let pinned = handle.state::<AppState>().pin.lock().unwrap().as_ref().map(|p| p.position);
+      if let (Some(pinned), Ok(current)) = (pinned, window.outer_position()) {
+        // Our own snap-back fires a move too; only react when it's off.
+        if current != pinned {
+          log::debug!("pinned panel moved to ({}, {}); snapping back", current.x, current.y);
+          autosave::suppress(&window);
+          let _ = window.set_position(Position::Physical(pinned));
+        }
+      }
+    });
+
+    *state.pin.lock().map_err(|e| e.to_string())? = Some(Pin {
+      position,
+      listener,
+      was_on_top,
+      was_decorated,
+    });
+
+    let _ = journal::emit(&app, "panel-pinned", serde_json::json!({ "x": position.x, "y": position.y }));
+    Ok(ok_status())
+  })
+}
+
+#[tauri::command]
+pub fn unpin_from_position(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "unpin_from_position", move || {
+    log::info!("unpin_from_position invoked");
+
+    let pin = app
+      .state::<AppState>()
+      .pin
+      .lock()
+      .map_err(|e| e.to_string())?
+      .take()
+      .ok_or("Panel is not pinned")?;
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    window.unlisten(pin.listener);
+    stacking::set_always_on_top(&window, pin.was_on_top).map_err(|e| e.to_string())?;
+    if let Some(decorated) = pin.was_decorated {
+      window.set_decorations(decorated).map_err(|e| e.to_string())?;
+    }
+
+    let store = profiles::settings_store(&app)?;
+    store.delete(PINNED_POSITION_KEY);
+    store.save().map_err(|e| e.to_string())?;
+
+    let _ = journal::emit(&app, "panel-unpinned", ());
+    Ok(ok_status())
+  })
+}
+
+#[cfg(target_os = "macos")]
+fn apply_movable(window: &tauri::WebviewWindow, movable: bool) -> Result<(), String> {
+  use objc2_app_kit::NSWindow;
+
+  let ns_window = window.ns_window().map_err(|e| e.to_string())?;
+  let ns_window = unsafe { &*(ns_window as *const NSWindow) };
+  ns_window.setMovable(movable);
+  Ok(())
+}
+
+// No native switch elsewhere; the frontend stops starting drags instead.
+#[cfg(not(target_os = "macos"))]
+fn apply_movable(_window: &tauri::WebviewWindow, _movable: bool) -> Result<(), String> {
+  Ok(())
+}
+
+/// Reads the persisted movable flag into state and applies it during setup.
+pub fn restore_movable(app: &AppHandle) {
+  let movable = profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(WINDOW_MOVABLE_KEY))
+    .and_then(|value| value.as_bool())
+    .unwrap_or(settings::DEFAULT_WINDOW_MOVABLE);
+  *app.state::<AppState>().window_movable.lock().unwrap() = movable;
+  if let Some(window) = app.get_webview_window("panel") {
+    if let Err(e) = apply_movable(&window, movable) {
+      log::warn!("could not restore window movable flag: {}", e);
+    }
+  }
+}
+
+/// Locks or unlocks dragging the panel around. Together with pinning this
+/// keeps the panel from being moved by the user at all.
+#[tauri::command]
+pub fn set_window_movable(app: AppHandle, movable: bool) -> CommandResult {
+  audit::run(app.clone(), "set_window_movable", move || {
+    log::info!("set_window_movable: movable={}", movable);
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    apply_movable(&window, movable)?;
+
+    let store = profiles::settings_store(&app)?;
+    store.set(WINDOW_MOVABLE_KEY, movable);
+    store.save().map_err(|e| e.to_string())?;
+
+    *app.state::<AppState>().window_movable.lock().map_err(|e| e.to_string())? = movable;
+    let _ = journal::emit(&app, "window-movable-changed", serde_json::json!({ "movable": movable }));
+    Ok(ok_status())
+  })
+}
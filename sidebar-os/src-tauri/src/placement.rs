@@ -0,0 +1,325 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, Position, WebviewWindow};
+
+use crate::audit;
+use crate::autosave;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::history;
+use crate::hot_corner::Corner;
+use crate::layouts;
+use crate::monitors;
+use crate::state::AppState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Anchor {
+  Center,
+  TopCenter,
+  RightCenter,
+  LeftCenter,
+  TopLeft,
+  TopRight,
+  BottomLeft,
+  BottomRight,
+}
+
+impl Anchor {
+  pub fn parse(value: &str) -> Option<Anchor> {
+    serde_json::from_value(serde_json::Value::String(value.to_lowercase())).ok()
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Placement {
+  pub anchor: Anchor,
+  pub margin: i32,
+}
+
+/// Where each mode goes when nothing is stored, matching what the frontend
+/// has always done for it.
+pub fn default_placement(mode: &str) -> Placement {
+  match mode {
+    "hovered" => Placement { anchor: Anchor::TopCenter, margin: 40 },
+    "sidepanel_right" => Placement { anchor: Anchor::RightCenter, margin: 40 },
+    "sidepanel_left" => Placement { anchor: Anchor::LeftCenter, margin: 40 },
+    _ => Placement { anchor: Anchor::Center, margin: 0 },
+  }
+}
+
+pub fn calculate_anchor_position(
+  monitor_position: PhysicalPosition<i32>,
+  monitor_size: PhysicalSize<u32>,
+  window_size: PhysicalSize<u32>,
+  placement: Placement,
+) -> (i32, i32) {
+  let available_width = monitor_size.width as i32 - window_size.width as i32;
+  let available_height = monitor_size.height as i32 - window_size.height as i32;
+  let min_x = monitor_position.x;
+  let max_x = monitor_position.x + available_width.max(0);
+  let min_y = monitor_position.y;
+  let max_y = monitor_position.y + available_height.max(0);
+  let m = placement.margin;
+
+  let corner = |corner| {
+    crate::calculate_corner_position(monitor_position, monitor_size, window_size, m, corner)
+  };
+
+  match placement.anchor {
+    Anchor::TopCenter => {
+      crate::calculate_top_center_position(monitor_position, monitor_size, window_size, m, false)
+    }
+    Anchor::Center => (
+      (monitor_position.x + available_width / 2).clamp(min_x, max_x),
+      (monitor_position.y + available_height / 2).clamp(min_y, max_y),
+    ),
+    Anchor::RightCenter => (
+      (monitor_position.x + available_width - m).clamp(min_x, max_x),
+      (monitor_position.y + available_height / 2).clamp(min_y, max_y),
+    ),
+    Anchor::LeftCenter => (
+      (monitor_position.x + m).clamp(min_x, max_x),
+      (monitor_position.y + available_height / 2).clamp(min_y, max_y),
+    ),
+    Anchor::TopLeft => corner(Corner::TopLeft),
+    Anchor::TopRight => corner(Corner::TopRight),
+    Anchor::BottomLeft => corner(Corner::BottomLeft),
+    Anchor::BottomRight => corner(Corner::BottomRight),
+  }
+}
+
+pub fn load_placement(app: &AppHandle, mode: &str) -> Placement {
+  layouts::load_entry(app, mode)
+    .placement
+    .unwrap_or_else(|| default_placement(mode))
+}
+
+/// Records the anchor last applied to the panel so it can be re-run later,
+/// e.g. after a monitor disappears.
+pub fn remember(app: &AppHandle, placement: Placement) {
+  if let Ok(mut last) = app.state::<AppState>().last_placement.lock() {
+    *last = Some(placement);
+  }
+}
+
+/// The anchor the panel is currently meant to sit at: the last one applied,
+/// else the current mode's placement.
+pub fn current(app: &AppHandle) -> Placement {
+  let state = app.state::<AppState>();
+  let last = *state.last_placement.lock().unwrap();
+  last.unwrap_or_else(|| {
+    let mode = state.current_mode.lock().unwrap().clone();
+    load_placement(app, &mode)
+  })
+}
+
+/// Moves the window to `placement` on `monitor`.
+pub fn apply_placement_on(
+  window: &WebviewWindow,
+  monitor: &Monitor,
+  placement: Placement,
+) -> Result<(i32, i32), String> {
+  let window_size = window.outer_size().map_err(|e| e.to_string())?;
+  let (origin, size) = monitors::usable_area(window.app_handle(), monitor);
+
+  let (x, y) = calculate_anchor_position(origin, size, window_size, placement);
+  autosave::suppress(window);
+  window
+    .set_position(Position::Physical(PhysicalPosition { x, y }))
+    .map_err(|e| e.to_string())?;
+  remember(window.app_handle(), placement);
+
+  log::debug!("applied placement {:?} at ({}, {})", placement, x, y);
+  Ok((x, y))
+}
+
+/// Moves the window to `placement` on its current (or pinned) monitor, or
+/// the primary one when the window isn't on any monitor.
+pub fn apply_placement(window: &WebviewWindow, placement: Placement) -> Result<(i32, i32), String> {
+  let monitor = match monitors::panel_monitor(window)? {
+    Some(monitor) => monitor,
+    None => window
+      .primary_monitor()
+      .map_err(|e| e.to_string())?
+      .ok_or(AppError::MonitorNotFound)?,
+  };
+  apply_placement_on(window, &monitor, placement)
+}
+
+#[tauri::command]
+pub fn set_mode_placement(
+  app: AppHandle,
+  mode: String,
+  anchor: String,
+  margin: Option<i32>,
+) -> CommandResult {
+  audit::run(app.clone(), "set_mode_placement", move || {
+    log::info!("set_mode_placement: mode={}, anchor={}, margin={:?}", mode, anchor, margin);
+
+    let anchor = Anchor::parse(&anchor)
+      .ok_or_else(|| AppError::InvalidArgument(format!("Unknown anchor: {}", anchor)))?;
+    let placement = Placement {
+      anchor,
+      margin: margin.unwrap_or(default_placement(&mode).margin),
+    };
+
+    layouts::update_entry(&app, &mode, |entry| entry.placement = Some(placement))?;
+    Ok(ok_status())
+  })
+}
+
+/// Where `anchor` would put the panel, without moving it. `monitor_index`
+/// indexes `available_monitors`; None means the panel's current (or pinned)
+/// monitor.
+#[tauri::command]
+pub fn preview_position(
+  app: AppHandle,
+  anchor: String,
+  margin: Option<i32>,
+  monitor_index: Option<usize>,
+) -> Result<(i32, i32), AppError> {
+  audit::run(app.clone(), "preview_position", move || {
+    let anchor = Anchor::parse(&anchor).ok_or(format!("Unknown anchor: {}", anchor))?;
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+
+    let monitor = match monitor_index {
+      Some(index) => window
+        .available_monitors()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .nth(index)
+        .ok_or(format!("No monitor at index {}", index))?,
+      None => monitors::panel_monitor(&window)?.ok_or(AppError::MonitorNotFound)?,
+    };
+    let window_size = window.outer_size().map_err(|e| e.to_string())?;
+
+    let placement = Placement {
+      anchor,
+      margin: margin.unwrap_or(0),
+    };
+    let (origin, size) = monitors::usable_area(&app, &monitor);
+    Ok(calculate_anchor_position(origin, size, window_size, placement))
+  })
+}
+
+// Anchors `best_fit` tries, most preferred first, and the margin it wants
+// around the panel.
+const FIT_ORDER: &[Anchor] = &[
+  Anchor::RightCenter,
+  Anchor::TopCenter,
+  Anchor::LeftCenter,
+  Anchor::TopRight,
+  Anchor::TopLeft,
+  Anchor::BottomRight,
+  Anchor::BottomLeft,
+  Anchor::Center,
+];
+const FIT_MARGIN: i32 = 40;
+
+/// Smallest gap between the window placed at `placement` and the edges of
+/// an `area`-sized monitor; 0 when it doesn't fit.
+pub fn fit_score(area: PhysicalSize<u32>, window: PhysicalSize<u32>, placement: Placement) -> i32 {
+  // The top-center and corner math can't take a window bigger than the area.
+  let window = PhysicalSize {
+    width: window.width.min(area.width),
+    height: window.height.min(area.height),
+  };
+  let (x, y) = calculate_anchor_position(PhysicalPosition { x: 0, y: 0 }, area, window, placement);
+  let right = area.width as i32 - (x + window.width as i32);
+  let bottom = area.height as i32 - (y + window.height as i32);
+  x.min(y).min(right).min(bottom)
+}
+
+/// The first anchor in `FIT_ORDER` that leaves `FIT_MARGIN` on every side,
+/// else the one leaving the most room.
+pub fn best_fit(area: PhysicalSize<u32>, window: PhysicalSize<u32>) -> Anchor {
+  let scored = FIT_ORDER
+    .iter()
+    .map(|&anchor| (anchor, fit_score(area, window, Placement { anchor, margin: FIT_MARGIN })));
+  let mut best = (Anchor::Center, i32::MIN);
+  for (anchor, score) in scored {
+    if score >= FIT_MARGIN {
+      return anchor;
+    }
+    if score > best.1 {
+      best = (anchor, score);
+    }
+  }
+  best.0
+}
+
+/// The anchor the panel fits best at on its monitor's work area (see
+/// `best_fit`), by name. With `apply`, also moves the panel there.
+#[tauri::command]
+pub fn best_fit_anchor(app: AppHandle, apply: Option<bool>) -> Result<String, AppError> {
+  audit::run(app.clone(), "best_fit_anchor", move || {
+    log::info!("best_fit_anchor: apply={:?}", apply);
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let monitor = monitors::panel_monitor(&window)?.ok_or(AppError::MonitorNotFound)?;
+    let window_size = window.outer_size().map_err(|e| e.to_string())?;
+    let area = monitors::MonitorRect::work_area(&monitor);
+
+    let anchor = best_fit(PhysicalSize { width: area.width, height: area.height }, window_size);
+    if apply.unwrap_or(false) {
+      history::record(&app, &window);
+      apply_placement_on(&window, &monitor, Placement { anchor, margin: FIT_MARGIN })?;
+    }
+
+    let name = serde_json::to_value(anchor)?;
+    Ok(name.as_str().unwrap_or_default().to_string())
+  })
+}
+
+#[tauri::command]
+pub fn get_mode_placement(app: AppHandle, mode: String) -> Result<Placement, AppError> {
+  audit::run(app.clone(), "get_mode_placement", move || {
+    Ok(load_placement(&app, &mode))
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const ORIGIN: PhysicalPosition<i32> = PhysicalPosition { x: 0, y: 0 };
+  const MONITOR: PhysicalSize<u32> = PhysicalSize { width: 1920, height: 1080 };
+  const WINDOW: PhysicalSize<u32> = PhysicalSize { width: 420, height: 800 };
+
+  #[test]
+  fn parses_anchor_names() {
+    assert_eq!(Anchor::parse("right-center"), Some(Anchor::RightCenter));
+    assert_eq!(Anchor::parse("Top-Center"), Some(Anchor::TopCenter));
+    assert_eq!(Anchor::parse("somewhere"), None);
+  }
+
+  #[test]
+  fn defaults_follow_existing_frontend_layout() {
+    assert_eq!(default_placement("hovered").anchor, Anchor::TopCenter);
+    assert_eq!(default_placement("sidepanel_right").anchor, Anchor::RightCenter);
+    assert_eq!(default_placement("sidepanel_left").anchor, Anchor::LeftCenter);
+    assert_eq!(default_placement("expanded").anchor, Anchor::Center);
+    assert_eq!(default_placement("collapsed").anchor, Anchor::Center);
+  }
+
+  #[test]
+  fn side_anchors_match_the_position_commands() {
+    let right = Placement { anchor: Anchor::RightCenter, margin: 40 };
+    let left = Placement { anchor: Anchor::LeftCenter, margin: 40 };
+
+    assert_eq!(calculate_anchor_position(ORIGIN, MONITOR, WINDOW, right), (1460, 140));
+    assert_eq!(calculate_anchor_position(ORIGIN, MONITOR, WINDOW, left), (40, 140));
+  }
+
+  #[test]
+  fn center_ignores_margin() {
+    let center = Placement { anchor: Anchor::Center, margin: 99 };
+    assert_eq!(calculate_anchor_position(ORIGIN, MONITOR, WINDOW, center), (750, 140));
+  }
+
+  #[test]
+  fn best_fit_prefers_right_center_then_the_roomiest_anchor() {
+    assert_eq!(best_fit(MONITOR, WINDOW), Anchor::RightCenter);
+    assert_eq!(best_fit(MONITOR, PhysicalSize { width: 1900, height: 400 }), Anchor::TopCenter);
+    assert_eq!(best_fit(MONITOR, PhysicalSize { width: 3000, height: 2000 }), Anchor::RightCenter);
+  }
+}
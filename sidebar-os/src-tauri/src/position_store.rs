@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_store::Store;
+
+use crate::profiles;
+use crate::state::AppState;
+
+/// The parts of the settings store the position commands use, so they can
+/// run against an in-memory store in tests.
+pub trait PositionStore: Send + Sync {
+  fn get(&self, key: &str) -> Option<Value>;
+  fn set(&self, key: &str, value: Value);
+  fn delete(&self, key: &str) -> bool;
+  fn has(&self, key: &str) -> bool;
+  fn keys(&self) -> Vec<String>;
+  fn save(&self) -> Result<(), String>;
+}
+
+impl PositionStore for Store<Wry> {
+  fn get(&self, key: &str) -> Option<Value> {
+    Store::get(self, key)
+  }
+
+  fn set(&self, key: &str, value: Value) {
+    Store::set(self, key, value)
+  }
+
+  fn delete(&self, key: &str) -> bool {
+    Store::delete(self, key)
+  }
+
+  fn has(&self, key: &str) -> bool {
+    Store::has(self, key)
+  }
+
+  fn keys(&self) -> Vec<String> {
+    Store::keys(self)
+  }
+
+  fn save(&self) -> Result<(), String> {
+    Store::save(self).map_err(|e| e.to_string())
+  }
+}
+
+impl<T: PositionStore + ?Sized> PositionStore for Arc<T> {
+  fn get(&self, key: &str) -> Option<Value> {
+    (**self).get(key)
+  }
+
+  fn set(&self, key: &str, value: Value) {
+    (**self).set(key, value)
+  }
+
+  fn delete(&self, key: &str) -> bool {
+    (**self).delete(key)
+  }
+
+  fn has(&self, key: &str) -> bool {
+    (**self).has(key)
+  }
+
+  fn keys(&self) -> Vec<String> {
+    (**self).keys()
+  }
+
+  fn save(&self) -> Result<(), String> {
+    (**self).save()
+  }
+}
+
+/// The store the position commands read and write: `AppState`'s
+/// `position_store` if one was put there, else the active profile's
+/// settings store.
+pub fn resolve(app: &AppHandle) -> Result<Arc<dyn PositionStore>, String> {
+  if let Some(store) = app.state::<AppState>().position_store.lock().unwrap().clone() {
+    return Ok(store);
+  }
+  let store: Arc<dyn PositionStore> = profiles::settings_store(app).map_err(|e| e.to_string())?;
+  Ok(store)
+}
+
+/// A `PositionStore` that only lives in memory.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStore(std::sync::Mutex<std::collections::BTreeMap<String, Value>>);
+
+#[cfg(test)]
+impl PositionStore for MemoryStore {
+  fn get(&self, key: &str) -> Option<Value> {
+    self.0.lock().unwrap().get(key).cloned()
+  }
+
+  fn set(&self, key: &str, value: Value) {
+    self.0.lock().unwrap().insert(key.to_string(), value);
+  }
+
+  fn delete(&self, key: &str) -> bool {
+    self.0.lock().unwrap().remove(key).is_some()
+  }
+
+  fn has(&self, key: &str) -> bool {
+    self.0.lock().unwrap().contains_key(key)
+  }
+
+  fn keys(&self) -> Vec<String> {
+    self.0.lock().unwrap().keys().cloned().collect()
+  }
+
+  fn save(&self) -> Result<(), String> {
+    Ok(())
+  }
+}
@@ -0,0 +1,248 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::state::AppState;
+use crate::{controller, hot_corner, hotkeys, journal, migrations, paths, settings, theme, tray};
+
+pub const DEFAULT_PROFILE: &str = "default";
+// Small root store that only knows which profiles exist and which is active.
+const ROOT_STORE: &str = "profiles.json";
+const ACTIVE_KEY: &str = "active_profile";
+const PROFILES_KEY: &str = "profiles";
+const MAX_NAME_LEN: usize = 32;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileInfo {
+  pub name: String,
+  pub active: bool,
+}
+
+/// The default profile keeps using settings.json so existing installs carry over.
+pub fn store_file(profile: &str) -> String {
+  if profile == DEFAULT_PROFILE {
+    "settings.json".to_string()
+  } else {
+    format!("settings.{}.json", profile)
+  }
+}
+
+pub fn validate_name(name: &str) -> Result<(), String> {
+  if name.is_empty() || name.len() > MAX_NAME_LEN {
+    return Err(format!("Profile name must be 1-{} characters", MAX_NAME_LEN));
+  }
+  if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+    return Err(format!("Invalid profile name: {}", name));
+  }
+  Ok(())
+}
+
+/// Store backing the active profile. Every settings read/write goes through here.
+pub fn settings_store(app: &AppHandle) -> tauri_plugin_store::Result<Arc<Store<Wry>>> {
+  let profile = app.state::<AppState>().active_profile.lock().unwrap().clone();
+  app.store(paths::store_path(&store_file(&profile)))
+}
+
+fn known_profiles(app: &AppHandle) -> Vec<String> {
+  let mut names: Vec<String> = app
+    .store(paths::store_path(ROOT_STORE))
+    .ok()
+    .and_then(|store| store.get(PROFILES_KEY))
+    .and_then(|value| serde_json::from_value(value).ok())
+    .unwrap_or_default();
+  if !names.iter().any(|n| n == DEFAULT_PROFILE) {
+    names.insert(0, DEFAULT_PROFILE.to_string());
+  }
+  names
+}
+
+fn write_root(app: &AppHandle, active: &str, profiles: &[String]) -> Result<(), String> {
+  let root = app.store(paths::store_path(ROOT_STORE)).map_err(|e| e.to_string())?;
+  root.set(ACTIVE_KEY, active);
+  root.set(PROFILES_KEY, serde_json::to_value(profiles).map_err(|e| e.to_string())?);
+  root.save().map_err(|e| e.to_string())
+}
+
+/// Restores the active profile name during setup, before anything reads settings.
+pub fn load_active(app: &AppHandle) {
+  let stored = app
+    .store(paths::store_path(ROOT_STORE))
+    .ok()
+    .and_then(|store| store.get(ACTIVE_KEY))
+    .and_then(|value| value.as_str().map(str::to_string));
+
+  let active = match stored {
+    Some(name) if known_profiles(app).contains(&name) => name,
+    Some(name) => {
+      log::warn!("active profile {:?} no longer exists; using default", name);
+      DEFAULT_PROFILE.to_string()
+    }
+    None => DEFAULT_PROFILE.to_string(),
+  };
+
+  log::info!("using settings profile {:?}", active);
+  *app.state::<AppState>().active_profile.lock().unwrap() = active;
+}
+
+/// Re-reads everything that was read from the previous store, after the
+/// profile or the store directory changed.
+pub fn reload_settings(app: &AppHandle) {
+  settings::seed_defaults(app);
+  migrations::run(app);
+  hotkeys::reregister(app);
+  hot_corner::load_from_store(app);
+  controller::reapply_layout(app);
+  theme::reapply(app);
+  tray::refresh_menu(app);
+}
+
+fn activate(app: &AppHandle, name: &str) -> Result<(), String> {
+  write_root(app, name, &known_profiles(app))?;
+  *app.state::<AppState>().active_profile.lock().map_err(|e| e.to_string())? = name.to_string();
+  reload_settings(app);
+
+  let _ = journal::emit(app, "profile-changed", serde_json::json!({ "profile": name }));
+  Ok(())
+}
+
+fn remove_store_file(app: &AppHandle, file: &str) {
+  let file = paths::store_path(file);
+  if let Ok(store) = app.store(&file) {
+    store.clear();
+    store.close_resource();
+  }
+  if let Ok(path) = tauri_plugin_store::resolve_store_path(app, &file) {
+    if let Err(e) = std::fs::remove_file(&path) {
+      if e.kind() != std::io::ErrorKind::NotFound {
+        log::warn!("could not remove {}: {}", path.display(), e);
+      }
+    }
+  }
+}
+
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> Result<Vec<ProfileInfo>, AppError> {
+  audit::run(app.clone(), "list_profiles", move || {
+    let active = app.state::<AppState>().active_profile.lock().map_err(|e| e.to_string())?.clone();
+    Ok(
+      known_profiles(&app)
+        .into_iter()
+        .map(|name| ProfileInfo {
+          active: name == active,
+          name,
+        })
+        .collect(),
+    )
+  })
+}
+
+/// Creates a profile, either as a copy of the active one or empty so every
+/// setting falls back to its default.
+#[tauri::command]
+pub fn create_profile(
+  app: AppHandle,
+  name: String,
+  copy_from_current: Option<bool>,
+) -> CommandResult {
+  audit::run(app.clone(), "create_profile", move || {
+    log::info!("create_profile: name={}, copy_from_current={:?}", name, copy_from_current);
+
+    validate_name(&name).map_err(AppError::InvalidArgument)?;
+    let mut profiles = known_profiles(&app);
+    if profiles.contains(&name) {
+      return Err(AppError::InvalidArgument(format!("Profile already exists: {}", name)));
+    }
+
+    let file = store_file(&name);
+    let populate = || -> Result<(), String> {
+      let store = app.store(paths::store_path(&file)).map_err(|e| e.to_string())?;
+      // A leftover file from an interrupted create must not leak into the new profile.
+      store.clear();
+      if copy_from_current.unwrap_or(true) {
+        let current = settings_store(&app).map_err(|e| e.to_string())?;
+        for (key, value) in current.entries() {
+          store.set(key, value);
+        }
+      }
+      store.save().map_err(|e| e.to_string())
+    };
+
+    if let Err(e) = populate() {
+      remove_store_file(&app, &file);
+      return Err(AppError::StoreError(e));
+    }
+
+    profiles.push(name.clone());
+    let active = app.state::<AppState>().active_profile.lock().map_err(|e| e.to_string())?.clone();
+    if let Err(e) = write_root(&app, &active, &profiles) {
+      remove_store_file(&app, &file);
+      return Err(AppError::StoreError(e));
+    }
+
+    Ok(ok_status())
+  })
+}
+
+#[tauri::command]
+pub fn switch_profile(app: AppHandle, name: String) -> CommandResult {
+  audit::run(app.clone(), "switch_profile", move || {
+    log::info!("switch_profile: name={}", name);
+
+    if !known_profiles(&app).contains(&name) {
+      return Err(AppError::InvalidArgument(format!("Unknown profile: {}", name)));
+    }
+    activate(&app, &name)?;
+    Ok(ok_status())
+  })
+}
+
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, name: String) -> CommandResult {
+  audit::run(app.clone(), "delete_profile", move || {
+    log::info!("delete_profile: name={}", name);
+
+    if name == DEFAULT_PROFILE {
+      return Err(AppError::InvalidArgument("The default profile cannot be deleted".to_string()));
+    }
+    if !known_profiles(&app).contains(&name) {
+      return Err(AppError::InvalidArgument(format!("Unknown profile: {}", name)));
+    }
+
+    let active = app.state::<AppState>().active_profile.lock().map_err(|e| e.to_string())?.clone();
+    if active == name {
+      activate(&app, DEFAULT_PROFILE)?;
+    }
+
+    let remaining: Vec<String> = known_profiles(&app).into_iter().filter(|n| n != &name).collect();
+    let active = app.state::<AppState>().active_profile.lock().map_err(|e| e.to_string())?.clone();
+    write_root(&app, &active, &remaining)?;
+    remove_store_file(&app, &store_file(&name));
+
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn default_profile_keeps_legacy_file() {
+    assert_eq!(store_file(DEFAULT_PROFILE), "settings.json");
+    assert_eq!(store_file("work"), "settings.work.json");
+  }
+
+  #[test]
+  fn rejects_unsafe_profile_names() {
+    assert!(validate_name("work").is_ok());
+    assert!(validate_name("home_2-b").is_ok());
+    assert!(validate_name("").is_err());
+    assert!(validate_name("../evil").is_err());
+    assert!(validate_name("with space").is_err());
+    assert!(validate_name(&"x".repeat(MAX_NAME_LEN + 1)).is_err());
+  }
+}
@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::hotkeys;
+use crate::journal;
+use crate::profiles;
+use crate::state::AppState;
+
+pub const SKIP_CONFIRMATION_KEY: &str = "skip_quit_confirmation";
+// How long a quit request waits for the second press or `confirm_quit`.
+pub const CONFIRM_WINDOW: Duration = Duration::from_secs(5);
+// Quits like any Mac app's Cmd+Q, but only while the panel has focus.
+pub const QUIT_ACCELERATOR_KEY: &str = "quit_accelerator";
+
+fn skips_confirmation(app: &AppHandle) -> bool {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(SKIP_CONFIRMATION_KEY))
+    .and_then(|value| value.as_bool())
+    .unwrap_or(crate::settings::DEFAULT_SKIP_QUIT_CONFIRMATION)
+}
+
+/// Flushes the settings store, drops the global shortcuts and exits. Shared
+/// by the tray's Quit item and a confirmed quit request.
+pub fn shutdown(app: &AppHandle) {
+  log::info!("shutting down");
+  match profiles::settings_store(app) {
+    Ok(store) => {
+      if let Err(e) = store.save() {
+        log::warn!("could not flush settings before quitting: {}", e);
+      }
+    }
+    Err(e) => log::warn!("could not open settings before quitting: {}", e),
+  }
+  if let Err(e) = app.global_shortcut().unregister_all() {
+    log::warn!("could not unregister shortcuts before quitting: {}", e);
+  }
+  app.exit(0);
+}
+
+/// Clears `pending` if it holds `generation` (any request when None).
+/// Whoever gets here first (the confirmation or the timeout) wins.
+pub fn take_if(pending: &mut Option<u64>, generation: Option<u64>) -> bool {
+  match *pending {
+    Some(current) if generation.is_none_or(|g| g == current) => {
+      *pending = None;
+      true
+    }
+    _ => false,
+  }
+}
+
+fn take_pending(app: &AppHandle, generation: Option<u64>) -> bool {
+  take_if(&mut app.state::<AppState>().pending_quit.lock().unwrap(), generation)
+}
+
+/// Quit-app hotkey: the first press emits `quit-requested` so the frontend
+/// can ask, and waits `CONFIRM_WINDOW` for a second press, `confirm_quit` or
+/// `quit_app`, emitting `quit-cancelled` if none comes. Quits straight away
+/// with `skip_quit_confirmation`. Runs off the shortcut handler's thread,
+/// since shutting down unregisters shortcuts.
+pub fn request(app: &AppHandle) {
+  let app = app.clone();
+  std::thread::spawn(move || {
+    if take_pending(&app, None) {
+      log::info!("quit confirmed by a second press");
+      shutdown(&app);
+      return;
+    }
+    if skips_confirmation(&app) {
+      shutdown(&app);
+      return;
+    }
+
+    let state = app.state::<AppState>();
+    let generation = {
+      let mut next = state.next_quit_request.lock().unwrap();
+      *next += 1;
+      *next
+    };
+    *state.pending_quit.lock().unwrap() = Some(generation);
+    let timeout_ms = CONFIRM_WINDOW.as_millis() as u64;
+    let _ = journal::emit_to(
+      &app,
+      "panel",
+      "quit-requested",
+      serde_json::json!({ "timeout_ms": timeout_ms }),
+    );
+
+    std::thread::sleep(CONFIRM_WINDOW);
+    if take_pending(&app, Some(generation)) {
+      log::info!("quit request not confirmed; cancelling");
+      let _ = journal::emit_to(&app, "panel", "quit-cancelled", ());
+    }
+  });
+}
+
+fn quit_accelerator(app: &AppHandle) -> String {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(QUIT_ACCELERATOR_KEY))
+    .and_then(|value| value.as_str().map(String::from))
+    .unwrap_or_else(|| crate::settings::DEFAULT_QUIT_ACCELERATOR.to_string())
+}
+
+/// Whether the quit key should be held: the panel has focus and nothing has
+/// taken the app's shortcuts down. A hotkey capture has to see the chord
+/// itself, and `suspend_hotkeys` promises nothing gets registered.
+fn wants_quit_key(app: &AppHandle) -> bool {
+  let state = app.state::<AppState>();
+  *state.panel_focused.lock().unwrap()
+    && !hotkeys::suspended(app)
+    && state.paused_shortcuts.lock().unwrap().is_none()
+    && state.hotkey_capture.lock().unwrap().is_none()
+}
+
+/// Registers `quit_accelerator` (Cmd+Q by default) while `wants_quit_key`,
+/// and releases it otherwise. A global Cmd+Q would take the key from every
+/// other app, the way Escape once did. Called on focus changes (see
+/// `escape::track_focus`) and whenever suspending, pausing, capture or the
+/// setting change; never with their locks held. Left alone if something
+/// else already holds the key, e.g. a user binding for quit-app. macOS only.
+pub fn sync_quit_key(app: &AppHandle) {
+  if !cfg!(target_os = "macos") {
+    return;
+  }
+  let wanted = wants_quit_key(app).then(|| quit_accelerator(app));
+  let state = app.state::<AppState>();
+  let mut bound = state.quit_key_bound.lock().unwrap();
+  if *bound == wanted {
+    return;
+  }
+  let shortcuts = app.global_shortcut();
+  if let Some(old) = bound.take() {
+    if let Err(e) = shortcuts.unregister(old.as_str()) {
+      log::warn!("could not release {}: {}", old, e);
+    }
+  }
+  let Some(accelerator) = wanted else {
+    return;
+  };
+  if shortcuts.is_registered(accelerator.as_str()) {
+    return;
+  }
+  let result = shortcuts.on_shortcut(accelerator.as_str(), |app, _shortcut, event| {
+    if event.state == ShortcutState::Pressed {
+      request(app);
+    }
+  });
+  match result {
+    Ok(()) => *bound = Some(accelerator),
+    Err(e) => log::warn!("could not bind {} in the panel: {}", accelerator, e),
+  }
+}
+
+/// Confirms a pending quit request (see `request`) and shuts down.
+#[tauri::command]
+pub fn confirm_quit(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "confirm_quit", move || {
+    log::info!("confirm_quit invoked");
+
+    if !take_pending(&app, None) {
+      return Err(AppError::InvalidArgument("No quit is pending".to_string()));
+    }
+    shutdown(&app);
+    Ok(ok_status())
+  })
+}
+
+/// Quits whether or not a quit request is pending, e.g. once the frontend's
+/// own confirmation is accepted.
+#[tauri::command]
+pub fn quit_app(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "quit_app", move || {
+    log::info!("quit_app invoked");
+
+    take_pending(&app, None);
+    shutdown(&app);
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn only_the_pending_request_is_taken() {
+    let mut pending = Some(2);
+    assert!(!take_if(&mut pending, Some(1)));
+    assert_eq!(pending, Some(2));
+    assert!(take_if(&mut pending, Some(2)));
+    assert_eq!(pending, None);
+    assert!(!take_if(&mut pending, None));
+  }
+
+  #[test]
+  fn confirmation_takes_any_pending_request() {
+    let mut pending = Some(7);
+    assert!(take_if(&mut pending, None));
+    assert!(!take_if(&mut pending, Some(7)));
+  }
+}
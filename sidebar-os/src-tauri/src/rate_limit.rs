@@ -0,0 +1,115 @@
+use std::time::{Duration, Instant};
+
+// `debug_log` lines allowed per second. A burst can use a second's worth at
+// once.
+pub const DEBUG_LOG_PER_SECOND: u32 = 100;
+// `log-rate-limit-exceeded` goes out at most this often.
+pub const WARNING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Holds up to `per_second` tokens, refilled continuously at that rate.
+#[derive(Debug)]
+pub struct TokenBucket {
+  rate: f64,
+  tokens: f64,
+  refilled: Instant,
+}
+
+impl TokenBucket {
+  pub fn new(per_second: u32, now: Instant) -> Self {
+    let rate = per_second as f64;
+    Self { rate, tokens: rate, refilled: now }
+  }
+
+  /// Takes a token if there's one left.
+  pub fn try_take(&mut self, now: Instant) -> bool {
+    let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+    self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+    self.refilled = now;
+    if self.tokens >= 1.0 {
+      self.tokens -= 1.0;
+      true
+    } else {
+      false
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+  Log,
+  Drop,
+  // Dropped, and time to tell the frontend how many lines are gone so far.
+  DropAndWarn(u64),
+}
+
+/// Keeps a flooding frontend from drowning out the rest of the log.
+#[derive(Debug)]
+pub struct LogRateLimit {
+  bucket: TokenBucket,
+  pub dropped_log_lines: u64,
+  last_warning: Option<Instant>,
+}
+
+impl Default for LogRateLimit {
+  fn default() -> Self {
+    Self {
+      bucket: TokenBucket::new(DEBUG_LOG_PER_SECOND, Instant::now()),
+      dropped_log_lines: 0,
+      last_warning: None,
+    }
+  }
+}
+
+impl LogRateLimit {
+  /// Whether a `debug_log` line at `now` gets logged; dropped ones are
+  /// counted.
+  pub fn check(&mut self, now: Instant) -> Verdict {
+    if self.bucket.try_take(now) {
+      return Verdict::Log;
+    }
+    self.dropped_log_lines += 1;
+    let since_warning = self.last_warning.map(|last| now.saturating_duration_since(last));
+    if since_warning.is_none_or(|since| since >= WARNING_INTERVAL) {
+      self.last_warning = Some(now);
+      Verdict::DropAndWarn(self.dropped_log_lines)
+    } else {
+      Verdict::Drop
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn bucket_refills_over_time() {
+    let start = Instant::now();
+    let mut bucket = TokenBucket::new(2, start);
+    assert!(bucket.try_take(start));
+    assert!(bucket.try_take(start));
+    assert!(!bucket.try_take(start));
+    assert!(bucket.try_take(start + Duration::from_millis(500)));
+    assert!(!bucket.try_take(start + Duration::from_millis(500)));
+  }
+
+  #[test]
+  fn floods_are_dropped_and_warned_about_once_a_second() {
+    let start = Instant::now();
+    let mut limit = LogRateLimit { bucket: TokenBucket::new(100, start), ..Default::default() };
+    let verdicts: Vec<Verdict> = (0..103).map(|_| limit.check(start)).collect();
+    assert!(verdicts[..100].iter().all(|v| *v == Verdict::Log));
+    assert_eq!(verdicts[100..], [Verdict::DropAndWarn(1), Verdict::Drop, Verdict::Drop]);
+
+    // Refilled tokens get logged again; drops keep counting.
+    let later = start + Duration::from_millis(10);
+    assert_eq!(limit.check(later), Verdict::Log);
+    assert_eq!(limit.check(later), Verdict::Drop);
+    let second_later = start + WARNING_INTERVAL + Duration::from_millis(10);
+    let mut verdict = limit.check(second_later);
+    while verdict == Verdict::Log {
+      verdict = limit.check(second_later);
+    }
+    assert_eq!(verdict, Verdict::DropAndWarn(5));
+  }
+}
@@ -0,0 +1,140 @@
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+
+// Edge bits; corners are the two edges they join.
+pub const TOP: u8 = 1;
+pub const BOTTOM: u8 = 2;
+pub const LEFT: u8 = 4;
+pub const RIGHT: u8 = 8;
+pub const ALL: u8 = TOP | BOTTOM | LEFT | RIGHT;
+
+pub fn parse_directions(directions: &[String]) -> Result<u8, String> {
+  directions.iter().try_fold(0, |edges, direction| {
+    let edge = match direction.to_lowercase().as_str() {
+      "top" => TOP,
+      "bottom" => BOTTOM,
+      "left" => LEFT,
+      "right" => RIGHT,
+      _ => return Err(format!("Unknown resize direction: {}", direction)),
+    };
+    Ok(edges | edge)
+  })
+}
+
+// Windows: the undecorated panel's resize borders come from WM_NCHITTEST, so
+// a subclass filters the hit-test result down to the allowed edges. A corner
+// with one allowed edge resizes along that edge only.
+#[cfg(target_os = "windows")]
+mod hit_test {
+  use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+  use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+  use windows_sys::Win32::UI::Shell::{DefSubclassProc, SetWindowSubclass};
+  use windows_sys::Win32::UI::WindowsAndMessaging::{
+    HTBOTTOM, HTBOTTOMLEFT, HTBOTTOMRIGHT, HTCLIENT, HTLEFT, HTRIGHT, HTTOP, HTTOPLEFT,
+    HTTOPRIGHT, WM_NCHITTEST,
+  };
+
+  use super::{ALL, BOTTOM, LEFT, RIGHT, TOP};
+
+  static ALLOWED: AtomicU8 = AtomicU8::new(ALL);
+  static INSTALLED: AtomicBool = AtomicBool::new(false);
+  const SUBCLASS_ID: usize = 0x5245_5349; // "RESI"
+
+  const CODES: [(u32, u8); 8] = [
+    (HTTOP, TOP),
+    (HTBOTTOM, BOTTOM),
+    (HTLEFT, LEFT),
+    (HTRIGHT, RIGHT),
+    (HTTOPLEFT, TOP | LEFT),
+    (HTTOPRIGHT, TOP | RIGHT),
+    (HTBOTTOMLEFT, BOTTOM | LEFT),
+    (HTBOTTOMRIGHT, BOTTOM | RIGHT),
+  ];
+
+  fn filter(code: u32, allowed: u8) -> u32 {
+    let Some(&(_, edges)) = CODES.iter().find(|(c, _)| *c == code) else {
+      return code;
+    };
+    let kept = edges & allowed;
+    CODES
+      .iter()
+      .find(|(_, e)| *e == kept)
+      .map(|(c, _)| *c)
+      .unwrap_or(HTCLIENT)
+  }
+
+  unsafe extern "system" fn subclass_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    _id: usize,
+    _data: usize,
+  ) -> LRESULT {
+    let result = DefSubclassProc(hwnd, msg, wparam, lparam);
+    if msg == WM_NCHITTEST {
+      return filter(result as u32, ALLOWED.load(Ordering::Relaxed)) as LRESULT;
+    }
+    result
+  }
+
+  pub fn apply(window: &tauri::WebviewWindow, edges: u8) -> Result<(), String> {
+    ALLOWED.store(edges, Ordering::Relaxed);
+    window.set_resizable(edges != 0).map_err(|e| e.to_string())?;
+    if edges != ALL && !INSTALLED.swap(true, Ordering::SeqCst) {
+      let hwnd = window.hwnd().map_err(|e| e.to_string())?.0 as HWND;
+      if unsafe { SetWindowSubclass(hwnd, Some(subclass_proc), SUBCLASS_ID, 0) } == 0 {
+        INSTALLED.store(false, Ordering::SeqCst);
+        return Err("SetWindowSubclass failed".to_string());
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(target_os = "windows")]
+fn apply(window: &tauri::WebviewWindow, edges: u8) -> Result<(), String> {
+  hit_test::apply(window, edges)
+}
+
+// macOS (NSWindowStyleMaskResizable) and Linux only know resizable or not:
+// any direction enables resizing on every edge, none disables it.
+#[cfg(not(target_os = "windows"))]
+fn apply(window: &tauri::WebviewWindow, edges: u8) -> Result<(), String> {
+  if edges != 0 && edges != ALL {
+    log::debug!("partial resize directions aren't supported here; allowing all edges");
+  }
+  window.set_resizable(edges != 0).map_err(|e| e.to_string())
+}
+
+/// Restricts which edges of the panel can be dragged to resize it, e.g.
+/// `["bottom"]`. An empty list turns resizing off. Partial constraints are
+/// honored on Windows only; see `apply`.
+#[tauri::command]
+pub fn set_resize_directions(app: AppHandle, directions: Vec<String>) -> CommandResult {
+  audit::run(app.clone(), "set_resize_directions", move || {
+    log::info!("set_resize_directions: directions={:?}", directions);
+
+    let edges = parse_directions(&directions).map_err(AppError::InvalidArgument)?;
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    apply(&window, edges)?;
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_edge_names() {
+    let dirs = |d: &[&str]| parse_directions(&d.iter().map(|s| s.to_string()).collect::<Vec<_>>());
+    assert_eq!(dirs(&["bottom", "Right"]), Ok(BOTTOM | RIGHT));
+    assert_eq!(dirs(&["top", "bottom", "left", "right"]), Ok(ALL));
+    assert_eq!(dirs(&[]), Ok(0));
+    assert!(dirs(&["diagonal"]).is_err());
+  }
+}
@@ -0,0 +1,194 @@
+use std::path::{Component, Path, PathBuf};
+
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::error::AppError;
+use crate::paths;
+
+/// Converts 32-bit BGRA rows (the native macOS capture layout) to tightly
+/// packed RGBA, undoing premultiplied alpha if needed.
+#[cfg(any(target_os = "macos", test))]
+fn bgra_to_rgba(
+  data: &[u8],
+  width: usize,
+  height: usize,
+  bytes_per_row: usize,
+  premultiplied: bool,
+) -> Vec<u8> {
+  let mut rgba = Vec::with_capacity(width * height * 4);
+  for row in data.chunks(bytes_per_row).take(height) {
+    for px in row[..width * 4].chunks_exact(4) {
+      let (b, g, r, a) = (px[0], px[1], px[2], px[3]);
+      let unmultiply = |c: u8| -> u8 {
+        if !premultiplied || a == 0 || a == 255 {
+          c
+        } else {
+          ((c as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8
+        }
+      };
+      rgba.extend_from_slice(&[unmultiply(r), unmultiply(g), unmultiply(b), a]);
+    }
+  }
+  rgba
+}
+
+#[cfg(any(target_os = "macos", test))]
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>, String> {
+  let mut bytes = Vec::new();
+  let mut encoder = png::Encoder::new(&mut bytes, width, height);
+  encoder.set_color(png::ColorType::Rgba);
+  encoder.set_depth(png::BitDepth::Eight);
+  let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+  writer.write_image_data(rgba).map_err(|e| e.to_string())?;
+  writer.finish().map_err(|e| e.to_string())?;
+  Ok(bytes)
+}
+
+// CGWindowListCreateImage is deprecated in favour of ScreenCaptureKit, which
+// is async-only and needs its own permission flow; this still works for our
+// own window.
+#[cfg(target_os = "macos")]
+#[allow(deprecated)]
+fn capture(window: &tauri::WebviewWindow) -> Result<Vec<u8>, String> {
+  use objc2_app_kit::NSWindow;
+  use objc2_core_graphics::{
+    CGDataProvider, CGImage, CGImageAlphaInfo, CGImageByteOrderInfo, CGRectNull,
+    CGWindowImageOption, CGWindowListCreateImage, CGWindowListOption,
+  };
+
+  let ns_window = window.ns_window().map_err(|e| e.to_string())?;
+  let window_number = unsafe { &*(ns_window as *const NSWindow) }.windowNumber();
+
+  let image = CGWindowListCreateImage(
+    unsafe { CGRectNull },
+    CGWindowListOption::OptionIncludingWindow,
+    window_number as u32,
+    CGWindowImageOption::BoundsIgnoreFraming | CGWindowImageOption::BestResolution,
+  )
+  .ok_or("Could not capture the panel window")?;
+
+  let image = Some(&*image);
+  let alpha = CGImage::alpha_info(image);
+  if CGImage::bits_per_pixel(image) != 32
+    || CGImage::byte_order_info(image) != CGImageByteOrderInfo::Order32Little
+    || ![CGImageAlphaInfo::PremultipliedFirst, CGImageAlphaInfo::First, CGImageAlphaInfo::NoneSkipFirst]
+      .contains(&alpha)
+  {
+    return Err("Unsupported screenshot pixel format".to_string());
+  }
+
+  let data = CGDataProvider::data(CGImage::data_provider(image).as_deref())
+    .ok_or("Could not read screenshot pixels")?
+    .to_vec();
+  let (width, height) = (CGImage::width(image), CGImage::height(image));
+  let rgba = bgra_to_rgba(
+    &data,
+    width,
+    height,
+    CGImage::bytes_per_row(image),
+    alpha == CGImageAlphaInfo::PremultipliedFirst,
+  );
+  encode_png(width as u32, height as u32, &rgba)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn capture(_window: &tauri::WebviewWindow) -> Result<Vec<u8>, AppError> {
+  Err(AppError::PlatformUnsupported("Screenshots are only supported on macOS".to_string()))
+}
+
+// Content protection is only set through tauri.conf.json, so the config
+// is the source of truth.
+fn content_protected(app: &AppHandle) -> bool {
+  app
+    .config()
+    .app
+    .windows
+    .iter()
+    .any(|w| w.label == "panel" && w.content_protected)
+}
+
+/// Relative path under the screenshots folder; anything that could escape it
+/// is rejected.
+fn screenshot_path(dir: &Path, name: Option<&str>) -> Result<PathBuf, String> {
+  let name = match name {
+    Some(name) => name.to_string(),
+    None => format!("panel-{}.png", crate::now_ms()),
+  };
+  let relative = Path::new(&name);
+  if name.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+    return Err(format!("Invalid screenshot path: {}", name));
+  }
+  Ok(dir.join(relative))
+}
+
+#[tauri::command]
+pub fn take_screenshot(app: AppHandle) -> Result<Vec<u8>, AppError> {
+  audit::run(app.clone(), "take_screenshot", move || {
+    log::info!("take_screenshot invoked");
+
+    if content_protected(&app) {
+      return Err("Screenshots are disabled while the panel is content-protected".into());
+    }
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    Ok(capture(&window)?)
+  })
+}
+
+/// Saves a PNG of the panel under `<app data>/screenshots` and returns the
+/// full path. `path` is an optional file name relative to that folder.
+#[tauri::command]
+pub fn save_screenshot_to_file(app: AppHandle, path: Option<String>) -> Result<String, AppError> {
+  audit::run(app.clone(), "save_screenshot_to_file", move || {
+    log::info!("save_screenshot_to_file: path={:?}", path);
+
+    let dir = paths::data_dir(&app)?.join("screenshots");
+    let target = screenshot_path(&dir, path.as_deref())?;
+    let png = take_screenshot(app)?;
+
+    if let Some(parent) = target.parent() {
+      std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&target, png).map_err(|e| e.to_string())?;
+
+    log::info!("screenshot saved to {}", target.display());
+    Ok(target.to_string_lossy().into_owned())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn swaps_channels_and_drops_row_padding() {
+    // 1x2 image, 8 bytes of padding per row.
+    let data = [
+      10, 20, 30, 255, 0, 0, 0, 0, //
+      40, 50, 60, 255, 0, 0, 0, 0,
+    ];
+    assert_eq!(bgra_to_rgba(&data, 1, 2, 8, true), vec![30, 20, 10, 255, 60, 50, 40, 255]);
+  }
+
+  #[test]
+  fn unmultiplies_partial_alpha() {
+    let data = [50, 50, 50, 128];
+    assert_eq!(bgra_to_rgba(&data, 1, 1, 4, true), vec![100, 100, 100, 128]);
+    assert_eq!(bgra_to_rgba(&data, 1, 1, 4, false), vec![50, 50, 50, 128]);
+  }
+
+  #[test]
+  fn encodes_a_valid_png() {
+    let png = encode_png(1, 1, &[255, 0, 0, 255]).unwrap();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+  }
+
+  #[test]
+  fn screenshot_path_stays_in_folder() {
+    let dir = Path::new("/data/screenshots");
+    assert_eq!(screenshot_path(dir, Some("bug.png")).unwrap(), dir.join("bug.png"));
+    assert!(screenshot_path(dir, Some("../escape.png")).is_err());
+    assert!(screenshot_path(dir, Some("/etc/passwd")).is_err());
+    assert!(screenshot_path(dir, None).unwrap().starts_with(dir));
+  }
+}
@@ -0,0 +1,136 @@
+use keyring::Entry;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::error::{ok_status, AppError};
+use crate::state::AppState;
+
+pub const REDACTED: &str = "[redacted]";
+
+/// Errors from the secret commands. `KeyringUnavailable` means the platform
+/// credential store can't be used at all; it goes back as
+/// `PLATFORM_UNSUPPORTED` so the frontend can pick a fallback.
+#[derive(Debug)]
+pub enum SecretError {
+  KeyringUnavailable(String),
+  InvalidName(String),
+  Other(String),
+}
+
+impl From<keyring::Error> for SecretError {
+  fn from(e: keyring::Error) -> Self {
+    match e {
+      keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_) => {
+        SecretError::KeyringUnavailable(e.to_string())
+      }
+      other => SecretError::Other(other.to_string()),
+    }
+  }
+}
+
+impl From<SecretError> for AppError {
+  fn from(e: SecretError) -> Self {
+    match e {
+      SecretError::KeyringUnavailable(message) => AppError::PlatformUnsupported(message),
+      SecretError::InvalidName(message) => AppError::InvalidArgument(message),
+      SecretError::Other(message) => AppError::Other(message),
+    }
+  }
+}
+
+fn entry(app: &AppHandle, name: &str) -> Result<Entry, SecretError> {
+  if name.trim().is_empty() {
+    return Err(SecretError::InvalidName("Secret name must not be empty".to_string()));
+  }
+  // Namespaced by the bundle identifier so other apps' entries never collide.
+  Ok(Entry::new(&app.config().identifier, name)?)
+}
+
+/// Remembers a secret value so `debug_log` can scrub it from webview logs.
+fn track(app: &AppHandle, value: &str) {
+  if value.is_empty() {
+    return;
+  }
+  let state = app.state::<AppState>();
+  let mut known = state.known_secrets.lock().unwrap();
+  if !known.iter().any(|v| v == value) {
+    known.push(value.to_string());
+  }
+}
+
+/// Replaces every known secret value in `message`.
+pub fn redact(message: &str, secrets: &[String]) -> String {
+  secrets
+    .iter()
+    .filter(|s| !s.is_empty())
+    .fold(message.to_string(), |msg, secret| msg.replace(secret.as_str(), REDACTED))
+}
+
+#[tauri::command]
+pub fn secret_set(app: AppHandle, name: String, value: String) -> Result<Value, AppError> {
+  audit::run(app.clone(), "secret_set", move || {
+    log::info!("secret_set: name={}", name);
+
+    entry(&app, &name)?.set_password(&value).map_err(SecretError::from)?;
+    track(&app, &value);
+    Ok(ok_status())
+  })
+}
+
+#[tauri::command]
+pub fn secret_get(app: AppHandle, name: String) -> Result<Option<String>, AppError> {
+  audit::run(app.clone(), "secret_get", move || {
+    log::info!("secret_get: name={}", name);
+
+    match entry(&app, &name)?.get_password() {
+      Ok(value) => {
+        track(&app, &value);
+        Ok(Some(value))
+      }
+      Err(keyring::Error::NoEntry) => Ok(None),
+      Err(e) => Err(SecretError::from(e).into()),
+    }
+  })
+}
+
+#[tauri::command]
+pub fn secret_delete(app: AppHandle, name: String) -> Result<Value, AppError> {
+  audit::run(app.clone(), "secret_delete", move || {
+    log::info!("secret_delete: name={}", name);
+
+    match entry(&app, &name)?.delete_credential() {
+      Ok(()) | Err(keyring::Error::NoEntry) => Ok(ok_status()),
+      Err(e) => Err(SecretError::from(e).into()),
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn redacts_known_secrets() {
+    let secrets = vec!["sk-123".to_string(), "hunter2".to_string()];
+    assert_eq!(
+      redact("key=sk-123 pass=hunter2", &secrets),
+      "key=[redacted] pass=[redacted]"
+    );
+    assert_eq!(redact("nothing here", &secrets), "nothing here");
+  }
+
+  #[test]
+  fn empty_secret_is_not_a_wildcard() {
+    assert_eq!(redact("abc", &[String::new()]), "abc");
+  }
+
+  #[test]
+  fn unavailable_keyring_has_its_own_variant() {
+    let err = SecretError::from(keyring::Error::NoStorageAccess("locked".into()));
+    assert!(matches!(err, SecretError::KeyringUnavailable(_)));
+    assert_eq!(AppError::from(err).code(), crate::error_codes::PLATFORM_UNSUPPORTED);
+    let invalid = AppError::from(SecretError::InvalidName("x".into()));
+    assert_eq!(invalid, AppError::InvalidArgument("x".to_string()));
+  }
+}
@@ -0,0 +1,600 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::background::{self, BackgroundColor};
+use crate::double_tap::{self, Modifier};
+use crate::error::AppError;
+use crate::escape::{self, EscapeAction};
+use crate::hot_corner::{self, Corner};
+use crate::hotkeys::{self, HotkeyBehavior, HotkeyConfig};
+use crate::layouts::{Layout, LayoutProfile};
+use crate::placement::{self, Placement};
+use crate::sizing::{self, ModeSize};
+use crate::state::AppState;
+use crate::{
+  autosave, controller, journal, layouts, monitors, pin, profiles, quit, stacking, theme, title,
+  webview, WindowPos,
+};
+use crate::stacking::OnTopLevel;
+use crate::theme::ThemePreference;
+
+// Fallbacks for keys missing from the store. Also what first run seeds.
+pub const DEFAULT_AUTO_RECENTER: bool = true;
+pub const DEFAULT_ALLOW_WEBVIEW_RELOAD: bool = false;
+pub const DEFAULT_STALE_LAYOUT_DAYS: u64 = 30;
+pub const DEFAULT_VISIBLE_ON_FULLSCREEN: bool = false;
+pub const DEFAULT_WINDOW_MOVABLE: bool = true;
+// Full monitor (below the menu bar) by default; the work area also leaves
+// out the Dock.
+pub const DEFAULT_USE_WORK_AREA: bool = false;
+pub const DEFAULT_AUTOSAVE_POSITION: bool = true;
+pub const DEFAULT_TOGGLE_NOTIFY_ONLY: bool = false;
+pub const DEFAULT_SKIP_TASKBAR: bool = true;
+pub const DEFAULT_HOTKEY_CYCLES_MONITORS: bool = false;
+pub const DEFAULT_SKIP_QUIT_CONFIRMATION: bool = false;
+pub const DEFAULT_QUIT_ACCELERATOR: &str = "Cmd+Q";
+pub const DEFAULT_DOUBLE_TAP_INTERVAL_MS: u64 = 300;
+const INITIALIZED_KEY: &str = "initialized";
+// Modes that get a placement written on first run.
+const SEEDED_MODES: [&str; 5] =
+  ["collapsed", "expanded", "hovered", "sidepanel_right", "sidepanel_left"];
+
+/// Bumped whenever a field of `Settings` or the store layout changes
+/// meaning; `migrations` brings older stores up to it.
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SizeSetting {
+  pub width: f64,
+  pub height: f64,
+}
+
+/// Every known setting with defaults filled in. `Settings::default()` is the
+/// canonical set used for first-run seeding, per-key fallbacks and the
+/// frontend's "reset to default" buttons; `load` overlays the store on top.
+#[derive(Debug, Clone, Serialize)]
+pub struct Settings {
+  pub schema_version: u32,
+  pub profile: String,
+  pub placements: BTreeMap<String, Placement>,
+  pub sizes: BTreeMap<String, SizeSetting>,
+  pub hotkeys: HotkeyConfig,
+  pub hot_corner: Option<Corner>,
+  pub pinned_monitor: Option<String>,
+  pub double_tap_modifier: Option<Modifier>,
+  pub double_tap_interval_ms: u64,
+  pub auto_recenter: bool,
+  pub allow_webview_reload: bool,
+  pub stale_layout_days: u64,
+  pub allowed_origins: Vec<String>,
+  pub visible_on_fullscreen: bool,
+  pub window_movable: bool,
+  pub use_work_area: bool,
+  pub autosave_position: bool,
+  pub toggle_collapse_notify_only: bool,
+  pub hotkey_behavior: HotkeyBehavior,
+  pub hotkey_cycles_monitors: bool,
+  pub skip_quit_confirmation: bool,
+  pub quit_accelerator: String,
+  pub escape_action: EscapeAction,
+  pub skip_taskbar: bool,
+  pub always_on_top_level: OnTopLevel,
+  pub window_title: Option<String>,
+  pub background_color: Option<BackgroundColor>,
+  pub theme: ThemePreference,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Self {
+      schema_version: SCHEMA_VERSION,
+      profile: profiles::DEFAULT_PROFILE.to_string(),
+      placements: SEEDED_MODES
+        .iter()
+        .map(|mode| (mode.to_string(), placement::default_placement(mode)))
+        .collect(),
+      sizes: SEEDED_MODES
+        .iter()
+        .filter_map(|mode| {
+          let preset = controller::size_preset(mode)?;
+          Some((mode.to_string(), SizeSetting { width: preset.width, height: preset.height }))
+        })
+        .collect(),
+      hotkeys: HotkeyConfig::default(),
+      hot_corner: None,
+      pinned_monitor: None,
+      double_tap_modifier: None,
+      double_tap_interval_ms: DEFAULT_DOUBLE_TAP_INTERVAL_MS,
+      auto_recenter: DEFAULT_AUTO_RECENTER,
+      allow_webview_reload: DEFAULT_ALLOW_WEBVIEW_RELOAD,
+      stale_layout_days: DEFAULT_STALE_LAYOUT_DAYS,
+      allowed_origins: Vec::new(),
+      visible_on_fullscreen: DEFAULT_VISIBLE_ON_FULLSCREEN,
+      window_movable: DEFAULT_WINDOW_MOVABLE,
+      use_work_area: DEFAULT_USE_WORK_AREA,
+      autosave_position: DEFAULT_AUTOSAVE_POSITION,
+      toggle_collapse_notify_only: DEFAULT_TOGGLE_NOTIFY_ONLY,
+      hotkey_behavior: HotkeyBehavior::default(),
+      hotkey_cycles_monitors: DEFAULT_HOTKEY_CYCLES_MONITORS,
+      skip_quit_confirmation: DEFAULT_SKIP_QUIT_CONFIRMATION,
+      quit_accelerator: DEFAULT_QUIT_ACCELERATOR.to_string(),
+      escape_action: EscapeAction::default(),
+      skip_taskbar: DEFAULT_SKIP_TASKBAR,
+      always_on_top_level: OnTopLevel::default(),
+      window_title: None,
+      background_color: None,
+      theme: ThemePreference::default(),
+    }
+  }
+}
+
+fn stored<T: serde::de::DeserializeOwned>(entries: &HashMap<String, Value>, key: &str) -> Option<T> {
+  entries.get(key).and_then(|value| serde_json::from_value(value.clone()).ok())
+}
+
+impl Settings {
+  /// Defaults overridden by whatever valid values `entries` holds.
+  fn with_stored(mut self, entries: &HashMap<String, Value>) -> Self {
+    for (mode, entry) in layouts::resolve(entries) {
+      if let Some(p) = entry.placement {
+        self.placements.insert(mode.clone(), p);
+      }
+      if let Some(size) = entry.size {
+        let size = SizeSetting { width: size.width as f64, height: size.height as f64 };
+        self.sizes.insert(mode, size);
+      }
+    }
+    self.hotkeys = stored(entries, hotkeys::HOTKEYS_KEY).unwrap_or(self.hotkeys);
+    self.hot_corner = stored(entries, hot_corner::HOT_CORNER_KEY).or(self.hot_corner);
+    let pinned = entries.get(monitors::PINNED_MONITOR_KEY).cloned();
+    self.pinned_monitor = monitors::pinned_name(pinned).or(self.pinned_monitor);
+    self.double_tap_modifier =
+      stored(entries, double_tap::MODIFIER_KEY).or(self.double_tap_modifier);
+    self.double_tap_interval_ms =
+      stored(entries, double_tap::INTERVAL_KEY).unwrap_or(self.double_tap_interval_ms);
+    self.auto_recenter = stored(entries, monitors::AUTO_RECENTER_KEY).unwrap_or(self.auto_recenter);
+    self.allow_webview_reload =
+      stored(entries, webview::ALLOW_RELOAD_KEY).unwrap_or(self.allow_webview_reload);
+    self.stale_layout_days = stored(entries, layouts::STALE_DAYS_KEY).unwrap_or(self.stale_layout_days);
+    self.allowed_origins =
+      stored(entries, webview::ALLOWED_ORIGINS_KEY).unwrap_or(self.allowed_origins);
+    self.visible_on_fullscreen =
+      stored(entries, stacking::VISIBLE_ON_FULLSCREEN_KEY).unwrap_or(self.visible_on_fullscreen);
+    self.window_movable = stored(entries, pin::WINDOW_MOVABLE_KEY).unwrap_or(self.window_movable);
+    self.use_work_area = stored(entries, monitors::USE_WORK_AREA_KEY).unwrap_or(self.use_work_area);
+    self.autosave_position =
+      stored(entries, autosave::AUTOSAVE_POSITION_KEY).unwrap_or(self.autosave_position);
+    self.toggle_collapse_notify_only = stored(entries, hotkeys::TOGGLE_NOTIFY_ONLY_KEY)
+      .unwrap_or(self.toggle_collapse_notify_only);
+    self.hotkey_behavior =
+      stored(entries, hotkeys::HOTKEY_BEHAVIOR_KEY).unwrap_or(self.hotkey_behavior);
+    self.hotkey_cycles_monitors =
+      stored(entries, hotkeys::HOTKEY_CYCLES_MONITORS_KEY).unwrap_or(self.hotkey_cycles_monitors);
+    self.skip_quit_confirmation =
+      stored(entries, quit::SKIP_CONFIRMATION_KEY).unwrap_or(self.skip_quit_confirmation);
+    self.quit_accelerator =
+      stored(entries, quit::QUIT_ACCELERATOR_KEY).unwrap_or(self.quit_accelerator);
+    self.escape_action = stored(entries, escape::ESCAPE_ACTION_KEY).unwrap_or(self.escape_action);
+    self.skip_taskbar = stored(entries, stacking::SKIP_TASKBAR_KEY).unwrap_or(self.skip_taskbar);
+    self.always_on_top_level =
+      stored(entries, stacking::ON_TOP_LEVEL_KEY).unwrap_or(self.always_on_top_level);
+    self.window_title = stored(entries, title::WINDOW_TITLE_KEY).or(self.window_title);
+    self.background_color =
+      stored(entries, background::BACKGROUND_COLOR_KEY).or(self.background_color);
+    self.theme = stored(entries, theme::THEME_KEY).unwrap_or(self.theme);
+    self
+  }
+
+  /// Reads the active profile's store once.
+  pub fn load(app: &AppHandle) -> Result<Self, String> {
+    let store = profiles::settings_store(app).map_err(|e| e.to_string())?;
+    let entries: HashMap<String, Value> = store.entries().into_iter().collect();
+    let profile = app.state::<AppState>().active_profile.lock().map_err(|e| e.to_string())?.clone();
+    Ok(Settings { profile, ..Settings::default() }.with_stored(&entries))
+  }
+
+  /// The store-backed settings as entries.
+  fn entries(&self) -> Vec<(String, Value)> {
+    let layout: BTreeMap<String, Value> = self
+      .placements
+      .iter()
+      .map(|(mode, p)| (mode.clone(), serde_json::json!({ "placement": p })))
+      .collect();
+    let mut entries = vec![(layouts::LAYOUT_KEY.to_string(), serde_json::json!(layout))];
+    entries.extend([
+      (hotkeys::HOTKEYS_KEY.to_string(), serde_json::json!(self.hotkeys)),
+      (monitors::AUTO_RECENTER_KEY.to_string(), Value::Bool(self.auto_recenter)),
+      (webview::ALLOW_RELOAD_KEY.to_string(), Value::Bool(self.allow_webview_reload)),
+      (layouts::STALE_DAYS_KEY.to_string(), Value::from(self.stale_layout_days)),
+      (webview::ALLOWED_ORIGINS_KEY.to_string(), serde_json::json!(self.allowed_origins)),
+      (stacking::VISIBLE_ON_FULLSCREEN_KEY.to_string(), Value::Bool(self.visible_on_fullscreen)),
+      (pin::WINDOW_MOVABLE_KEY.to_string(), Value::Bool(self.window_movable)),
+      (monitors::USE_WORK_AREA_KEY.to_string(), Value::Bool(self.use_work_area)),
+      (autosave::AUTOSAVE_POSITION_KEY.to_string(), Value::Bool(self.autosave_position)),
+      (
+        hotkeys::TOGGLE_NOTIFY_ONLY_KEY.to_string(),
+        Value::Bool(self.toggle_collapse_notify_only),
+      ),
+      (hotkeys::HOTKEY_BEHAVIOR_KEY.to_string(), serde_json::json!(self.hotkey_behavior)),
+      (hotkeys::HOTKEY_CYCLES_MONITORS_KEY.to_string(), Value::Bool(self.hotkey_cycles_monitors)),
+      (quit::SKIP_CONFIRMATION_KEY.to_string(), Value::Bool(self.skip_quit_confirmation)),
+      (quit::QUIT_ACCELERATOR_KEY.to_string(), Value::String(self.quit_accelerator.clone())),
+      (double_tap::INTERVAL_KEY.to_string(), Value::from(self.double_tap_interval_ms)),
+      (escape::ESCAPE_ACTION_KEY.to_string(), serde_json::json!(self.escape_action)),
+      (stacking::SKIP_TASKBAR_KEY.to_string(), Value::Bool(self.skip_taskbar)),
+      (stacking::ON_TOP_LEVEL_KEY.to_string(), serde_json::json!(self.always_on_top_level)),
+      (theme::THEME_KEY.to_string(), serde_json::json!(self.theme)),
+    ]);
+    if let Some(corner) = self.hot_corner {
+      entries.push((hot_corner::HOT_CORNER_KEY.to_string(), serde_json::json!(corner)));
+    }
+    if let Some(name) = &self.pinned_monitor {
+      entries.push((monitors::PINNED_MONITOR_KEY.to_string(), Value::String(name.clone())));
+    }
+    if let Some(modifier) = self.double_tap_modifier {
+      entries.push((double_tap::MODIFIER_KEY.to_string(), serde_json::json!(modifier)));
+    }
+    if let Some(title) = &self.window_title {
+      entries.push((title::WINDOW_TITLE_KEY.to_string(), Value::String(title.clone())));
+    }
+    if let Some(color) = self.background_color {
+      entries.push((background::BACKGROUND_COLOR_KEY.to_string(), serde_json::json!(color)));
+    }
+    entries
+  }
+}
+
+/// First run for the active profile's store: writes every default that isn't
+/// already set, then marks the store initialized.
+pub fn seed_defaults(app: &AppHandle) {
+  let Ok(store) = profiles::settings_store(app) else {
+    return;
+  };
+  if store.has(INITIALIZED_KEY) {
+    return;
+  }
+
+  let mut seeded = 0;
+  for (key, value) in Settings::default().entries() {
+    if !store.has(&key) {
+      store.set(key, value);
+      seeded += 1;
+    }
+  }
+  store.set(INITIALIZED_KEY, true);
+  match store.save() {
+    Ok(()) => log::info!("seeded {} default settings", seeded),
+    Err(e) => log::warn!("failed to save seeded defaults: {}", e),
+  }
+}
+
+/// What a settings key is allowed to hold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+  Bool,
+  Count,
+  HotCorner,
+  Modifier,
+  Hotkeys,
+  Placement,
+  Position,
+  LayoutProfile,
+  ModeSize,
+  Layout,
+  Title,
+  MonitorName,
+  Theme,
+  BackgroundColor,
+  OnTopLevel,
+  HotkeyBehavior,
+  EscapeAction,
+  Accelerator,
+}
+
+// The webview's reload and origin allowlists are left out on purpose, like
+// `debug_mode`: any page script can call `save_settings`, and it mustn't be
+// able to let itself reload or navigate.
+fn kind_of(key: &str) -> Option<Kind> {
+  match key {
+    monitors::AUTO_RECENTER_KEY
+    | stacking::VISIBLE_ON_FULLSCREEN_KEY
+    | pin::WINDOW_MOVABLE_KEY
+    | monitors::USE_WORK_AREA_KEY
+    | autosave::AUTOSAVE_POSITION_KEY
+    | hotkeys::TOGGLE_NOTIFY_ONLY_KEY
+    | stacking::SKIP_TASKBAR_KEY
+    | hotkeys::HOTKEY_CYCLES_MONITORS_KEY
+    | quit::SKIP_CONFIRMATION_KEY => Some(Kind::Bool),
+    stacking::ON_TOP_LEVEL_KEY => Some(Kind::OnTopLevel),
+    hotkeys::HOTKEY_BEHAVIOR_KEY => Some(Kind::HotkeyBehavior),
+    escape::ESCAPE_ACTION_KEY => Some(Kind::EscapeAction),
+    layouts::STALE_DAYS_KEY | double_tap::INTERVAL_KEY => Some(Kind::Count),
+    hot_corner::HOT_CORNER_KEY => Some(Kind::HotCorner),
+    double_tap::MODIFIER_KEY => Some(Kind::Modifier),
+    hotkeys::HOTKEYS_KEY => Some(Kind::Hotkeys),
+    quit::QUIT_ACCELERATOR_KEY => Some(Kind::Accelerator),
+    title::WINDOW_TITLE_KEY => Some(Kind::Title),
+    monitors::PINNED_MONITOR_KEY => Some(Kind::MonitorName),
+    theme::THEME_KEY => Some(Kind::Theme),
+    background::BACKGROUND_COLOR_KEY => Some(Kind::BackgroundColor),
+    layouts::LAYOUT_KEY => Some(Kind::Layout),
+    _ if key.starts_with(layouts::LEGACY_PLACEMENT_PREFIX) => Some(Kind::Placement),
+    _ if key.starts_with(layouts::LEGACY_POSITION_PREFIX) => Some(Kind::Position),
+    _ if key.starts_with(layouts::LAYOUT_PROFILE_PREFIX) => Some(Kind::LayoutProfile),
+    _ if key.starts_with(sizing::MODE_SIZE_PREFIX) => Some(Kind::ModeSize),
+    _ => None,
+  }
+}
+
+/// Checks `value` against the key's schema. Null always means "remove".
+fn validate_entry(key: &str, value: &Value) -> Result<(), String> {
+  let kind = kind_of(key).ok_or(format!("Unknown setting: {}", key))?;
+  if value.is_null() {
+    return Ok(());
+  }
+
+  let ok = match kind {
+    Kind::Bool => value.is_boolean(),
+    Kind::Count => value.is_u64(),
+    Kind::HotCorner => serde_json::from_value::<Corner>(value.clone()).is_ok(),
+    Kind::Modifier => serde_json::from_value::<Modifier>(value.clone()).is_ok(),
+    Kind::Hotkeys => {
+      let config: HotkeyConfig =
+        serde_json::from_value(value.clone()).map_err(|e| format!("{}: {}", key, e))?;
+      hotkeys::validate(&config).map_err(|e| format!("{}: {}", key, e))?;
+      true
+    }
+    Kind::Placement => serde_json::from_value::<Placement>(value.clone()).is_ok(),
+    Kind::Position => serde_json::from_value::<WindowPos>(value.clone()).is_ok(),
+    Kind::LayoutProfile => serde_json::from_value::<LayoutProfile>(value.clone()).is_ok(),
+    Kind::ModeSize => serde_json::from_value::<ModeSize>(value.clone()).is_ok(),
+    Kind::Layout => serde_json::from_value::<Layout>(value.clone()).is_ok(),
+    Kind::MonitorName => monitors::pinned_name(Some(value.clone())).is_some(),
+    Kind::Theme => serde_json::from_value::<ThemePreference>(value.clone()).is_ok(),
+    Kind::BackgroundColor => serde_json::from_value::<BackgroundColor>(value.clone()).is_ok(),
+    Kind::OnTopLevel => serde_json::from_value::<OnTopLevel>(value.clone()).is_ok(),
+    Kind::HotkeyBehavior => serde_json::from_value::<HotkeyBehavior>(value.clone()).is_ok(),
+    Kind::EscapeAction => serde_json::from_value::<EscapeAction>(value.clone()).is_ok(),
+    Kind::Accelerator => {
+      let accelerator =
+        value.as_str().ok_or(format!("Invalid value for {}: expected a string", key))?;
+      hotkeys::parse_accelerator(accelerator).map_err(|e| format!("{}: {}", key, e))?;
+      true
+    }
+    Kind::Title => {
+      let title = value.as_str().ok_or(format!("Invalid value for {}: expected a string", key))?;
+      title::validate_title(title).map_err(|e| format!("{}: {}", key, e))?;
+      true
+    }
+  };
+
+  if ok {
+    Ok(())
+  } else {
+    Err(format!("Invalid value for {}: expected {:?}", key, kind))
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingOutcome {
+  pub applied: bool,
+  pub error: Option<String>,
+}
+
+/// Writes several settings with a single save and a single `settings-changed`
+/// event. By default nothing is written unless every entry is valid; with
+/// `partial` the valid ones are applied and the rest reported per key.
+#[tauri::command]
+pub fn save_settings(
+  app: AppHandle,
+  entries: HashMap<String, Value>,
+  partial: Option<bool>,
+) -> Result<BTreeMap<String, SettingOutcome>, AppError> {
+  audit::run(app.clone(), "save_settings", move || {
+    log::info!("save_settings: {} entries, partial={:?}", entries.len(), partial);
+
+    let checked: BTreeMap<String, Result<(), String>> = entries
+      .keys()
+      .map(|key| (key.clone(), validate_entry(key, &entries[key])))
+      .collect();
+
+    if !partial.unwrap_or(false) {
+      let errors: Vec<&String> = checked.values().filter_map(|r| r.as_ref().err()).collect();
+      if !errors.is_empty() {
+        let message = errors.into_iter().cloned().collect::<Vec<_>>().join("; ");
+        return Err(AppError::InvalidArgument(message));
+      }
+    }
+
+    let store = profiles::settings_store(&app)?;
+    let mut applied = Vec::new();
+    let mut legacy = Vec::new();
+    for (key, result) in &checked {
+      if result.is_err() {
+        continue;
+      }
+      applied.push(key.clone());
+      // Old flat layout keys are folded into the `layout` object instead.
+      if layouts::is_legacy_key(key) {
+        legacy.push((key, &entries[key]));
+        continue;
+      }
+      match &entries[key] {
+        Value::Null => {
+          store.delete(key);
+        }
+        value => store.set(key.clone(), value.clone()),
+      }
+    }
+    store.save().map_err(|e| e.to_string())?;
+    if !legacy.is_empty() {
+      layouts::write_legacy(&app, &legacy)?;
+    }
+
+    if applied.iter().any(|k| k == hotkeys::HOTKEYS_KEY) {
+      hotkeys::reload(&app);
+    }
+    if applied.iter().any(|k| k == quit::QUIT_ACCELERATOR_KEY) {
+      quit::sync_quit_key(&app);
+    }
+    if applied.iter().any(|k| k == hot_corner::HOT_CORNER_KEY) {
+      hot_corner::load_from_store(&app);
+    }
+    if applied.iter().any(|k| k == double_tap::MODIFIER_KEY || k == double_tap::INTERVAL_KEY) {
+      double_tap::load_from_store(&app);
+    }
+    if applied.iter().any(|k| k == background::BACKGROUND_COLOR_KEY) {
+      background::restore_background(&app);
+    }
+    if applied.iter().any(|k| k == theme::THEME_KEY) {
+      theme::reapply(&app);
+    }
+    if applied.iter().any(|k| k == stacking::SKIP_TASKBAR_KEY || k == stacking::ON_TOP_LEVEL_KEY) {
+      stacking::reapply(&app);
+    }
+    if !applied.is_empty() {
+      let _ = journal::emit(&app, "settings-changed", serde_json::json!({ "keys": applied }));
+    }
+
+    Ok(
+      checked
+        .into_iter()
+        .map(|(key, result)| {
+          let outcome = SettingOutcome {
+            applied: result.is_ok(),
+            error: result.err(),
+          };
+          (key, outcome)
+        })
+        .collect(),
+    )
+  })
+}
+
+#[tauri::command]
+pub fn get_default_settings(app: AppHandle) -> Result<Settings, AppError> {
+  audit::run(app, "get_default_settings", || {
+    Ok(Settings::default())
+  })
+}
+
+/// All settings in one call, so the frontend can hydrate without a string
+/// of separate reads.
+#[tauri::command]
+pub fn get_settings_snapshot(app: AppHandle) -> Result<Settings, AppError> {
+  audit::run(app.clone(), "get_settings_snapshot", move || {
+    Ok(Settings::load(&app)?)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn accepts_known_keys_with_valid_values() {
+    assert!(validate_entry("auto_recenter", &json!(false)).is_ok());
+    assert!(validate_entry("hot_corner", &json!("top-left")).is_ok());
+    assert!(validate_entry("quit_accelerator", &json!("Ctrl+Shift+Q")).is_ok());
+    assert!(validate_entry("placement_expanded", &json!({ "anchor": "center", "margin": 0 })).is_ok());
+    assert!(validate_entry("custom_position_collapsed", &json!({ "x": 1, "y": 2 })).is_ok());
+  }
+
+  #[test]
+  fn rejects_unknown_keys_and_wrong_types() {
+    assert!(validate_entry("not_a_setting", &json!(true)).is_err());
+    assert!(validate_entry("auto_recenter", &json!("yes")).is_err());
+    assert!(validate_entry("stale_layout_days", &json!(-1)).is_err());
+    assert!(validate_entry("hot_corner", &json!("middle")).is_err());
+    assert!(validate_entry("double_tap_modifier", &json!("fn")).is_err());
+    assert!(validate_entry("window_title", &json!("  ")).is_err());
+    assert!(validate_entry("quit_accelerator", &json!("Ctrl+")).is_err());
+    assert!(validate_entry("allowed_origins", &json!(["https://evil.example"])).is_err());
+    assert!(validate_entry("allow_webview_reload", &json!(true)).is_err());
+    let hotkeys = json!({ "show-panel": { "accelerators": ["Cmd+Nope"] } });
+    assert!(validate_entry("hotkeys", &hotkeys).is_err());
+  }
+
+  #[test]
+  fn seeded_defaults_pass_validation() {
+    let entries = Settings::default().entries();
+    // The layout object plus one entry per behavior flag.
+    assert_eq!(entries.len(), 19);
+    for (key, value) in entries {
+      // Seeded, but not writable through `save_settings`; see `kind_of`.
+      if key == webview::ALLOW_RELOAD_KEY || key == webview::ALLOWED_ORIGINS_KEY {
+        continue;
+      }
+      assert!(validate_entry(&key, &value).is_ok(), "{} should validate", key);
+    }
+  }
+
+  #[test]
+  fn stored_values_override_defaults() {
+    let entries = HashMap::from([
+      ("auto_recenter".to_string(), json!(false)),
+      ("placement_hovered".to_string(), json!({ "anchor": "top-left", "margin": 8 })),
+      ("hot_corner".to_string(), json!("bottom-right")),
+      ("window_movable".to_string(), json!("not a bool")),
+    ]);
+    let settings = Settings::default().with_stored(&entries);
+    assert!(!settings.auto_recenter);
+    assert_eq!(settings.placements["hovered"].margin, 8);
+    assert_eq!(settings.hot_corner, Some(Corner::BottomRight));
+    // Invalid values fall back to the default.
+    assert!(settings.window_movable);
+  }
+
+  #[test]
+  fn snapshot_field_names_are_stable() {
+    let value = serde_json::to_value(Settings::default()).unwrap();
+    let mut keys: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    assert_eq!(
+      keys,
+      vec![
+        "allow_webview_reload",
+        "allowed_origins",
+        "always_on_top_level",
+        "auto_recenter",
+        "autosave_position",
+        "background_color",
+        "double_tap_interval_ms",
+        "double_tap_modifier",
+        "escape_action",
+        "hot_corner",
+        "hotkey_behavior",
+        "hotkey_cycles_monitors",
+        "hotkeys",
+        "pinned_monitor",
+        "placements",
+        "profile",
+        "quit_accelerator",
+        "schema_version",
+        "sizes",
+        "skip_quit_confirmation",
+        "skip_taskbar",
+        "stale_layout_days",
+        "theme",
+        "toggle_collapse_notify_only",
+        "use_work_area",
+        "visible_on_fullscreen",
+        "window_movable",
+        "window_title",
+      ]
+    );
+    assert_eq!(value["sizes"]["collapsed"], json!({ "width": 220.0, "height": 160.0 }));
+    assert_eq!(value["placements"]["hovered"], json!({ "anchor": "top-center", "margin": 40 }));
+  }
+
+  #[test]
+  fn null_removes_any_known_key() {
+    assert!(validate_entry("hotkeys", &Value::Null).is_ok());
+    assert!(validate_entry("unknown", &Value::Null).is_err());
+  }
+}
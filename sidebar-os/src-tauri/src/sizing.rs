@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, LogicalSize, Manager, PhysicalSize, Size};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::{history, layouts, monitors, placement};
+
+// Smallest size the panel is usable at (the collapsed pill).
+pub const MIN_WIDTH: u32 = 180;
+pub const MIN_HEIGHT: u32 = 50;
+// Legacy flat key; sizes now live in the `layout` object.
+pub const MODE_SIZE_PREFIX: &str = "mode_size_";
+
+/// A size registered for a mode, in logical pixels like the presets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModeSize {
+  pub width: u32,
+  pub height: u32,
+}
+
+pub fn load_mode_size(app: &AppHandle, mode: &str) -> Option<ModeSize> {
+  layouts::load_entry(app, mode).size
+}
+
+/// Size for the given percentages of the monitor. A missing or zero
+/// percentage keeps that axis at its current size; the result is clamped
+/// between the panel minimum and the monitor size.
+pub fn percent_size(
+  monitor_size: PhysicalSize<u32>,
+  current: PhysicalSize<u32>,
+  width_pct: Option<f64>,
+  height_pct: Option<f64>,
+) -> PhysicalSize<u32> {
+  let axis = |pct: Option<f64>, full: u32, current: u32, min: u32| -> u32 {
+    let target = match pct {
+      Some(p) if p > 0.0 => (full as f64 * p / 100.0).round() as u32,
+      _ => current,
+    };
+    target.clamp(min.min(full), full)
+  };
+
+  PhysicalSize {
+    width: axis(width_pct, monitor_size.width, current.width, MIN_WIDTH),
+    height: axis(height_pct, monitor_size.height, current.height, MIN_HEIGHT),
+  }
+}
+
+#[tauri::command]
+pub fn set_window_size_percent(
+  app: AppHandle,
+  width_pct: Option<f64>,
+  height_pct: Option<f64>,
+) -> Result<(u32, u32), AppError> {
+  audit::run(app.clone(), "set_window_size_percent", move || {
+    log::info!("set_window_size_percent: width={:?}%, height={:?}%", width_pct, height_pct);
+
+    for pct in [width_pct, height_pct].into_iter().flatten() {
+      if !(0.0..=100.0).contains(&pct) {
+        return Err(AppError::InvalidArgument(format!("Percentage out of range: {}", pct)));
+      }
+    }
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let monitor = monitors::panel_monitor(&window)?.ok_or(AppError::MonitorNotFound)?;
+    let current = window.outer_size().map_err(|e| e.to_string())?;
+    history::record(&app, &window);
+
+    let size = percent_size(monitor.size().to_owned(), current, width_pct, height_pct);
+    window
+      .set_size(Size::Physical(size))
+      .map_err(|e| e.to_string())?;
+
+    // Keep the panel at its anchor now that its size changed.
+    placement::apply_placement_on(&window, &monitor, placement::current(&app))?;
+
+    log::debug!("panel resized to {}x{}", size.width, size.height);
+    Ok((size.width, size.height))
+  })
+}
+
+#[tauri::command]
+pub fn register_mode_size(
+  app: AppHandle,
+  mode: String,
+  width: u32,
+  height: u32,
+) -> CommandResult {
+  audit::run(app.clone(), "register_mode_size", move || {
+    log::info!("register_mode_size: mode={}, width={}, height={}", mode, width, height);
+
+    if width < MIN_WIDTH || height < MIN_HEIGHT {
+      return Err(AppError::InvalidArgument(format!(
+        "Size must be at least {}x{}",
+        MIN_WIDTH, MIN_HEIGHT
+      )));
+    }
+    layouts::update_entry(&app, &mode, |entry| entry.size = Some(ModeSize { width, height }))?;
+    Ok(ok_status())
+  })
+}
+
+/// Resizes the panel to the size registered for `mode` and keeps it at its
+/// anchor. Returns false if nothing is registered.
+#[tauri::command]
+pub fn apply_mode_size(app: AppHandle, mode: String) -> Result<bool, AppError> {
+  audit::run(app.clone(), "apply_mode_size", move || {
+    log::info!("apply_mode_size: mode={}", mode);
+
+    let Some(size) = load_mode_size(&app, &mode) else {
+      return Ok(false);
+    };
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    history::record(&app, &window);
+
+    window
+      .set_size(Size::Logical(LogicalSize { width: size.width as f64, height: size.height as f64 }))
+      .map_err(|e| e.to_string())?;
+    placement::apply_placement(&window, placement::current(&app))?;
+    Ok(true)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const MONITOR: PhysicalSize<u32> = PhysicalSize { width: 2000, height: 1000 };
+  const CURRENT: PhysicalSize<u32> = PhysicalSize { width: 800, height: 600 };
+
+  #[test]
+  fn computes_size_from_percentages() {
+    let size = percent_size(MONITOR, CURRENT, Some(30.0), Some(50.0));
+    assert_eq!((size.width, size.height), (600, 500));
+  }
+
+  #[test]
+  fn none_or_zero_keeps_current_axis() {
+    let size = percent_size(MONITOR, CURRENT, None, Some(0.0));
+    assert_eq!((size.width, size.height), (800, 600));
+
+    let size = percent_size(MONITOR, CURRENT, Some(10.0), None);
+    assert_eq!((size.width, size.height), (200, 600));
+  }
+
+  #[test]
+  fn clamps_to_panel_minimum() {
+    let size = percent_size(MONITOR, CURRENT, Some(1.0), Some(1.0));
+    assert_eq!((size.width, size.height), (MIN_WIDTH, MIN_HEIGHT));
+  }
+
+  #[test]
+  fn mode_size_shape() {
+    let value = serde_json::json!({ "width": 300, "height": 80 });
+    let size: ModeSize = serde_json::from_value(value).unwrap();
+    assert_eq!(size, ModeSize { width: 300, height: 80 });
+  }
+}
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, Position, Size};
+
+use crate::audit;
+use crate::error::AppError;
+use crate::monitors::{self, MonitorRect};
+use crate::placement::{self, Placement};
+use crate::state::AppState;
+use crate::{autosave, history, journal, profiles, stacking};
+
+// Named snapshots, keyed by name.
+pub const SNAPSHOTS_KEY: &str = "window_snapshots";
+
+/// Everything about the panel at one moment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowSnapshot {
+  pub x: i32,
+  pub y: i32,
+  pub width: u32,
+  pub height: u32,
+  pub monitor: Option<String>,
+  // Where the panel sat within its monitor (0..1 on each axis), used when
+  // that monitor is gone.
+  pub fraction: (f64, f64),
+  pub mode: String,
+  pub always_on_top: bool,
+  pub placement: Option<Placement>,
+  pub saved_at: u64,
+}
+
+/// Position of the window's top-left within `monitor` as a fraction of the
+/// room it has to move in.
+pub fn fraction_in(monitor: MonitorRect, x: i32, y: i32, size: PhysicalSize<u32>) -> (f64, f64) {
+  let axis = |pos: i32, origin: i32, full: u32, size: u32| {
+    let room = full as i32 - size as i32;
+    if room <= 0 {
+      0.0
+    } else {
+      ((pos - origin) as f64 / room as f64).clamp(0.0, 1.0)
+    }
+  };
+  (
+    axis(x, monitor.x, monitor.width, size.width),
+    axis(y, monitor.y, monitor.height, size.height),
+  )
+}
+
+/// Where a snapshot goes given the connected monitors: its exact position if
+/// its monitor is still there, else the same relative spot on `fallback`.
+pub fn resolve_position(
+  snapshot: &WindowSnapshot,
+  monitors: &[(Option<String>, MonitorRect)],
+  fallback: MonitorRect,
+) -> (i32, i32) {
+  let present = snapshot.monitor.is_some()
+    && monitors.iter().any(|(name, _)| name == &snapshot.monitor);
+  if present {
+    return (snapshot.x, snapshot.y);
+  }
+  let room_x = (fallback.width as i32 - snapshot.width as i32).max(0);
+  let room_y = (fallback.height as i32 - snapshot.height as i32).max(0);
+  (
+    fallback.x + (snapshot.fraction.0 * room_x as f64).round() as i32,
+    fallback.y + (snapshot.fraction.1 * room_y as f64).round() as i32,
+  )
+}
+
+fn load_all(app: &AppHandle) -> BTreeMap<String, WindowSnapshot> {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(SNAPSHOTS_KEY))
+    .and_then(|value| serde_json::from_value(value).ok())
+    .unwrap_or_default()
+}
+
+fn save_all(app: &AppHandle, snapshots: &BTreeMap<String, WindowSnapshot>) -> Result<(), String> {
+  let store = profiles::settings_store(app).map_err(|e| e.to_string())?;
+  store.set(SNAPSHOTS_KEY, serde_json::to_value(snapshots).map_err(|e| e.to_string())?);
+  store.save().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn save_window_snapshot(app: AppHandle, name: String) -> Result<WindowSnapshot, AppError> {
+  audit::run(app.clone(), "save_window_snapshot", move || {
+    log::info!("save_window_snapshot: name={}", name);
+
+    profiles::validate_name(&name)?;
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let position = window.outer_position().map_err(|e| e.to_string())?;
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let monitor = window.current_monitor().map_err(|e| e.to_string())?;
+    let state = app.state::<AppState>();
+
+    let snapshot = WindowSnapshot {
+      x: position.x,
+      y: position.y,
+      width: size.width,
+      height: size.height,
+      monitor: monitor.as_ref().and_then(|m| m.name().cloned()),
+      fraction: monitor
+        .as_ref()
+        .map(|m| fraction_in(MonitorRect::from(m), position.x, position.y, size))
+        .unwrap_or((0.5, 0.5)),
+      mode: state.current_mode.lock().map_err(|e| e.to_string())?.clone(),
+      always_on_top: stacking::is_always_on_top(&window).map_err(|e| e.to_string())?,
+      placement: *state.last_placement.lock().map_err(|e| e.to_string())?,
+      saved_at: crate::now_ms(),
+    };
+
+    let mut snapshots = load_all(&app);
+    snapshots.insert(name, snapshot.clone());
+    save_all(&app, &snapshots)?;
+    Ok(snapshot)
+  })
+}
+
+/// Re-applies a named snapshot. Monitors are checked before anything moves,
+/// and a failure part-way puts the window back. Names with no snapshot fall
+/// back to the mode's saved custom position, as this command used to do.
+/// Returns false if there was nothing to restore.
+#[tauri::command]
+pub fn restore_window_snapshot(app: AppHandle, name: String) -> Result<bool, AppError> {
+  audit::run(app.clone(), "restore_window_snapshot", move || {
+    log::info!("restore_window_snapshot: name={}", name);
+
+    let Some(snapshot) = load_all(&app).remove(&name) else {
+      return crate::restore_mode_snapshot(&app, &name);
+    };
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+
+    let monitors: Vec<(Option<String>, MonitorRect)> = window
+      .available_monitors()
+      .map_err(|e| e.to_string())?
+      .iter()
+      .map(|m| (m.name().cloned(), MonitorRect::from(m)))
+      .collect();
+    let fallback = monitors::panel_monitor(&window)?
+      .or(window.primary_monitor().map_err(|e| e.to_string())?)
+      .map(|m| MonitorRect::from(&m))
+      .or_else(|| monitors.first().map(|(_, rect)| *rect))
+      .ok_or(AppError::MonitorNotFound)?;
+    let (x, y) = resolve_position(&snapshot, &monitors, fallback);
+
+    let state = app.state::<AppState>();
+    let before_position = window.outer_position().map_err(|e| e.to_string())?;
+    let before_size = window.outer_size().map_err(|e| e.to_string())?;
+    let before_on_top = stacking::is_always_on_top(&window).map_err(|e| e.to_string())?;
+    history::record(&app, &window);
+
+    autosave::suppress(&window);
+    let apply = || -> Result<(), String> {
+      let size = PhysicalSize { width: snapshot.width, height: snapshot.height };
+      window.set_size(Size::Physical(size)).map_err(|e| e.to_string())?;
+      window
+        .set_position(Position::Physical(PhysicalPosition { x, y }))
+        .map_err(|e| e.to_string())?;
+      stacking::set_always_on_top(&window, snapshot.always_on_top).map_err(|e| e.to_string())
+    };
+    if let Err(e) = apply() {
+      log::warn!("snapshot {} failed ({}); restoring previous window state", name, e);
+      let _ = window.set_size(Size::Physical(before_size));
+      let _ = window.set_position(Position::Physical(before_position));
+      let _ = stacking::set_always_on_top(&window, before_on_top);
+      return Err(e.into());
+    }
+
+    if let Some(p) = snapshot.placement {
+      placement::remember(&app, p);
+    }
+    *state.current_mode.lock().map_err(|e| e.to_string())? = snapshot.mode.clone();
+    let _ = journal::emit(&app, "panel-mode-changed", serde_json::json!({ "mode": snapshot.mode }));
+    Ok(true)
+  })
+}
+
+#[tauri::command]
+pub fn list_window_snapshots(app: AppHandle) -> Result<BTreeMap<String, WindowSnapshot>, AppError> {
+  audit::run(app.clone(), "list_window_snapshots", move || {
+    Ok(load_all(&app))
+  })
+}
+
+#[tauri::command]
+pub fn delete_window_snapshot(app: AppHandle, name: String) -> Result<bool, AppError> {
+  audit::run(app.clone(), "delete_window_snapshot", move || {
+    log::info!("delete_window_snapshot: name={}", name);
+
+    let mut snapshots = load_all(&app);
+    let existed = snapshots.remove(&name).is_some();
+    if existed {
+      save_all(&app, &snapshots)?;
+    }
+    Ok(existed)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const LAPTOP: MonitorRect = MonitorRect { x: 0, y: 0, width: 1440, height: 900 };
+  const EXTERNAL: MonitorRect = MonitorRect { x: 1440, y: 0, width: 2560, height: 1440 };
+
+  fn snapshot_on_external() -> WindowSnapshot {
+    let size = PhysicalSize { width: 560, height: 440 };
+    WindowSnapshot {
+      x: 2440,
+      y: 500,
+      width: size.width,
+      height: size.height,
+      monitor: Some("DELL".to_string()),
+      fraction: fraction_in(EXTERNAL, 2440, 500, size),
+      mode: "expanded".to_string(),
+      always_on_top: true,
+      placement: None,
+      saved_at: 0,
+    }
+  }
+
+  #[test]
+  fn exact_position_when_the_monitor_is_connected() {
+    let monitors = [(Some("DELL".to_string()), EXTERNAL), (None, LAPTOP)];
+    assert_eq!(resolve_position(&snapshot_on_external(), &monitors, LAPTOP), (2440, 500));
+  }
+
+  #[test]
+  fn missing_monitor_keeps_the_relative_spot() {
+    let snapshot = snapshot_on_external();
+    assert_eq!(snapshot.fraction, (0.5, 0.5));
+    assert_eq!(resolve_position(&snapshot, &[(None, LAPTOP)], LAPTOP), (440, 230));
+  }
+}
@@ -0,0 +1,354 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::state::AppState;
+use crate::{profiles, settings};
+
+pub const VISIBLE_ON_FULLSCREEN_KEY: &str = "visible_on_fullscreen";
+pub const SKIP_TASKBAR_KEY: &str = "skip_taskbar";
+pub const ON_TOP_LEVEL_KEY: &str = "always_on_top_level";
+
+/// How high an always-on-top panel floats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnTopLevel {
+  /// Above other apps' windows, below the taskbar, Dock and menu bar.
+  #[default]
+  Normal,
+  /// Above nearly everything. Only differs from `Normal` on macOS.
+  ScreenSaver,
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+  use objc2::rc::Retained;
+  use objc2::runtime::AnyObject;
+  use objc2::MainThreadMarker;
+  use objc2_app_kit::{
+    NSApplication, NSFloatingWindowLevel, NSNormalWindowLevel, NSRunningApplication,
+    NSScreenSaverWindowLevel, NSWindow, NSWindowCollectionBehavior, NSWindowOrderingMode,
+  };
+  use objc2_core_foundation::{CFRetained, CFString};
+  use objc2_core_graphics::{
+    kCGNullWindowID, kCGWindowLayer, kCGWindowNumber, kCGWindowOwnerPID,
+    CGWindowListCopyWindowInfo, CGWindowListOption,
+  };
+  use objc2_foundation::{NSArray, NSDictionary, NSNumber, NSString};
+
+  fn key(name: &CFString) -> &NSString {
+    // CFString and NSString are toll-free bridged.
+    unsafe { &*(name as *const CFString as *const NSString) }
+  }
+
+  fn number(info: &NSDictionary<NSString, AnyObject>, name: &CFString) -> Option<i64> {
+    let value = info.objectForKey(key(name))?;
+    Some(value.downcast_ref::<NSNumber>()?.as_i64())
+  }
+
+  /// Window number of the frontmost normal-level window owned by `pid`.
+  fn frontmost_window_of(pid: i32) -> Option<isize> {
+    let options =
+      CGWindowListOption::OptionOnScreenOnly | CGWindowListOption::ExcludeDesktopElements;
+    let list = CGWindowListCopyWindowInfo(options, kCGNullWindowID)?;
+    // CFArray of CFDictionary bridges to NSArray of NSDictionary; from_raw
+    // takes over the +1 from the Copy call.
+    let list: Retained<NSArray<NSDictionary<NSString, AnyObject>>> =
+      unsafe { Retained::from_raw(CFRetained::into_raw(list).as_ptr().cast()) }?;
+
+    let (owner_key, layer_key, number_key) =
+      unsafe { (kCGWindowOwnerPID, kCGWindowLayer, kCGWindowNumber) };
+
+    // The list is ordered front to back.
+    list.iter().find_map(|info| {
+      let owner = number(&info, owner_key)?;
+      let layer = number(&info, layer_key)?;
+      if owner == pid as i64 && layer == 0 {
+        number(&info, number_key).map(|n| n as isize)
+      } else {
+        None
+      }
+    })
+  }
+
+  /// Orders `ns_window` just above the target app's frontmost window.
+  /// Returns false if the app isn't running or has no visible window.
+  pub fn order_above(ns_window: *mut std::ffi::c_void, bundle_id: &str) -> bool {
+    let running =
+      NSRunningApplication::runningApplicationsWithBundleIdentifier(&NSString::from_str(bundle_id));
+    let Some(target) = running.iter().find(|app| !app.isTerminated()) else {
+      return false;
+    };
+    let Some(window_number) = frontmost_window_of(target.processIdentifier()) else {
+      return false;
+    };
+
+    let window = unsafe { &*(ns_window as *const NSWindow) };
+    window.setLevel(NSNormalWindowLevel);
+    window.orderWindow_relativeTo(NSWindowOrderingMode::Above, window_number);
+    true
+  }
+
+  pub fn set_screen_saver_level(ns_window: *mut std::ffi::c_void) {
+    let window = unsafe { &*(ns_window as *const NSWindow) };
+    window.setLevel(NSScreenSaverWindowLevel);
+  }
+
+  /// Brings the window up without activating the app or making the window
+  /// key, unhiding the app first if `hide_panel` hid it. Main thread only.
+  pub fn order_front_quietly(ns_window: *mut std::ffi::c_void) {
+    if let Some(mtm) = MainThreadMarker::new() {
+      NSApplication::sharedApplication(mtm).unhideWithoutActivation();
+    }
+    let window = unsafe { &*(ns_window as *const NSWindow) };
+    window.orderFrontRegardless();
+  }
+
+  /// Floating or higher; tao only counts exactly floating as on top.
+  pub fn is_on_top(ns_window: *mut std::ffi::c_void) -> bool {
+    let window = unsafe { &*(ns_window as *const NSWindow) };
+    window.level() >= NSFloatingWindowLevel
+  }
+
+  /// Lets the panel appear over other apps' fullscreen spaces.
+  pub fn set_fullscreen_auxiliary(ns_window: *mut std::ffi::c_void, enabled: bool) {
+    let window = unsafe { &*(ns_window as *const NSWindow) };
+    let mut behavior = window.collectionBehavior();
+    behavior.set(NSWindowCollectionBehavior::FullScreenAuxiliary, enabled);
+    window.setCollectionBehavior(behavior);
+  }
+}
+
+#[cfg(target_os = "macos")]
+fn load_level(app: &AppHandle) -> OnTopLevel {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(ON_TOP_LEVEL_KEY))
+    .and_then(|value| serde_json::from_value(value).ok())
+    .unwrap_or_default()
+}
+
+fn skip_taskbar(app: &AppHandle) -> bool {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(SKIP_TASKBAR_KEY))
+    .and_then(|value| value.as_bool())
+    .unwrap_or(settings::DEFAULT_SKIP_TASKBAR)
+}
+
+/// Use instead of `WebviewWindow::set_always_on_top`, which always picks the
+/// normal floating level, so an opted-in higher level sticks. While
+/// suspended this only records what to restore on resume.
+pub fn set_always_on_top(window: &WebviewWindow, on_top: bool) -> tauri::Result<()> {
+  if let Some(desired) = window.state::<AppState>().on_top_suspended.lock().unwrap().as_mut() {
+    *desired = on_top;
+    return Ok(());
+  }
+  #[cfg(target_os = "macos")]
+  if on_top && load_level(window.app_handle()) == OnTopLevel::ScreenSaver {
+    // Raw pointers aren't Send; the window outlives the closure.
+    let ns_window = window.ns_window()? as usize;
+    return window.run_on_main_thread(move || {
+      macos::set_screen_saver_level(ns_window as *mut std::ffi::c_void)
+    });
+  }
+  window.set_always_on_top(on_top)
+}
+
+/// Counterpart of `set_always_on_top`: the higher level counts as on top too,
+/// and so does a suspended panel that will be on top again.
+pub fn is_always_on_top(window: &WebviewWindow) -> tauri::Result<bool> {
+  if let Some(desired) = *window.state::<AppState>().on_top_suspended.lock().unwrap() {
+    return Ok(desired);
+  }
+  #[cfg(target_os = "macos")]
+  {
+    Ok(macos::is_on_top(window.ns_window()?))
+  }
+  #[cfg(not(target_os = "macos"))]
+  window.is_always_on_top()
+}
+
+/// Shows the panel without taking focus from the app in front. Linux has no
+/// way to ask for that, so there it's a plain show.
+pub fn show_without_focus(window: &WebviewWindow) -> tauri::Result<()> {
+  #[cfg(target_os = "macos")]
+  {
+    // Raw pointers aren't Send; the window outlives the closure.
+    let ns_window = window.ns_window()? as usize;
+    window.run_on_main_thread(move || {
+      macos::order_front_quietly(ns_window as *mut std::ffi::c_void)
+    })
+  }
+  #[cfg(target_os = "windows")]
+  {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{ShowWindow, SW_SHOWNOACTIVATE};
+
+    let hwnd = window.hwnd()?.0 as _;
+    unsafe { ShowWindow(hwnd, SW_SHOWNOACTIVATE) };
+    Ok(())
+  }
+  #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+  window.show()
+}
+
+/// Re-applies the stored taskbar flag and on-top level, during setup and
+/// after either setting changes.
+pub fn reapply(app: &AppHandle) {
+  let Some(window) = app.get_webview_window("panel") else {
+    return;
+  };
+  if let Err(e) = window.set_skip_taskbar(skip_taskbar(app)) {
+    log::warn!("could not set skip_taskbar: {}", e);
+  }
+  if is_always_on_top(&window).unwrap_or(false) {
+    let _ = set_always_on_top(&window, true);
+  }
+}
+
+#[cfg(target_os = "macos")]
+fn apply_fullscreen_visibility(app: &AppHandle, visible: bool) -> Result<(), String> {
+  let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+  let ns_window = window.ns_window().map_err(|e| e.to_string())?;
+  macos::set_fullscreen_auxiliary(ns_window, visible);
+  Ok(())
+}
+
+/// Re-applies the persisted fullscreen-spaces flag during setup.
+#[cfg(target_os = "macos")]
+pub fn restore_fullscreen_visibility(app: &AppHandle) {
+  let visible = profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(VISIBLE_ON_FULLSCREEN_KEY))
+    .and_then(|value| value.as_bool())
+    .unwrap_or(settings::DEFAULT_VISIBLE_ON_FULLSCREEN);
+  if visible {
+    if let Err(e) = apply_fullscreen_visibility(app, true) {
+      log::warn!("could not restore fullscreen visibility: {}", e);
+    }
+  }
+}
+
+/// Keeps the panel out of the taskbar (the default; it's a utility panel)
+/// or puts it back there.
+#[tauri::command]
+pub fn set_skip_taskbar(app: AppHandle, skip: bool) -> CommandResult {
+  audit::run(app.clone(), "set_skip_taskbar", move || {
+    log::info!("set_skip_taskbar: skip={}", skip);
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    window.set_skip_taskbar(skip).map_err(|e| e.to_string())?;
+    let store = profiles::settings_store(&app)?;
+    store.set(SKIP_TASKBAR_KEY, skip);
+    store.save()?;
+    Ok(ok_status())
+  })
+}
+
+/// Picks how high the panel floats while always-on-top. `normal` keeps it
+/// below the taskbar; `screen-saver` is the opt-in for above it all.
+#[tauri::command]
+pub fn set_always_on_top_level(app: AppHandle, level: OnTopLevel) -> CommandResult {
+  audit::run(app.clone(), "set_always_on_top_level", move || {
+    log::info!("set_always_on_top_level: level={:?}", level);
+
+    if level == OnTopLevel::ScreenSaver && cfg!(not(target_os = "macos")) {
+      log::debug!("screen-saver level is the same as normal on this platform");
+    }
+    let store = profiles::settings_store(&app)?;
+    store.set(ON_TOP_LEVEL_KEY, serde_json::to_value(level).map_err(|e| e.to_string())?);
+    store.save().map_err(|e| e.to_string())?;
+    reapply(&app);
+    Ok(ok_status())
+  })
+}
+
+/// Drops the panel below other windows, e.g. while a native file dialog is
+/// open, until `resume_always_on_top`. Calling it again while suspended does
+/// nothing.
+#[tauri::command]
+pub fn suspend_always_on_top(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "suspend_always_on_top", move || {
+    log::info!("suspend_always_on_top invoked");
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let state = app.state::<AppState>();
+    if state.on_top_suspended.lock().map_err(|e| e.to_string())?.is_some() {
+      return Ok(ok_status());
+    }
+    let desired = is_always_on_top(&window).map_err(|e| e.to_string())?;
+    window.set_always_on_top(false).map_err(|e| e.to_string())?;
+    *state.on_top_suspended.lock().map_err(|e| e.to_string())? = Some(desired);
+    Ok(ok_status())
+  })
+}
+
+/// Puts back the always-on-top state from before `suspend_always_on_top`,
+/// including any change requested meanwhile. A no-op when not suspended.
+#[tauri::command]
+pub fn resume_always_on_top(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "resume_always_on_top", move || {
+    log::info!("resume_always_on_top invoked");
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let desired =
+      app.state::<AppState>().on_top_suspended.lock().map_err(|e| e.to_string())?.take();
+    if let Some(on_top) = desired {
+      set_always_on_top(&window, on_top)?;
+    }
+    Ok(ok_status())
+  })
+}
+
+/// Puts the panel just above another app's windows instead of above
+/// everything. Returns Ok(false) if the target app isn't running.
+#[tauri::command]
+pub fn set_above_app(app: AppHandle, bundle_id: String) -> Result<bool, AppError> {
+  audit::run(app.clone(), "set_above_app", move || {
+    log::info!("set_above_app: bundle_id={}", bundle_id);
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+
+    #[cfg(target_os = "macos")]
+    {
+      window.set_always_on_top(false).map_err(|e| e.to_string())?;
+      let ns_window = window.ns_window().map_err(|e| e.to_string())?;
+      let placed = macos::order_above(ns_window, &bundle_id);
+      if !placed {
+        log::info!("{} is not running or has no visible windows", bundle_id);
+      }
+      Ok(placed)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+      let _ = window;
+      Err(AppError::PlatformUnsupported("set_above_app is only supported on macOS".to_string()))
+    }
+  })
+}
+
+#[tauri::command]
+pub fn set_visible_on_fullscreen_spaces(app: AppHandle, visible: bool) -> CommandResult {
+  audit::run(app.clone(), "set_visible_on_fullscreen_spaces", move || {
+    log::info!("set_visible_on_fullscreen_spaces: visible={}", visible);
+
+    #[cfg(not(target_os = "macos"))]
+    {
+      let _ = (app, visible);
+      Err(AppError::PlatformUnsupported(
+        "Fullscreen space visibility is only supported on macOS".to_string(),
+      ))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+      apply_fullscreen_visibility(&app, visible)?;
+      let store = profiles::settings_store(&app)?;
+      store.set(VISIBLE_ON_FULLSCREEN_KEY, visible);
+      store.save().map_err(|e| e.to_string())?;
+      Ok(ok_status())
+    }
+  })
+}
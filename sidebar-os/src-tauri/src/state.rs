@@ -0,0 +1,169 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Instant;
+
+use sysinfo::System;
+
+use crate::audit::CommandAuditLog;
+use crate::controller::Peek;
+use crate::double_tap::DoubleTap;
+use crate::history::WindowHistory;
+use crate::hot_corner::Corner;
+use crate::hotkey_capture::Capture;
+#[cfg(not(target_os = "windows"))]
+use crate::input_region::Rect;
+use crate::hotkeys::{Chord, PausedShortcut, ShortcutFailure};
+use crate::journal::EventJournal;
+use crate::pin::Pin;
+use crate::placement::Placement;
+use crate::position_store::PositionStore;
+use crate::rate_limit::LogRateLimit;
+
+/// Backend state shared across commands, registered with `Builder::manage`.
+pub struct AppState {
+  pub start_time: Instant,
+  pub event_journal: Mutex<EventJournal>,
+  // Every command invocation, recorded by `audit::audited`.
+  pub command_audit_log: Mutex<CommandAuditLog>,
+  pub hot_corner: Mutex<Option<Corner>>,
+  // Some while a modifier double tap summons the panel.
+  pub double_tap: Mutex<Option<DoubleTap>>,
+  pub active_profile: Mutex<String>,
+  pub current_mode: Mutex<String>,
+  // Anchor most recently applied to the panel, re-run by auto-recenter.
+  pub last_placement: Mutex<Option<Placement>>,
+  // Stands in for the settings store in the position commands when set; see
+  // `position_store::resolve`.
+  pub position_store: Mutex<Option<Arc<dyn PositionStore>>>,
+  pub window_history: Mutex<WindowHistory>,
+  // Some while the panel is pinned in place.
+  pub pin: Mutex<Option<Pin>>,
+  pub window_movable: Mutex<bool>,
+  // Some while minimized via `minimize_panel`; holds whether the panel was
+  // always-on-top before.
+  pub minimized: Mutex<Option<bool>>,
+  // Some while `suspend_always_on_top` has lowered the panel; holds whether
+  // it should be on top once resumed.
+  pub on_top_suspended: Mutex<Option<bool>>,
+  // Last monitor layout seen by the watcher; see `monitors_changed_since`.
+  pub monitor_fingerprint: Mutex<Option<u64>>,
+  // Move events before this are our own moves, not drags; see `autosave`.
+  pub autosave_suppressed_until: Mutex<Option<Instant>>,
+  // Interactive parts of the panel; None means all of it. Windows uses a
+  // native window region instead.
+  #[cfg(not(target_os = "windows"))]
+  pub input_region: Mutex<Option<Vec<Rect>>>,
+  #[cfg(not(target_os = "windows"))]
+  pub input_region_watcher: Mutex<bool>,
+  // Accelerators that registered successfully during setup.
+  pub registered_shortcuts: Mutex<Vec<String>>,
+  pub shortcut_failures: Mutex<Vec<ShortcutFailure>>,
+  // Accelerator → last registration error, cleared once it registers.
+  pub hotkey_errors: Mutex<BTreeMap<String, String>>,
+  // Some while `pause_all_shortcuts` is in effect.
+  pub paused_shortcuts: Mutex<Option<Vec<PausedShortcut>>>,
+  // Some while `suspend_hotkeys` is in effect; holds what to register again
+  // on resume.
+  pub suspended_hotkeys: Mutex<Option<Vec<PausedShortcut>>>,
+  pub chords: Mutex<Vec<Chord>>,
+  // (generation, second key) while a chord waits for its second key.
+  pub armed_chord: Mutex<Option<(u64, String)>>,
+  pub next_chord_arm: Mutex<u64>,
+  // Follows the panel's focus events; see `escape::track_focus`.
+  pub panel_focused: Mutex<bool>,
+  // Some while `start_hotkey_capture` is listening.
+  pub hotkey_capture: Mutex<Option<Capture>>,
+  pub next_capture: Mutex<u64>,
+  // Generation of the quit request waiting for confirmation; see
+  // `quit::request`.
+  pub pending_quit: Mutex<Option<u64>>,
+  pub next_quit_request: Mutex<u64>,
+  // The quit accelerator while it's registered because the panel has focus;
+  // see `quit::sync_quit_key`.
+  pub quit_key_bound: Mutex<Option<String>>,
+  // Last show-panel press that did something; see `hotkeys::summon`.
+  pub last_summon: Mutex<Option<Instant>>,
+  // Some while the peek-panel hotkey is held; see `controller::start_peek`.
+  pub peek: Mutex<Option<Peek>>,
+  pub next_peek: Mutex<u64>,
+  // Set once the webview has its listeners up (see `frontend_ready`).
+  pub frontend_ready: Mutex<bool>,
+  // True if this launch set `has_run_before`.
+  pub first_run: Mutex<bool>,
+  pub tray_tooltip: Mutex<String>,
+  // Tooltip to restore once the busy indicator is cleared; Some while busy.
+  pub tray_busy: Mutex<Option<String>>,
+  // Kept between calls so CPU usage can be measured as a delta.
+  pub system: Mutex<System>,
+  // Serializes read-modify-write of the usage counters in the store.
+  pub usage_lock: Mutex<()>,
+  // Secret values seen this session, scrubbed from webview log lines.
+  pub known_secrets: Mutex<Vec<String>>,
+  // Throttles `debug_log` and counts the lines it dropped.
+  pub debug_log_limit: Mutex<LogRateLimit>,
+  // execute_js calls waiting on the webview, keyed by script id.
+  pub pending_scripts: Mutex<HashMap<u64, mpsc::Sender<Result<String, String>>>>,
+  pub next_script_id: Mutex<u64>,
+  // Mirrors the stored `debug_mode` once applied; see `webview::debug_mode`.
+  pub debug_mode: Mutex<bool>,
+  // Whether `toggle_devtools` last opened the inspector.
+  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub devtools_open: Mutex<bool>,
+}
+
+impl Default for AppState {
+  fn default() -> Self {
+    Self {
+      start_time: Instant::now(),
+      event_journal: Mutex::default(),
+      command_audit_log: Mutex::default(),
+      hot_corner: Mutex::default(),
+      double_tap: Mutex::default(),
+      active_profile: Mutex::new(crate::profiles::DEFAULT_PROFILE.to_string()),
+      current_mode: Mutex::new("default".to_string()),
+      last_placement: Mutex::default(),
+      position_store: Mutex::default(),
+      window_history: Mutex::default(),
+      pin: Mutex::default(),
+      window_movable: Mutex::new(crate::settings::DEFAULT_WINDOW_MOVABLE),
+      minimized: Mutex::default(),
+      on_top_suspended: Mutex::default(),
+      monitor_fingerprint: Mutex::default(),
+      autosave_suppressed_until: Mutex::default(),
+      #[cfg(not(target_os = "windows"))]
+      input_region: Mutex::default(),
+      #[cfg(not(target_os = "windows"))]
+      input_region_watcher: Mutex::default(),
+      registered_shortcuts: Mutex::default(),
+      shortcut_failures: Mutex::default(),
+      hotkey_errors: Mutex::default(),
+      paused_shortcuts: Mutex::default(),
+      suspended_hotkeys: Mutex::default(),
+      chords: Mutex::default(),
+      armed_chord: Mutex::default(),
+      next_chord_arm: Mutex::default(),
+      hotkey_capture: Mutex::default(),
+      next_capture: Mutex::default(),
+      pending_quit: Mutex::default(),
+      next_quit_request: Mutex::default(),
+      quit_key_bound: Mutex::default(),
+      last_summon: Mutex::default(),
+      peek: Mutex::default(),
+      next_peek: Mutex::default(),
+      panel_focused: Mutex::default(),
+      frontend_ready: Mutex::default(),
+      first_run: Mutex::default(),
+      tray_tooltip: Mutex::new(crate::tray::DEFAULT_TOOLTIP.to_string()),
+      tray_busy: Mutex::default(),
+      system: Mutex::new(System::new()),
+      usage_lock: Mutex::default(),
+      known_secrets: Mutex::default(),
+      debug_log_limit: Mutex::default(),
+      pending_scripts: Mutex::default(),
+      next_script_id: Mutex::default(),
+      debug_mode: Mutex::default(),
+      #[cfg(any(debug_assertions, feature = "devtools"))]
+      devtools_open: Mutex::default(),
+    }
+  }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Theme, WindowEvent};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::{journal, profiles, tray};
+
+pub const THEME_KEY: &str = "theme";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemePreference {
+  #[default]
+  System,
+  Light,
+  Dark,
+}
+
+impl ThemePreference {
+  /// Theme hint for the window; None follows the OS.
+  pub fn hint(self) -> Option<Theme> {
+    match self {
+      ThemePreference::System => None,
+      ThemePreference::Light => Some(Theme::Light),
+      ThemePreference::Dark => Some(Theme::Dark),
+    }
+  }
+}
+
+pub fn load(app: &AppHandle) -> ThemePreference {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(THEME_KEY))
+    .and_then(|value| serde_json::from_value(value).ok())
+    .unwrap_or_default()
+}
+
+fn emit_changed(app: &AppHandle, preference: ThemePreference, effective: Option<Theme>) {
+  let _ = journal::emit(
+    app,
+    "theme-changed",
+    serde_json::json!({ "theme": preference, "effective": effective }),
+  );
+}
+
+/// Applies the preference to the window, plus the tray icon: a template icon
+/// (tinted by macOS to match the menu bar) when following the system, the
+/// colored one when a theme is forced.
+fn apply(app: &AppHandle, preference: ThemePreference) -> Option<Theme> {
+  let window = app.get_webview_window("panel");
+  if let Some(window) = &window {
+    if let Err(e) = window.set_theme(preference.hint()) {
+      log::warn!("could not set window theme: {}", e);
+    }
+  }
+  if let Some(icon) = app.tray_by_id(tray::TRAY_ID) {
+    let _ = icon.set_icon_as_template(preference == ThemePreference::System);
+  }
+  window.and_then(|w| w.theme().ok())
+}
+
+/// The panel's current appearance, "light" or "dark" once serialized. None
+/// if the panel window isn't there.
+pub fn effective(app: &AppHandle) -> Option<Theme> {
+  app.get_webview_window("panel").and_then(|w| w.theme().ok())
+}
+
+/// Re-applies the stored theme after it was changed some other way (e.g.
+/// `save_settings` or a profile switch).
+pub fn reapply(app: &AppHandle) {
+  let preference = load(app);
+  let effective = apply(app, preference);
+  emit_changed(app, preference, effective);
+}
+
+/// Applies the stored theme during setup and forwards OS appearance changes
+/// while the preference is `system`.
+pub fn restore_theme(app: &AppHandle) {
+  apply(app, load(app));
+
+  if let Some(window) = app.get_webview_window("panel") {
+    let handle = app.clone();
+    window.on_window_event(move |event| {
+      if let WindowEvent::ThemeChanged(theme) = event {
+        let preference = load(&handle);
+        if preference == ThemePreference::System {
+          log::debug!("system appearance changed to {:?}", theme);
+          emit_changed(&handle, preference, Some(*theme));
+        }
+      }
+    });
+  }
+}
+
+#[tauri::command]
+pub fn get_theme(app: AppHandle) -> Result<ThemePreference, AppError> {
+  audit::run(app.clone(), "get_theme", move || {
+    Ok(load(&app))
+  })
+}
+
+/// What the panel looks like right now, as opposed to the stored preference
+/// `get_theme` returns. `theme-changed` carries the same value as `effective`.
+#[tauri::command]
+pub fn get_effective_theme(app: AppHandle) -> Result<Theme, AppError> {
+  audit::run(app.clone(), "get_effective_theme", move || {
+    effective(&app).ok_or(AppError::WindowNotFound)
+  })
+}
+
+#[tauri::command]
+pub fn set_theme(app: AppHandle, theme: ThemePreference) -> CommandResult {
+  audit::run(app.clone(), "set_theme", move || {
+    log::info!("set_theme: theme={:?}", theme);
+
+    let store = profiles::settings_store(&app)?;
+    store.set(THEME_KEY, serde_json::to_value(theme).map_err(|e| e.to_string())?);
+    store.save().map_err(|e| e.to_string())?;
+
+    let effective = apply(&app, theme);
+    emit_changed(&app, theme, effective);
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_only_known_themes() {
+    let parse = |s: &str| serde_json::from_value::<ThemePreference>(serde_json::json!(s)).ok();
+    assert_eq!(parse("dark"), Some(ThemePreference::Dark));
+    assert_eq!(parse("system"), Some(ThemePreference::System));
+    assert_eq!(parse("sepia"), None);
+    assert_eq!(ThemePreference::System.hint(), None);
+  }
+}
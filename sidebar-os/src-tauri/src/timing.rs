@@ -0,0 +1,29 @@
+use std::time::Instant;
+
+use tauri::ipc::Invoke;
+use tauri::Runtime;
+
+// Log target for command timings, next to the `webview` target used for
+// forwarded webview logs.
+pub const TARGET: &str = "commands";
+
+/// Wraps the invoke handler so every command logs when it starts and how
+/// long it took. Sync commands run inside the handler, so their time is the
+/// real one; async commands (`execute_js`) only count until they're spawned.
+pub fn timed<R: Runtime>(
+  handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+  move |invoke| {
+    let command = invoke.message.command().to_string();
+    log::debug!(target: TARGET, "command {} started", command);
+    let started = Instant::now();
+    let handled = handler(invoke);
+    log::debug!(
+      target: TARGET,
+      "command {} finished in {}ms",
+      command,
+      started.elapsed().as_millis()
+    );
+    handled
+  }
+}
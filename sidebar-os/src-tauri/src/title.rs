@@ -0,0 +1,75 @@
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::profiles;
+
+pub const WINDOW_TITLE_KEY: &str = "window_title";
+const MAX_TITLE_LEN: usize = 200;
+
+pub fn validate_title(title: &str) -> Result<(), String> {
+  if title.trim().is_empty() {
+    return Err("Window title must not be empty".to_string());
+  }
+  if title.chars().count() > MAX_TITLE_LEN {
+    return Err(format!("Window title is longer than {} characters", MAX_TITLE_LEN));
+  }
+  Ok(())
+}
+
+// The panel is borderless, so VoiceOver reads the content view rather than
+// the title bar; label it too.
+#[cfg(target_os = "macos")]
+fn apply_accessibility_label(window: &WebviewWindow, title: &str) -> Result<(), String> {
+  use objc2_app_kit::{NSAccessibility, NSWindow};
+  use objc2_foundation::NSString;
+
+  let ns_window = window.ns_window().map_err(|e| e.to_string())?;
+  let ns_window = unsafe { &*(ns_window as *const NSWindow) };
+  if let Some(view) = ns_window.contentView() {
+    view.setAccessibilityLabel(Some(&NSString::from_str(title)));
+  }
+  Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn apply_accessibility_label(_window: &WebviewWindow, _title: &str) -> Result<(), String> {
+  Ok(())
+}
+
+fn apply_title(window: &WebviewWindow, title: &str) -> Result<(), String> {
+  window.set_title(title).map_err(|e| e.to_string())?;
+  apply_accessibility_label(window, title)
+}
+
+/// Re-applies a persisted title during setup. Without one the window keeps
+/// the title from the config.
+pub fn restore_title(app: &AppHandle) {
+  let title = profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(WINDOW_TITLE_KEY))
+    .and_then(|value| value.as_str().map(str::to_string));
+  if let (Some(title), Some(window)) = (title, app.get_webview_window("panel")) {
+    if let Err(e) = apply_title(&window, &title) {
+      log::warn!("could not restore window title: {}", e);
+    }
+  }
+}
+
+/// Sets the panel's title (shown in window-manager menus and read by screen
+/// readers) and remembers it.
+#[tauri::command]
+pub fn set_window_title(app: AppHandle, title: String) -> CommandResult {
+  audit::run(app.clone(), "set_window_title", move || {
+    log::info!("set_window_title: title={}", title);
+
+    validate_title(&title).map_err(AppError::InvalidArgument)?;
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    apply_title(&window, &title)?;
+
+    let store = profiles::settings_store(&app)?;
+    store.set(WINDOW_TITLE_KEY, title);
+    store.save()?;
+    Ok(ok_status())
+  })
+}
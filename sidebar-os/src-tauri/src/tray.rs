@@ -0,0 +1,145 @@
+use tauri::image::Image;
+use tauri::menu::{Menu, MenuBuilder, MenuItemBuilder, SubmenuBuilder};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::audit;
+use crate::error::{ok_status, CommandResult};
+use crate::hotkeys;
+use crate::layouts;
+use crate::state::AppState;
+
+pub const TRAY_ID: &str = "tray";
+// Menu ids for the Modes submenu are the mode name behind this prefix.
+const MODE_ITEM_PREFIX: &str = "mode:";
+pub const HOTKEYS_ITEM_ID: &str = "hotkeys";
+pub const DEFAULT_TOOLTIP: &str = "Demo AI - Click to Show";
+const BUSY_TOOLTIP: &str = "Working…";
+
+/// Sets the tray tooltip and remembers it so a busy indicator can restore it.
+pub fn set_tooltip(app: &AppHandle, tooltip: &str) -> Result<(), String> {
+  let tray = app.tray_by_id(TRAY_ID).ok_or("Tray icon not found")?;
+  tray.set_tooltip(Some(tooltip)).map_err(|e| e.to_string())?;
+  *app.state::<AppState>().tray_tooltip.lock().map_err(|e| e.to_string())? = tooltip.to_string();
+  Ok(())
+}
+
+/// The mode a tray menu item switches to, if it's one of the Modes items.
+pub fn mode_for_menu_id(id: &str) -> Option<&str> {
+  id.strip_prefix(MODE_ITEM_PREFIX)
+}
+
+/// Label for the item that suspends or resumes the global hotkeys.
+pub fn hotkeys_item_label(suspended: bool) -> &'static str {
+  if suspended {
+    "Resume Hotkeys"
+  } else {
+    "Suspend Hotkeys"
+  }
+}
+
+/// Show, a Modes submenu listing `modes`, the hotkeys toggle, and Quit.
+pub fn build_menu(app: &AppHandle, modes: &[String]) -> tauri::Result<Menu<Wry>> {
+  let mut submenu = SubmenuBuilder::with_id(app, "modes", "Modes");
+  if modes.is_empty() {
+    submenu = submenu.item(&MenuItemBuilder::new("No saved modes").enabled(false).build(app)?);
+  }
+  for mode in modes {
+    let id = format!("{}{}", MODE_ITEM_PREFIX, mode);
+    submenu = submenu.item(&MenuItemBuilder::with_id(id, mode).build(app)?);
+  }
+
+  let show_item = MenuItemBuilder::with_id("show", "Show Window").build(app)?;
+  let hotkeys_label = hotkeys_item_label(hotkeys::suspended(app));
+  let hotkeys_item = MenuItemBuilder::with_id(HOTKEYS_ITEM_ID, hotkeys_label).build(app)?;
+  let quit_item = MenuItemBuilder::with_id("quit", "Quit").build(app)?;
+  MenuBuilder::new(app)
+    .item(&show_item)
+    .item(&submenu.build()?)
+    .item(&hotkeys_item)
+    .separator()
+    .item(&quit_item)
+    .build()
+}
+
+/// Rebuilds the tray menu so the Modes submenu matches the stored modes and
+/// the hotkeys item matches `hotkeys::suspended`. Called whenever either
+/// changes.
+pub fn rebuild_menu(app: &AppHandle) -> Result<(), String> {
+  let tray = app.tray_by_id(TRAY_ID).ok_or("Tray icon not found")?;
+  let menu = build_menu(app, &layouts::mode_names(app)).map_err(|e| e.to_string())?;
+  tray.set_menu(Some(menu)).map_err(|e| e.to_string())
+}
+
+/// Like `rebuild_menu`, for callers that shouldn't fail over it.
+pub fn refresh_menu(app: &AppHandle) {
+  if let Err(e) = rebuild_menu(app) {
+    log::warn!("could not rebuild tray menu: {}", e);
+  }
+}
+
+// Dimmed copy of the app icon so the busy state reads at a glance without
+// shipping a second asset.
+fn busy_icon(icon: &Image<'_>) -> Image<'static> {
+  let rgba = icon
+    .rgba()
+    .chunks_exact(4)
+    .flat_map(|px| [px[0], px[1], px[2], px[3] / 2])
+    .collect::<Vec<u8>>();
+  Image::new_owned(rgba, icon.width(), icon.height())
+}
+
+#[tauri::command]
+pub fn set_busy(app: AppHandle, busy: bool) -> CommandResult {
+  audit::run(app.clone(), "set_busy", move || {
+    log::info!("set_busy: busy={}", busy);
+
+    let tray = app.tray_by_id(TRAY_ID).ok_or("Tray icon not found")?;
+    let icon = app.default_window_icon().cloned();
+    let state = app.state::<AppState>();
+    let mut previous = state.tray_busy.lock().map_err(|e| e.to_string())?;
+
+    if busy {
+      if previous.is_some() {
+        return Ok(ok_status());
+      }
+      let tooltip = state.tray_tooltip.lock().map_err(|e| e.to_string())?.clone();
+      tray.set_tooltip(Some(BUSY_TOOLTIP)).map_err(|e| e.to_string())?;
+      if let Some(icon) = icon.as_ref() {
+        tray.set_icon(Some(busy_icon(icon))).map_err(|e| e.to_string())?;
+      }
+      *previous = Some(tooltip);
+    } else if let Some(tooltip) = previous.take() {
+      tray.set_tooltip(Some(tooltip)).map_err(|e| e.to_string())?;
+      tray.set_icon(icon).map_err(|e| e.to_string())?;
+    }
+
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn busy_icon_halves_alpha_only() {
+    let icon = Image::new_owned(vec![10, 20, 30, 255, 1, 2, 3, 0], 2, 1);
+    let busy = busy_icon(&icon);
+
+    assert_eq!(busy.rgba(), &[10, 20, 30, 127, 1, 2, 3, 0]);
+    assert_eq!((busy.width(), busy.height()), (2, 1));
+  }
+
+  #[test]
+  fn hotkeys_item_offers_the_opposite_of_the_current_state() {
+    assert_eq!(hotkeys_item_label(false), "Suspend Hotkeys");
+    assert_eq!(hotkeys_item_label(true), "Resume Hotkeys");
+  }
+
+  #[test]
+  fn mode_items_carry_the_mode_name() {
+    assert_eq!(mode_for_menu_id("mode:reading"), Some("reading"));
+    assert_eq!(mode_for_menu_id("mode:"), Some(""));
+    assert_eq!(mode_for_menu_id("show"), None);
+  }
+}
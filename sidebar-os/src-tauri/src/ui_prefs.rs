@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::audit;
+use crate::error::{ok_status, AppError};
+use crate::profiles;
+
+/// Store prefix for webview preferences. These keys are owned by the
+/// frontend, so `save_settings` doesn't accept them, but they live in the
+/// profile's store and travel with it when it's copied.
+pub const UI_PREFIX: &str = "ui.";
+pub const MAX_KEYS: usize = 100;
+pub const MAX_VALUE_BYTES: usize = 4096;
+const MAX_KEY_LEN: usize = 64;
+
+/// Why a pref write was refused. Every case is the caller's fault, so they
+/// all go back as `INVALID_ARGUMENT`.
+#[derive(Debug, PartialEq)]
+pub enum UiPrefError {
+  InvalidKey(String),
+  InvalidValue(String),
+  ValueTooLarge(String),
+  TooManyKeys(String),
+}
+
+impl From<UiPrefError> for AppError {
+  fn from(e: UiPrefError) -> Self {
+    match e {
+      UiPrefError::InvalidKey(message)
+      | UiPrefError::InvalidValue(message)
+      | UiPrefError::ValueTooLarge(message)
+      | UiPrefError::TooManyKeys(message) => AppError::InvalidArgument(message),
+    }
+  }
+}
+
+fn pref_key(key: &str) -> String {
+  format!("{}{}", UI_PREFIX, key)
+}
+
+fn check_key(key: &str) -> Result<(), UiPrefError> {
+  if key.is_empty() || key.len() > MAX_KEY_LEN || key.chars().any(char::is_whitespace) {
+    return Err(UiPrefError::InvalidKey(format!(
+      "Pref keys must be 1-{} characters without whitespace",
+      MAX_KEY_LEN
+    )));
+  }
+  Ok(())
+}
+
+/// Checks a write against the value rules and quotas. `key_count` is how many
+/// prefs are stored already; overwriting an existing key never hits the cap.
+pub fn check_write(
+  key: &str,
+  value: &Value,
+  key_count: usize,
+  exists: bool,
+) -> Result<(), UiPrefError> {
+  check_key(key)?;
+  if !matches!(value, Value::String(_) | Value::Number(_) | Value::Bool(_) | Value::Object(_)) {
+    return Err(UiPrefError::InvalidValue(
+      "Prefs must be a string, number, boolean or object".to_string(),
+    ));
+  }
+  let size = value.to_string().len();
+  if size > MAX_VALUE_BYTES {
+    return Err(UiPrefError::ValueTooLarge(format!(
+      "{} bytes is over the {} byte limit",
+      size, MAX_VALUE_BYTES
+    )));
+  }
+  if !exists && key_count >= MAX_KEYS {
+    return Err(UiPrefError::TooManyKeys(format!("At most {} prefs can be stored", MAX_KEYS)));
+  }
+  Ok(())
+}
+
+#[tauri::command]
+pub fn ui_pref_set(app: AppHandle, key: String, value: Value) -> Result<Value, AppError> {
+  audit::run(app.clone(), "ui_pref_set", move || {
+    log::info!("ui_pref_set: key={}", key);
+
+    let store = profiles::settings_store(&app)?;
+    let key_count = store.keys().iter().filter(|k| k.starts_with(UI_PREFIX)).count();
+    check_write(&key, &value, key_count, store.has(pref_key(&key)))?;
+
+    store.set(pref_key(&key), value);
+    store.save()?;
+    Ok(ok_status())
+  })
+}
+
+#[tauri::command]
+pub fn ui_pref_get(app: AppHandle, key: String) -> Result<Option<Value>, AppError> {
+  audit::run(app.clone(), "ui_pref_get", move || {
+    check_key(&key)?;
+    let store = profiles::settings_store(&app)?;
+    Ok(store.get(pref_key(&key)))
+  })
+}
+
+/// Every stored pref, keyed without the `ui.` prefix.
+#[tauri::command]
+pub fn ui_pref_list(app: AppHandle) -> Result<BTreeMap<String, Value>, AppError> {
+  audit::run(app.clone(), "ui_pref_list", move || {
+    let store = profiles::settings_store(&app)?;
+    Ok(
+      store
+        .entries()
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.strip_prefix(UI_PREFIX)?.to_string(), value)))
+        .collect(),
+    )
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn accepts_small_scalars_and_objects() {
+    assert_eq!(check_write("sidebarTab", &json!("notes"), 0, false), Ok(()));
+    assert_eq!(check_write("accent", &json!({ "hue": 210 }), 0, false), Ok(()));
+    let null = check_write("x", &json!(null), 0, false);
+    assert!(matches!(null, Err(UiPrefError::InvalidValue(_))));
+    let list = check_write("x", &json!([1]), 0, false);
+    assert!(matches!(list, Err(UiPrefError::InvalidValue(_))));
+    let spaced = check_write("a b", &json!(1), 0, false);
+    assert!(matches!(spaced, Err(UiPrefError::InvalidKey(_))));
+  }
+
+  #[test]
+  fn enforces_quotas() {
+    let big = json!("x".repeat(MAX_VALUE_BYTES));
+    assert!(matches!(check_write("k", &big, 0, false), Err(UiPrefError::ValueTooLarge(_))));
+
+    let full = check_write("new", &json!(1), MAX_KEYS, false);
+    assert!(matches!(full, Err(UiPrefError::TooManyKeys(_))));
+    // Overwriting is fine at the cap.
+    assert_eq!(check_write("old", &json!(1), MAX_KEYS, true), Ok(()));
+  }
+}
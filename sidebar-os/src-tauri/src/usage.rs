@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::profiles;
+use crate::state::AppState;
+
+/// Local-only usage counters. Nothing here ever leaves the machine.
+pub const USAGE_KEY: &str = "usage";
+// Counters stop here instead of wrapping.
+pub const MAX_COUNT: u64 = u32::MAX as u64;
+
+/// What brought the panel up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShowTrigger {
+  Launch,
+  Hotkey,
+  Tray,
+  SingleInstance,
+  HotCorner,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TriggerCounts {
+  #[serde(default)]
+  pub launch: u64,
+  #[serde(default)]
+  pub hotkey: u64,
+  #[serde(default)]
+  pub tray: u64,
+  #[serde(default)]
+  pub single_instance: u64,
+  #[serde(default)]
+  pub hot_corner: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageStats {
+  #[serde(default)]
+  pub shows: u64,
+  #[serde(default)]
+  pub triggers: TriggerCounts,
+  #[serde(default)]
+  pub last_shown_ms: Option<u64>,
+}
+
+impl UsageStats {
+  pub fn record(&mut self, trigger: ShowTrigger, now_ms: u64) {
+    let bump = |count: &mut u64| *count = count.saturating_add(1).min(MAX_COUNT);
+    bump(&mut self.shows);
+    bump(match trigger {
+      ShowTrigger::Launch => &mut self.triggers.launch,
+      ShowTrigger::Hotkey => &mut self.triggers.hotkey,
+      ShowTrigger::Tray => &mut self.triggers.tray,
+      ShowTrigger::SingleInstance => &mut self.triggers.single_instance,
+      ShowTrigger::HotCorner => &mut self.triggers.hot_corner,
+    });
+    self.last_shown_ms = Some(now_ms);
+  }
+}
+
+fn load(app: &AppHandle) -> UsageStats {
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(USAGE_KEY))
+    .and_then(|value| serde_json::from_value(value).ok())
+    .unwrap_or_default()
+}
+
+/// Counts a show. Only `set`s the store, leaving the write to its debounced
+/// auto-save so rapid triggers don't each hit the disk.
+pub fn record_show(app: &AppHandle, trigger: ShowTrigger) {
+  let state = app.state::<AppState>();
+  // Held across the read-modify-write so concurrent triggers can't lose counts.
+  let _guard = state.usage_lock.lock().unwrap();
+  let mut stats = load(app);
+  stats.record(trigger, crate::now_ms());
+  if let (Ok(store), Ok(value)) = (profiles::settings_store(app), serde_json::to_value(&stats)) {
+    store.set(USAGE_KEY, value);
+  }
+}
+
+#[tauri::command]
+pub fn get_usage_stats(app: AppHandle) -> Result<UsageStats, AppError> {
+  audit::run(app.clone(), "get_usage_stats", move || {
+    Ok(load(&app))
+  })
+}
+
+#[tauri::command]
+pub fn reset_usage_stats(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "reset_usage_stats", move || {
+    log::info!("reset_usage_stats invoked");
+
+    let state = app.state::<AppState>();
+    let _guard = state.usage_lock.lock().map_err(|e| e.to_string())?;
+    let store = profiles::settings_store(&app)?;
+    store.delete(USAGE_KEY);
+    store.save()?;
+    Ok(ok_status())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn counts_shows_per_trigger() {
+    let mut stats = UsageStats::default();
+    stats.record(ShowTrigger::Hotkey, 10);
+    stats.record(ShowTrigger::Hotkey, 20);
+    stats.record(ShowTrigger::Tray, 30);
+    assert_eq!(stats.shows, 3);
+    assert_eq!((stats.triggers.hotkey, stats.triggers.tray), (2, 1));
+    assert_eq!(stats.last_shown_ms, Some(30));
+  }
+
+  #[test]
+  fn counters_stop_at_the_cap() {
+    let mut stats = UsageStats { shows: MAX_COUNT, ..Default::default() };
+    stats.triggers.launch = u64::MAX;
+    stats.record(ShowTrigger::Launch, 1);
+    assert_eq!(stats.shows, MAX_COUNT);
+    assert_eq!(stats.triggers.launch, MAX_COUNT);
+  }
+}
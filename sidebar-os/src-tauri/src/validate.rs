@@ -0,0 +1,171 @@
+use crate::error::AppError;
+use crate::history::WindowGeometry;
+use crate::hotkeys::{self, HotkeyConfig, ShortcutAction};
+use crate::input_region::Rect;
+use crate::layouts::LayoutProfile;
+use crate::placement::Placement;
+use crate::WindowPos;
+
+// Well past any real desktop, even a wall of 8K monitors; anything beyond
+// is a unit mix-up or garbage from the frontend.
+pub const MAX_COORDINATE: i32 = 100_000;
+
+/// Checks on a command's input beyond what deserializing it already caught.
+/// Commands call it before touching anything.
+pub trait Validate {
+  fn validate(&self) -> Result<(), AppError>;
+}
+
+impl<T: Validate> Validate for [T] {
+  fn validate(&self) -> Result<(), AppError> {
+    self.iter().try_for_each(Validate::validate)
+  }
+}
+
+fn check_point(x: i32, y: i32) -> Result<(), AppError> {
+  // `unsigned_abs`, since `i32::MIN.abs()` overflows.
+  if x.unsigned_abs() > MAX_COORDINATE as u32 || y.unsigned_abs() > MAX_COORDINATE as u32 {
+    return Err(AppError::InvalidArgument(format!("Position out of range: ({}, {})", x, y)));
+  }
+  Ok(())
+}
+
+fn check_size(width: u32, height: u32) -> Result<(), AppError> {
+  if width == 0 || height == 0 || width > MAX_COORDINATE as u32 || height > MAX_COORDINATE as u32 {
+    return Err(AppError::InvalidArgument(format!("Size out of range: {}x{}", width, height)));
+  }
+  Ok(())
+}
+
+impl Validate for WindowPos {
+  fn validate(&self) -> Result<(), AppError> {
+    check_point(self.x, self.y)?;
+    match (self.width, self.height) {
+      (Some(width), Some(height)) => check_size(width, height),
+      (None, None) => Ok(()),
+      _ => Err(AppError::InvalidArgument("Width and height go together".to_string())),
+    }
+  }
+}
+
+impl Validate for WindowGeometry {
+  fn validate(&self) -> Result<(), AppError> {
+    check_point(self.x, self.y)?;
+    check_size(self.width, self.height)
+  }
+}
+
+impl Validate for Rect {
+  fn validate(&self) -> Result<(), AppError> {
+    check_point(self.x, self.y)?;
+    check_size(self.width, self.height)
+  }
+}
+
+impl Validate for Placement {
+  fn validate(&self) -> Result<(), AppError> {
+    if self.margin.unsigned_abs() > MAX_COORDINATE as u32 {
+      return Err(AppError::InvalidArgument(format!("Margin out of range: {}", self.margin)));
+    }
+    Ok(())
+  }
+}
+
+impl Validate for LayoutProfile {
+  fn validate(&self) -> Result<(), AppError> {
+    self.placement.validate()?;
+    match (self.width, self.height) {
+      (Some(width), Some(height)) => check_size(width, height),
+      (Some(size), None) | (None, Some(size)) => check_size(size, size),
+      (None, None) => Ok(()),
+    }
+  }
+}
+
+// Deserializing already refuses unknown action names, so every value is a
+// known action.
+impl Validate for ShortcutAction {
+  fn validate(&self) -> Result<(), AppError> {
+    Ok(())
+  }
+}
+
+impl Validate for HotkeyConfig {
+  fn validate(&self) -> Result<(), AppError> {
+    hotkeys::validate(self).map_err(AppError::InvalidArgument)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn window_pos(value: serde_json::Value) -> WindowPos {
+    serde_json::from_value(value).unwrap()
+  }
+
+  #[test]
+  fn window_positions_need_sane_coordinates_and_a_whole_size() {
+    assert!(window_pos(serde_json::json!({ "x": -1920, "y": 0 })).validate().is_ok());
+    let sized = serde_json::json!({ "x": 10, "y": 20, "width": 400, "height": 600 });
+    assert!(window_pos(sized).validate().is_ok());
+
+    assert!(window_pos(serde_json::json!({ "x": 1_000_000, "y": 0 })).validate().is_err());
+    assert!(window_pos(serde_json::json!({ "x": i32::MIN, "y": 0 })).validate().is_err());
+    assert!(window_pos(serde_json::json!({ "x": 0, "y": i32::MIN })).validate().is_err());
+    assert!(window_pos(serde_json::json!({ "x": 0, "y": 0, "width": 400 })).validate().is_err());
+    let empty = serde_json::json!({ "x": 0, "y": 0, "width": 0, "height": 10 });
+    assert!(window_pos(empty).validate().is_err());
+  }
+
+  #[test]
+  fn geometry_needs_a_size() {
+    let geometry = |width| WindowGeometry {
+      x: 0,
+      y: 0,
+      width,
+      height: 300,
+      monitor: None,
+      always_on_top: true,
+    };
+    assert!(geometry(400).validate().is_ok());
+    assert!(geometry(0).validate().is_err());
+  }
+
+  #[test]
+  fn rect_lists_fail_on_any_bad_rect() {
+    let ok = Rect { x: 0, y: 0, width: 10, height: 10 };
+    let off = Rect { x: -200_000, y: 0, width: 10, height: 10 };
+    assert!(vec![ok, ok].validate().is_ok());
+    assert!(vec![ok, off].validate().is_err());
+    assert!(Vec::<Rect>::new().validate().is_ok());
+  }
+
+  #[test]
+  fn layout_profiles_check_margin_and_size() {
+    let profile = |value| serde_json::from_value::<LayoutProfile>(value).unwrap();
+    let placement = serde_json::json!({ "anchor": "center", "margin": 40 });
+    assert!(profile(serde_json::json!({ "placement": placement })).validate().is_ok());
+
+    let huge = serde_json::json!({ "anchor": "center", "margin": 999_999 });
+    assert!(profile(serde_json::json!({ "placement": huge })).validate().is_err());
+    let lowest = serde_json::json!({ "anchor": "center", "margin": i32::MIN });
+    assert!(profile(serde_json::json!({ "placement": lowest })).validate().is_err());
+    let zero_width = serde_json::json!({ "placement": placement, "width": 0 });
+    assert!(profile(zero_width).validate().is_err());
+  }
+
+  #[test]
+  fn actions_are_known_once_deserialized() {
+    assert!(ShortcutAction::ShowPanel.validate().is_ok());
+    assert!(serde_json::from_value::<ShortcutAction>(serde_json::json!("nope")).is_err());
+  }
+
+  #[test]
+  fn hotkey_configs_reject_bad_accelerators() {
+    assert!(HotkeyConfig::default().validate().is_ok());
+    let bad = serde_json::json!({ "show-panel": "Ctrl+Nope" });
+    let config: HotkeyConfig = serde_json::from_value(bad).unwrap();
+    assert!(matches!(config.validate(), Err(AppError::InvalidArgument(_))));
+  }
+}
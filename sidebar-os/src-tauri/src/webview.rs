@@ -0,0 +1,437 @@
+use std::sync::mpsc;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager, PhysicalPosition, Url};
+
+use crate::audit;
+use crate::error::{ok_status, AppError, CommandResult};
+use crate::journal;
+use crate::profiles;
+use crate::settings;
+use crate::state::AppState;
+
+pub const ALLOW_RELOAD_KEY: &str = "allow_webview_reload";
+pub const ALLOWED_ORIGINS_KEY: &str = "allowed_origins";
+// Deliberately not a whitelisted setting, so `save_settings` can't turn it on.
+pub const DEBUG_MODE_KEY: &str = "debug_mode";
+const SCRIPT_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn reload_allowed(app: &AppHandle) -> bool {
+  if cfg!(debug_assertions) {
+    return true;
+  }
+  profiles::settings_store(app)
+    .ok()
+    .and_then(|store| store.get(ALLOW_RELOAD_KEY))
+    .and_then(|value| value.as_bool())
+    .unwrap_or(settings::DEFAULT_ALLOW_WEBVIEW_RELOAD)
+}
+
+/// Whether debug mode is on, as last applied. Always false in release
+/// builds, whatever the store says.
+pub fn debug_mode(app: &AppHandle) -> bool {
+  cfg!(debug_assertions) && *app.state::<AppState>().debug_mode.lock().unwrap()
+}
+
+pub fn require_debug_mode(app: &AppHandle, what: &str) -> Result<(), String> {
+  if debug_mode(app) {
+    Ok(())
+  } else {
+    Err(format!("{} needs debug mode; call set_debug_mode first", what))
+  }
+}
+
+/// Debug mode on: devtools open and trace logging. Off: back to the usual
+/// debug-level logging with devtools closed.
+#[cfg(debug_assertions)]
+fn apply_debug_mode(app: &AppHandle, enabled: bool) {
+  *app.state::<AppState>().debug_mode.lock().unwrap() = enabled;
+  log::set_max_level(if enabled { log::LevelFilter::Trace } else { log::LevelFilter::Debug });
+  if let Some(window) = app.get_webview_window("panel") {
+    if enabled {
+      window.open_devtools();
+    } else {
+      window.close_devtools();
+    }
+    *app.state::<AppState>().devtools_open.lock().unwrap() = enabled;
+  }
+}
+
+/// Applies the stored debug mode during setup, after the log plugin is in.
+pub fn restore_debug_mode(app: &AppHandle) {
+  #[cfg(debug_assertions)]
+  {
+    let enabled = profiles::settings_store(app)
+      .ok()
+      .and_then(|store| store.get(DEBUG_MODE_KEY))
+      .and_then(|value| value.as_bool())
+      .unwrap_or(false);
+    if enabled {
+      log::info!("debug mode is on");
+    }
+    apply_debug_mode(app, enabled);
+  }
+  #[cfg(not(debug_assertions))]
+  let _ = app;
+}
+
+/// Turns debug mode on or off: devtools, `execute_js` and trace logging.
+/// Persisted, but only debug builds can enable it.
+#[tauri::command]
+pub fn set_debug_mode(app: AppHandle, enabled: bool) -> CommandResult {
+  audit::run(app.clone(), "set_debug_mode", move || {
+    log::info!("set_debug_mode: enabled={}", enabled);
+
+    if enabled && !cfg!(debug_assertions) {
+      return Err(AppError::PlatformUnsupported(
+        "Debug mode is only available in debug builds".to_string(),
+      ));
+    }
+    let store = profiles::settings_store(&app)?;
+    store.set(DEBUG_MODE_KEY, enabled);
+    store.save().map_err(|e| e.to_string())?;
+
+    #[cfg(debug_assertions)]
+    apply_debug_mode(&app, enabled);
+    Ok(ok_status())
+  })
+}
+
+/// True if `url`'s origin (scheme, host and port) is in `allowed`.
+fn origin_allowed(url: &Url, allowed: &[String]) -> bool {
+  let origin = url.origin().ascii_serialization();
+  allowed
+    .iter()
+    .filter_map(|entry| Url::parse(entry).ok())
+    .any(|entry| entry.origin().ascii_serialization() == origin)
+}
+
+#[tauri::command]
+pub fn reload_webview(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "reload_webview", move || {
+    log::info!("reload_webview invoked");
+
+    if !reload_allowed(&app) {
+      return Err("Webview reload is disabled; set allow_webview_reload to enable it".into());
+    }
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    window
+      .eval("window.location.reload()")
+      .map_err(|e| e.to_string())?;
+
+    Ok(ok_status())
+  })
+}
+
+#[tauri::command]
+pub fn navigate_webview(app: AppHandle, url: String) -> CommandResult {
+  audit::run(app.clone(), "navigate_webview", move || {
+    log::info!("navigate_webview: url={}", url);
+
+    let parsed = Url::parse(&url)
+      .map_err(|e| AppError::InvalidArgument(format!("Invalid URL {}: {}", url, e)))?;
+    let allowed: Vec<String> = profiles::settings_store(&app)
+      .map_err(|e| e.to_string())?
+      .get(ALLOWED_ORIGINS_KEY)
+      .and_then(|value| serde_json::from_value(value).ok())
+      .unwrap_or_default();
+
+    if !origin_allowed(&parsed, &allowed) {
+      return Err(format!("Origin not allowed: {}", parsed.origin().ascii_serialization()).into());
+    }
+
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    // Quote through JSON so the URL can't break out of the string literal.
+    let target = serde_json::to_string(parsed.as_str()).map_err(|e| e.to_string())?;
+    window
+      .eval(format!("window.location.href = {}", target))
+      .map_err(|e| e.to_string())?;
+
+    Ok(ok_status())
+  })
+}
+
+/// Wraps `script` so its completion value (awaited if it's a promise) is sent
+/// back through `execute_js_result` as JSON.
+fn bridge_script(id: u64, script: &str) -> Result<String, String> {
+  let source = serde_json::to_string(script).map_err(|e| e.to_string())?;
+  Ok(format!(
+    r#"(async () => {{
+  const reply = (ok, value) => window.__TAURI_INTERNALS__.invoke('execute_js_result', {{ id: {id}, ok, value }});
+  try {{
+    const result = await (0, eval)({source});
+    reply(true, JSON.stringify(result) ?? 'null');
+  }} catch (e) {{
+    reply(false, String(e));
+  }}
+}})()"#
+  ))
+}
+
+/// Runs `script` in the panel and returns its JSON-encoded result. Needs
+/// debug mode (so debug builds only); meant for automated UI testing.
+#[tauri::command]
+pub async fn execute_js(app: AppHandle, script: String) -> Result<String, AppError> {
+  let result = run_script(&app, script).await;
+  audit::finish(&app, "execute_js", result)
+}
+
+async fn run_script(app: &AppHandle, script: String) -> Result<String, AppError> {
+  log::info!("execute_js invoked ({} chars)", script.len());
+
+  if !cfg!(debug_assertions) {
+    return Err(AppError::PlatformUnsupported("execute_js disabled in release builds".to_string()));
+  }
+  require_debug_mode(app, "execute_js")?;
+
+  let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+  let (tx, rx) = mpsc::channel();
+  let id = {
+    let state = app.state::<AppState>();
+    let mut next = state.next_script_id.lock().map_err(|e| e.to_string())?;
+    *next += 1;
+    state.pending_scripts.lock().map_err(|e| e.to_string())?.insert(*next, tx);
+    *next
+  };
+
+  if let Err(e) = window.eval(bridge_script(id, &script)?) {
+    app.state::<AppState>().pending_scripts.lock().unwrap().remove(&id);
+    return Err(e.into());
+  }
+
+  let reply = tauri::async_runtime::spawn_blocking(move || rx.recv_timeout(SCRIPT_TIMEOUT))
+    .await
+    .map_err(|e| e.to_string())?;
+  app.state::<AppState>().pending_scripts.lock().unwrap().remove(&id);
+
+  let result = reply.map_err(|_| {
+    AppError::Timeout(format!("Script did not finish within {}s", SCRIPT_TIMEOUT.as_secs()))
+  })?;
+  Ok(result?)
+}
+
+/// Opens the panel's devtools, or closes them if open, and returns whether
+/// they're open now. Only in debug builds or with the `devtools` feature.
+#[tauri::command]
+pub fn toggle_devtools(app: AppHandle) -> Result<bool, AppError> {
+  audit::run(app.clone(), "toggle_devtools", move || {
+    log::info!("toggle_devtools invoked");
+
+    #[cfg(any(debug_assertions, feature = "devtools"))]
+    {
+      let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+      let state = app.state::<AppState>();
+      let mut open = state.devtools_open.lock().map_err(|e| e.to_string())?;
+      if *open {
+        window.close_devtools();
+      } else {
+        window.open_devtools();
+      }
+      *open = !*open;
+      Ok(*open)
+    }
+    #[cfg(not(any(debug_assertions, feature = "devtools")))]
+    {
+      let _ = app;
+      Err(AppError::PlatformUnsupported("Devtools are not available in this build".to_string()))
+    }
+  })
+}
+
+/// Every key in the active profile's store. Debug mode only.
+#[tauri::command]
+pub fn get_store_keys(app: AppHandle) -> Result<Vec<String>, AppError> {
+  audit::run(app.clone(), "get_store_keys", move || {
+    require_debug_mode(&app, "get_store_keys")?;
+    let store = profiles::settings_store(&app)?;
+    let mut keys = store.keys();
+    keys.sort();
+    Ok(keys)
+  })
+}
+
+/// Raw store value for `key`, None if unset. Debug mode only.
+#[tauri::command]
+pub fn get_store_value(app: AppHandle, key: String) -> Result<Option<serde_json::Value>, AppError> {
+  audit::run(app.clone(), "get_store_value", move || {
+    require_debug_mode(&app, "get_store_value")?;
+    let store = profiles::settings_store(&app)?;
+    Ok(store.get(&key))
+  })
+}
+
+/// Writes `value` under `key` as is, skipping the `save_settings` schema
+/// checks; for live tweaks while developing. Debug mode only.
+#[tauri::command]
+pub fn set_store_value(app: AppHandle, key: String, value: serde_json::Value) -> CommandResult {
+  audit::run(app.clone(), "set_store_value", move || {
+    log::info!("set_store_value: key={}", key);
+
+    require_debug_mode(&app, "set_store_value")?;
+    let store = profiles::settings_store(&app)?;
+    store.set(key.clone(), value);
+    store.save().map_err(|e| e.to_string())?;
+    let _ = journal::emit(&app, "settings-changed", serde_json::json!({ "keys": [key] }));
+    Ok(ok_status())
+  })
+}
+
+/// Opens the panel's devtools. Needs a debug build or debug mode.
+#[tauri::command]
+pub fn open_devtools(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "open_devtools", move || {
+    log::info!("open_devtools invoked");
+
+    if !cfg!(debug_assertions) && !debug_mode(&app) {
+      return Err("Devtools need a debug build or debug mode".into());
+    }
+    #[cfg(any(debug_assertions, feature = "devtools"))]
+    {
+      let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+      window.open_devtools();
+      *app.state::<AppState>().devtools_open.lock().map_err(|e| e.to_string())? = true;
+      Ok(ok_status())
+    }
+    #[cfg(not(any(debug_assertions, feature = "devtools")))]
+    Err(AppError::PlatformUnsupported("Devtools are not available in this build".to_string()))
+  })
+}
+
+/// Closes the panel's devtools; a no-op if they aren't open or this build
+/// has none.
+#[tauri::command]
+pub fn close_devtools(app: AppHandle) -> CommandResult {
+  audit::run(app.clone(), "close_devtools", move || {
+    log::info!("close_devtools invoked");
+
+    #[cfg(any(debug_assertions, feature = "devtools"))]
+    {
+      let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+      window.close_devtools();
+      *app.state::<AppState>().devtools_open.lock().map_err(|e| e.to_string())? = false;
+    }
+    #[cfg(not(any(debug_assertions, feature = "devtools")))]
+    let _ = app;
+    Ok(ok_status())
+  })
+}
+
+/// Asks the webview itself, so devtools closed from their own window count.
+#[tauri::command]
+pub fn is_devtools_open(app: AppHandle) -> Result<bool, AppError> {
+  audit::run(app.clone(), "is_devtools_open", move || {
+    #[cfg(any(debug_assertions, feature = "devtools"))]
+    {
+      let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+      Ok(window.is_devtools_open())
+    }
+    #[cfg(not(any(debug_assertions, feature = "devtools")))]
+    {
+      let _ = app;
+      Ok(false)
+    }
+  })
+}
+
+/// Receives results from scripts started by `execute_js`.
+#[tauri::command]
+pub fn execute_js_result(app: AppHandle, id: u64, ok: bool, value: String) -> CommandResult {
+  audit::run(app.clone(), "execute_js_result", move || {
+    if !cfg!(debug_assertions) {
+      let message = "execute_js disabled in release builds".to_string();
+      return Err(AppError::PlatformUnsupported(message));
+    }
+
+    let state = app.state::<AppState>();
+    let sender = state.pending_scripts.lock().map_err(|e| e.to_string())?.remove(&id);
+    match sender {
+      Some(tx) => {
+        let _ = tx.send(if ok { Ok(value) } else { Err(value) });
+      }
+      None => log::warn!("execute_js result for unknown or expired script {}", id),
+    }
+    Ok(ok_status())
+  })
+}
+
+/// Physical screen point for a logical point inside the webview. `inner` is
+/// where the content starts and `outer` the window frame; the difference is
+/// the title bar/border, which is zero for the borderless panel but not for
+/// other windows.
+pub fn webview_point_to_screen(
+  outer: PhysicalPosition<i32>,
+  inner: PhysicalPosition<i32>,
+  scale_factor: f64,
+  x: f64,
+  y: f64,
+) -> (i32, i32) {
+  let (offset_x, offset_y) = (inner.x - outer.x, inner.y - outer.y);
+  (
+    outer.x + offset_x + (x * scale_factor).round() as i32,
+    outer.y + offset_y + (y * scale_factor).round() as i32,
+  )
+}
+
+/// Converts a point from the webview (CSS pixels) to screen coordinates, e.g.
+/// to anchor a second window next to an element.
+#[tauri::command]
+pub fn webview_to_screen(app: AppHandle, x: f64, y: f64) -> Result<(i32, i32), AppError> {
+  audit::run(app.clone(), "webview_to_screen", move || {
+    let window = app.get_webview_window("panel").ok_or(AppError::WindowNotFound)?;
+    let outer = window.outer_position().map_err(|e| e.to_string())?;
+    let inner = window.inner_position().map_err(|e| e.to_string())?;
+    let scale_factor = window.scale_factor().map_err(|e| e.to_string())?;
+    Ok(webview_point_to_screen(outer, inner, scale_factor, x, y))
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn allowed() -> Vec<String> {
+    vec!["https://panel.example.com".to_string(), "http://localhost:1420".to_string()]
+  }
+
+  #[test]
+  fn matches_origin_not_path() {
+    let url = Url::parse("https://panel.example.com/v2/index.html?x=1").unwrap();
+    assert!(origin_allowed(&url, &allowed()));
+    let url = Url::parse("http://localhost:1420/").unwrap();
+    assert!(origin_allowed(&url, &allowed()));
+  }
+
+  #[test]
+  fn rejects_other_scheme_host_or_port() {
+    for url in [
+      "http://panel.example.com/",
+      "https://evil.example.com/",
+      "http://localhost:8080/",
+      "https://panel.example.com.evil.io/",
+    ] {
+      assert!(!origin_allowed(&Url::parse(url).unwrap(), &allowed()), "{}", url);
+    }
+  }
+
+  #[test]
+  fn bridge_quotes_the_script() {
+    let wrapped = bridge_script(7, "document.title = \"x'\"").unwrap();
+    assert!(wrapped.contains("id: 7"));
+    assert!(wrapped.contains(r#"eval)("document.title = \"x'\"")"#));
+  }
+
+  #[test]
+  fn webview_points_scale_and_include_the_title_bar() {
+    let outer = PhysicalPosition { x: 100, y: 200 };
+    let inner = PhysicalPosition { x: 100, y: 256 };
+    assert_eq!(webview_point_to_screen(outer, inner, 2.0, 10.0, 5.5), (120, 267));
+    assert_eq!(webview_point_to_screen(outer, outer, 1.0, 10.0, 5.0), (110, 205));
+  }
+
+  #[test]
+  fn empty_allowlist_rejects_everything() {
+    let url = Url::parse("https://panel.example.com/").unwrap();
+    assert!(!origin_allowed(&url, &[]));
+  }
+}
@@ -7,6 +7,7 @@ import { listen, type UnlistenFn } from '@tauri-apps/api/event'
 import { codex } from './codex'
 import { VoiceRecorder, runVoicePipeline, playAudio, synthesize } from './voiceAgent'
 import { emailAssistant } from './emailAssistant'
+import type { AppError } from './types'
 
 // Default user for email assistant; override via VITE_EMAIL_ASSISTANT_USER_ID to match seeded backend user.
 const EMAIL_ASSISTANT_USER_ID = import.meta.env.VITE_EMAIL_ASSISTANT_USER_ID || 'test-user-id'
@@ -25,6 +26,16 @@ const serializeLogArg = (value: unknown) => {
   }
 }
 
+// Commands reject with an AppError; Tauri's own APIs and JS code reject with strings or Errors
+const describeError = (error: unknown) => {
+  if (error && typeof error === 'object' && 'code' in error && 'message' in error) {
+    const { code, message } = error as AppError
+    return `${message} (${code})`
+  }
+  if (error instanceof Error) return error.message
+  return String(error)
+}
+
 const emitLog = (level: LogLevel, args: unknown[]) => {
   const message = args.map(serializeLogArg).join(' ')
   if (!message) return
@@ -191,6 +202,10 @@ export default function App() {
     windowModeRef.current = windowMode
   }, [windowMode])
 
+  // Set when the backend already sized and placed the window for a mode change
+  // (panel-state-changed), so the window mode effect doesn't lay it out again
+  const backendLaidOutRef = useRef(false)
+
   // Dedupe rapid successive toggle events (from multiple listeners/platform quirks)
   const lastToggleAtRef = useRef<number>(0)
   const shouldHandleToggle = () => {
@@ -230,9 +245,13 @@ export default function App() {
     initCodex()
   }, [])
 
+  // Backend can lock dragging (set_window_movable); only macOS enforces it natively
+  const movableRef = useRef(true)
+
   // Programmatic drag helper (reliable across platforms)
   const startWindowDrag = async () => {
     if (!isTauriEnv) return
+    if (!movableRef.current) return
     try {
       const win = getCurrentWebviewWindow()
       await win.startDragging()
@@ -306,6 +325,63 @@ export default function App() {
     return () => window.removeEventListener('keydown', onKey)
   }, [showDashboard])
 
+  // Esc with no menu or dashboard to close goes to the backend, which acts on
+  // it per the escape_action setting - desktop only
+  useEffect(() => {
+    if (!isTauriEnv || showProjectsMenu || showDashboard) return
+    const onKey = (e: KeyboardEvent) => {
+      if (e.key !== 'Escape') return
+      e.preventDefault()
+      invoke('handle_escape').catch((error) => logError('handle_escape failed', error))
+    }
+    window.addEventListener('keydown', onKey)
+    return () => window.removeEventListener('keydown', onKey)
+  }, [showProjectsMenu, showDashboard])
+
+  // While the backend captures a hotkey, the next chord pressed here goes to it
+  // instead of the app; nothing is grabbed system-wide - desktop only
+  useEffect(() => {
+    if (!isTauriEnv) return
+    const modifierCodes = ['Shift', 'Control', 'Alt', 'Meta', 'OS']
+    const onKey = (e: KeyboardEvent) => {
+      e.preventDefault()
+      e.stopPropagation()
+      if (modifierCodes.some(m => e.code.startsWith(m))) return
+      if (e.key === 'Escape' && !e.ctrlKey && !e.altKey && !e.metaKey) {
+        invoke('cancel_hotkey_capture').catch((error) => logError('cancel_hotkey_capture failed', error))
+        return
+      }
+      const parts: string[] = []
+      if (e.ctrlKey) parts.push('Ctrl')
+      if (e.altKey) parts.push('Alt')
+      if (e.shiftKey) parts.push('Shift')
+      if (e.metaKey) parts.push('Meta')
+      parts.push(e.code)
+      invoke('submit_hotkey_capture', { accelerator: parts.join('+') })
+        .catch((error) => logInfo('Chord not captured:', describeError(error)))
+    }
+    const stopCapturing = () => window.removeEventListener('keydown', onKey, true)
+    const unlisteners: UnlistenFn[] = []
+    let disposed = false
+    const setup = async () => {
+      const started = await listen('hotkey-capture-started', () => {
+        stopCapturing()
+        // Capture phase, so the chord never reaches the app's own key handlers
+        window.addEventListener('keydown', onKey, true)
+      })
+      const captured = await listen('hotkey-captured', stopCapturing)
+      const ended = await listen('hotkey-capture-ended', stopCapturing)
+      unlisteners.push(started, captured, ended)
+      if (disposed) unlisteners.forEach(u => u())
+    }
+    setup().catch((error) => logError('Failed to listen for hotkey capture', error))
+    return () => {
+      disposed = true
+      stopCapturing()
+      unlisteners.forEach(u => u())
+    }
+  }, [])
+
   // Cleanup record popup timer
   useEffect(() => {
     return () => {
@@ -324,6 +400,11 @@ export default function App() {
       // In web mode, skip Tauri window manipulation to avoid errors
       return
     }
+    if (backendLaidOutRef.current) {
+      backendLaidOutRef.current = false
+      logInfo('Window mode changed to:', windowMode, '(already laid out by backend)')
+      return
+    }
     const updateWindowSize = async () => {
       logInfo('Window mode changed to:', windowMode)
       const win = getCurrentWebviewWindow()
@@ -471,7 +552,7 @@ export default function App() {
         }
       } catch (error) {
         logError('!!!!! ERROR updating window:', error)
-        alert('ERROR: ' + error)
+        alert('ERROR: ' + describeError(error))
       }
     }
 
@@ -598,10 +679,37 @@ export default function App() {
           setUseCustomPosition(false) // Hotkeys/tray always use default positions
           setWindowMode(prev => (prev === 'expanded' ? prev : 'expanded'))
         })
+        // Cmd+1 handled natively; just follow the backend's mode
+        const unlistenState = await listen<{ mode: WindowMode; visible: boolean }>(
+          'panel-state-changed',
+          (event) => {
+            logInfo('Received panel-state-changed from backend:', event.payload.mode)
+            // Only flag real changes; an unchanged mode doesn't re-run the effect
+            if (event.payload.mode !== windowModeRef.current) backendLaidOutRef.current = true
+            setUseCustomPosition(false)
+            setWindowMode(event.payload.mode)
+          }
+        )
+        const unlistenShortcutFailed = await listen<{ accelerator: string; error: string }>(
+          'shortcut-registration-failed',
+          (event) => {
+            logError('Global shortcut failed to register:', event.payload.accelerator, event.payload.error)
+          }
+        )
+        const unlistenMovable = await listen<{ movable: boolean }>('window-movable-changed', (event) => {
+          movableRef.current = event.payload.movable
+        })
         if (disposed) {
           unlistenEvent()
+          unlistenState()
+          unlistenShortcutFailed()
+          unlistenMovable()
         } else {
-          unlisteners.push(unlistenEvent)
+          unlisteners.push(unlistenEvent, unlistenState, unlistenShortcutFailed, unlistenMovable)
+          const flags = await invoke<{ is_movable: boolean }>('get_window_flags')
+          movableRef.current = flags.is_movable
+          // Backend holds setup-time events until we're listening
+          await invoke('frontend_ready')
         }
       } catch (error) {
         logError('Error wiring window listeners', error)
@@ -18,6 +18,13 @@ export interface ConversationItem {
   tokensOut?: number;
 }
 
+// What a rejected invoke() carries when the command returned an AppError
+export interface AppError {
+  code: string;
+  message: string;
+  details?: unknown;
+}
+
 // Window position storage
 export interface WindowPosition {
   x: number;